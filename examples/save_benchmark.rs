@@ -0,0 +1,94 @@
+//! Times saving and loading a 1,000-location world in each save format.
+//!
+//! cargo run --release --example save_benchmark
+
+use std::time::{Duration, Instant};
+
+use llm_text_adventure::model::{Actor, Item, ItemProperties, ItemState, ItemType, SaveFormat, WorldState};
+use llm_text_adventure::save::{FileSystemStorage, SaveManager};
+use llm_text_adventure::worldgen::fallback_location;
+
+const SIDE: i32 = 25;
+const LEVELS: i32 = 2;
+const RUNS: usize = 5;
+
+/// A 25x20x2 world where every tile has an item and every fifth an actor,
+/// with descriptions about as long as the LLM writes them.
+fn generated_world() -> WorldState {
+    let mut world = WorldState::new();
+    for z in 0..LEVELS {
+        for y in 0..20 {
+            for x in 0..SIDE {
+                let pos = (x, y, -z);
+                let mut loc = fallback_location(pos);
+                loc.name = format!("Clearing {}-{}-{}", x, y, z);
+                loc.description = "Tall grass sways around a ring of weathered standing stones. \
+                    A narrow path winds off between the trees, and somewhere nearby water trickles over rocks."
+                    .to_string();
+                loc.visited = true;
+
+                let item_id = format!("stone_{}_{}_{}", x, y, z);
+                world.items.insert(item_id.clone(), Item {
+                    id: item_id.clone(),
+                    name: "Smooth Stone".to_string(),
+                    description: "A palm-sized stone worn smooth by the river.".to_string(),
+                    item_type: ItemType::Material,
+                    state: ItemState::Normal,
+                    properties: ItemProperties { value: Some(1), weight: Some(1), ..Default::default() },
+                });
+                loc.items.push(item_id);
+
+                if (x + y) % 5 == 0 {
+                    let actor_id = format!("wanderer_{}_{}_{}", x, y, z);
+                    world.actors.insert(actor_id.clone(), Actor {
+                        id: actor_id.clone(),
+                        name: "Wanderer".to_string(),
+                        description: "A traveller in a dusty cloak.".to_string(),
+                        current_pos: pos,
+                        inventory: Vec::new(),
+                        money: 3,
+                        faction: None,
+                    });
+                    loc.actors.push(actor_id);
+                }
+                world.locations.insert(pos, loc);
+            }
+        }
+    }
+    world
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn main() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("save-benchmark-{}", std::process::id()));
+    let manager = SaveManager::with_storage(Box::new(FileSystemStorage::new(dir.clone())));
+    let mut world = generated_world();
+    println!("World: {} locations, {} items, {} actors", world.locations.len(), world.items.len(), world.actors.len());
+    println!("{:<6} {:>10} {:>10} {:>10}", "format", "size", "save", "load");
+
+    for format in [SaveFormat::Json, SaveFormat::Gzip] {
+        world.save_format = format;
+        let filename = format!("bench-{}.json", format);
+        let (mut saves, mut loads) = (Vec::new(), Vec::new());
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            manager.save_game(&filename, &world)?;
+            saves.push(start.elapsed());
+
+            let start = Instant::now();
+            let (loaded, _) = manager.load_save(&filename)?;
+            loads.push(start.elapsed());
+            assert_eq!(loaded.locations.len(), world.locations.len());
+        }
+        let size = std::fs::metadata(dir.join(&filename))?.len();
+        println!("{:<6} {:>8}KB {:>8.1}ms {:>8.1}ms", format, size / 1024,
+            median(saves).as_secs_f64() * 1000.0, median(loads).as_secs_f64() * 1000.0);
+    }
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
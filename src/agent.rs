@@ -1,9 +1,19 @@
-use crate::model::{WorldState, Item, Location, ItemState, ItemProperties, ItemType, Combatant, StatusType, CombatState, StatusEffect};
+use crate::model::{WorldState, Item, Location, ItemState, ItemProperties, ItemType, Combatant, StatusType, CombatState, StatusEffect, Exit, direction_offset, get_opposite_direction, describe_level, Quest, QuestStatus, QuestObjective, ObjectiveGoal, COMPASS_DIRECTIONS};
 use crate::tools::{ToolCall, ToolResult, ToolFunction, get_tool_definitions};
 use crate::llm::LlmClient;
+use crate::worldgen;
+use crate::clock::{self, ScheduledEvent};
+use crate::faction::{self, Disposition, Faction};
+use crate::magic::{self, Spell, SpellEffect, Utility};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+/// Journal turns shown to the DM so it remembers what just happened.
+const RECENT_TURNS_IN_PROMPT: usize = 5;
+
+/// Longest stretch of a past turn's narrative quoted in the prompt.
+const RECENT_NARRATIVE_CHARS: usize = 300;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmMessage {
     pub role: String,
@@ -26,6 +36,9 @@ pub struct LlmRequest {
 pub struct AgentResponse {
     pub narrative: String,
     pub suggested_actions: Vec<String>,
+    /// What the tools made happen besides the narrative: fired events, quest
+    /// progress, defeats and discoveries, for the journal.
+    pub notes: Vec<String>,
 }
 
 pub struct Agent {
@@ -33,6 +46,7 @@ pub struct Agent {
     world: WorldState,
     overall_timeout_seconds: u64,
     turn_narrative: Option<String>,
+    turn_notes: Vec<String>,
     debug_log: Vec<String>,
 }
 
@@ -43,6 +57,7 @@ impl Agent {
             world,
             overall_timeout_seconds: 60,
             turn_narrative: None,
+            turn_notes: Vec::new(),
             debug_log: Vec::new(),
         }
     }
@@ -74,6 +89,7 @@ impl Agent {
         ];
 
         self.turn_narrative = None;
+        self.turn_notes.clear();
 
         let tools = get_tool_definitions();
         let tool_schemas: Vec<serde_json::Value> = tools
@@ -104,6 +120,7 @@ impl Agent {
             return Ok(AgentResponse {
                 narrative: "[Timeout: The game took too long to respond]".to_string(),
                 suggested_actions: vec!["look around".to_string()],
+                notes: Vec::new(),
             });
         }
 
@@ -121,14 +138,8 @@ impl Agent {
             }
         }
 
-        if let Some(turn_narrative) = &self.turn_narrative {
-            let narrative = turn_narrative.clone();
-            self.log(&format!("Narrative length: {} chars", narrative.len()));
-            let suggested_actions = self.extract_suggested_actions(&narrative);
-            return Ok(AgentResponse {
-                narrative,
-                suggested_actions,
-            });
+        if let Some(narrative) = self.turn_narrative.clone() {
+            return Ok(self.respond(narrative));
         }
 
         if response_tool_calls.is_some() && response_content.is_none() {
@@ -157,24 +168,35 @@ impl Agent {
 
             if let Ok(narrative_response) = self.llm_client.send_chat_request(&narrative_request).await {
                 if let Some(content) = narrative_response.get("content").and_then(|c| c.as_str()) {
-                    let narrative = content.to_string();
-                    self.log(&format!("Narrative length: {} chars", narrative.len()));
-                    let suggested_actions = self.extract_suggested_actions(&narrative);
-                    return Ok(AgentResponse {
-                        narrative,
-                        suggested_actions,
-                    });
+                    return Ok(self.respond(content.to_string()));
                 }
             }
         }
 
         let narrative = response_content.map(|c| c.to_string()).unwrap_or_default();
+        Ok(self.respond(narrative))
+    }
+
+    /// Finish the turn with its narrative, the options it suggests and the
+    /// notes the tools gathered.
+    fn respond(&mut self, narrative: String) -> AgentResponse {
         self.log(&format!("Narrative length: {} chars", narrative.len()));
         let suggested_actions = self.extract_suggested_actions(&narrative);
-        Ok(AgentResponse {
+        AgentResponse {
             narrative,
             suggested_actions,
-        })
+            notes: std::mem::take(&mut self.turn_notes),
+        }
+    }
+
+    /// Add each line to a tool result on its own line, and keep it for the
+    /// turn's journal entry.
+    fn append_notes(&mut self, text: &mut String, lines: Vec<String>) {
+        for line in lines {
+            text.push('\n');
+            text.push_str(&line);
+            self.turn_notes.push(line);
+        }
     }
 
     fn build_system_message(&self) -> LlmMessage {
@@ -199,23 +221,72 @@ impl Agent {
             .filter_map(|id| self.world.items.get(id).map(|i| i.name.clone()))
             .collect();
 
-        let (x, y) = self.world.current_pos;
-        let adjacent_info = self.get_adjacent_info(x, y);
+        let (x, y, z) = self.world.current_pos;
+        let adjacent_info = self.get_adjacent_info(x, y, z);
+
+        let region_info = match self.world.region_at((x, y, z)) {
+            Some(region) if region.lore.is_empty() => format!("{} - {}", region.name, region.description),
+            Some(region) => format!("{} - {} Lore: {}", region.name, region.description, region.lore),
+            None => "None (unclaimed wilds)".to_string(),
+        };
+
+        let people: Vec<String> = current_loc.actors.iter()
+            .filter_map(|id| self.world.actors.get(id))
+            .map(|a| {
+                let faction = a.faction.as_deref()
+                    .and_then(|f| self.world.factions.get(f))
+                    .map(|f| format!("{}, ", f.name))
+                    .unwrap_or_default();
+                format!("{} ({}; {}{})", a.name, a.id, faction, self.world.disposition_of(&a.id))
+            })
+            .collect();
+        let people_here = if people.is_empty() { "Nobody".to_string() } else { people.join(", ") };
+
+        let spells: Vec<String> = self.world.player.known_spells.iter()
+            .filter_map(|id| magic::find_spell(&self.world, id))
+            .map(|s| format!("{} ({} mana)", s.id, s.mana_cost))
+            .collect();
+        let known_spells = if spells.is_empty() { "None".to_string() } else { spells.join(", ") };
+
+        let player_status = if self.world.player.status_effects.is_empty() {
+            "Fine".to_string()
+        } else {
+            self.world.player.status_effects.iter()
+                .map(|e| format!("{:?}({}t)", e.effect_type, e.duration))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
 
         let mut context = format!(
             r#"You are Dungeon Master for a text adventure game.
- Current Location: {} at ({}, {})
+ Current Location: {} at ({}, {}, {}) on {}
+ Region: {}
+ Terrain: {}
+ Time: {}
+ Weather: {}
  Description: {}
  Items here: {:?}
+ People here: {}
  Player Inventory: {:?}
  Player Money: {}
+ Player Status: {}
+ Player Mana: {}/{}
+ Known Spells: {}
 
  Adjacent Areas: {}"#,
-            current_loc.name, x, y,
+            current_loc.name, x, y, z, describe_level(z),
+            region_info,
+            self.world.terrain_at((x, y, z)).summary(),
+            self.world.clock.describe(),
+            self.world.weather.summary(),
             current_loc.description,
             visible_items,
+            people_here,
             player_inventory,
             self.world.player.money,
+            player_status,
+            self.world.player.mana, self.world.player.max_mana,
+            known_spells,
             adjacent_info
         );
 
@@ -240,7 +311,9 @@ impl Agent {
  Combatants:
  {}
 
- Combat Actions: start_combat, attack_actor, defend, flee, use_item_in_combat, end_turn"#,
+ Combat Actions: start_combat, attack_actor, defend, flee, use_item_in_combat, cast_spell, end_turn
+ Casting a spell ends the player's turn; do not call end_turn after cast_spell.
+ If the player drops to 0 HP the engine handles their defeat; narrate the fall and nothing after it."#,
                 self.world.combat.round_number,
                 self.world.combat.combatants.get(self.world.combat.current_turn_index)
                     .map(|c| c.id.as_str())
@@ -249,20 +322,75 @@ impl Agent {
             ));
         }
 
+        let upcoming: Vec<String> = self.world.events.iter()
+            .take_while(|e| e.at <= self.world.clock.minutes + clock::MINUTES_PER_DAY)
+            .map(|e| format!("- {} at {}: {}", e.id, clock::WorldClock { minutes: e.at }.time_of_day(), e.description))
+            .collect();
+        if !upcoming.is_empty() {
+            context.push_str(&format!("\n\n Upcoming Events (next 24 hours):\n {}", upcoming.join("\n ")));
+        }
+
+        if !self.world.factions.is_empty() {
+            let mut standings: Vec<String> = self.world.factions.values()
+                .map(|f| {
+                    let score = self.world.reputation_with(&f.id);
+                    format!("- {} ({}): {} ({})", f.id, f.name, score, Disposition::from_reputation(score))
+                })
+                .collect();
+            standings.sort();
+            context.push_str(&format!("\n\n Faction Reputation:\n {}", standings.join("\n ")));
+        }
+
+        let mut active_quests: Vec<&Quest> = self.world.quests.values()
+            .filter(|q| q.status == QuestStatus::Active)
+            .collect();
+        if !active_quests.is_empty() {
+            active_quests.sort_by(|a, b| a.id.cmp(&b.id));
+            let quest_info: Vec<String> = active_quests.iter()
+                .map(|q| {
+                    let objectives = q.objectives.iter().enumerate()
+                        .map(|(i, o)| format!("{}. [{}] {}", i, if o.done { "x" } else { " " }, o.description))
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    format!("- {} ({}): {} | Objectives: {}", q.id, q.title, q.description, objectives)
+                })
+                .collect();
+            context.push_str(&format!("\n\n Active Quests:\n {}", quest_info.join("\n ")));
+        }
+
+        let recent: Vec<String> = self.world.recent_turns(RECENT_TURNS_IN_PROMPT).iter()
+            .map(|entry| {
+                let mut narrative: String = entry.narrative.split_whitespace().collect::<Vec<_>>().join(" ");
+                if narrative.chars().count() > RECENT_NARRATIVE_CHARS {
+                    narrative = format!("{}...", narrative.chars().take(RECENT_NARRATIVE_CHARS).collect::<String>());
+                }
+                let events = if entry.events.is_empty() { String::new() } else { format!(" [{}]", entry.events.join("; ")) };
+                format!("- Turn {} at {}: \"{}\" -> {}{}", entry.turn, entry.location, entry.input, narrative, events)
+            })
+            .collect();
+        if !recent.is_empty() {
+            context.push_str(&format!("\n\n Recent Turns (oldest first):\n {}", recent.join("\n ")));
+        }
+
         context.push_str(&format!(
             r#"
 
  RULES:
  1. You can call MULTIPLE tools in ONE response.
  2. When calling tools: The narrative you generate should describe what happens AFTER tools execute.
- 3. For movement: Use move_to(direction). New tiles are auto-generated if needed.
+ 3. For movement: Use move_to(direction). New tiles are auto-generated if needed. Exits marked blocked, locked or hidden cannot be walked through. Use up/down for stairs, ladders and shafts, and enter/exit for going into and out of buildings; these need an exit, so use open_exit first to add the stairs or doorway.
  4. For describing location: Use update_location_description(text) to permanently change location's description.
- 5. For responding to player: Use generate_turn_narrative(text) if you want full control, or let the system generate narrative after your tools execute.
- 6. If you call tools WITHOUT using generate_turn_narrative or adding narrative content, the system will ask you to describe what happened with the updated world state.
- 7. End your response with 3-5 suggested actions (in the LLM content, not as a tool).
- 8. NEVER generate JSON text - use tool calls instead.
-
- Available tools: move_to, update_location_description, generate_turn_narrative, create_item, add_item_to_inventory, remove_item_from_inventory, add_item_to_location, remove_item_from_location, use_item, equip_item, unequip_item, combine_items, break_item, add_item_to_container, remove_item_to_container, start_combat, attack_actor, defend, flee, use_item_in_combat, end_turn"#
+ 5. For walls, collapsed tunnels, locked doors and secret doors: Use block_exit(direction, kind). Use open_exit(direction) to unlock, reveal or clear them.
+ 6. For responding to player: Use generate_turn_narrative(text) if you want full control, or let the system generate narrative after your tools execute.
+ 7. If you call tools WITHOUT using generate_turn_narrative or adding narrative content, the system will ask you to describe what happened with the updated world state.
+ 8. End your response with 3-5 suggested actions (in the LLM content, not as a tool).
+ 9. NEVER generate JSON text - use tool calls instead.
+ 10. Time passes as the player moves (15 minutes a tile) and fights. Use pass_time when they rest, sleep or wait, and schedule_event for things that happen at set times. Describe light and activity to suit the time of day, and keep narration consistent with the Weather line; it changes on its own.
+ 11. For factions: Use create_faction and set_actor_faction to organise people, adjust_reputation when the player helps or wrongs a faction, and trade_item for any purchase or sale (the engine sets prices). Play each person according to their disposition; hostile members refuse to trade. Defeating a member costs reputation automatically.
+ 12. For magic: Use learn_spell when the player studies a tome or is taught (built-in spells: firebolt, mend, frost_bind, light, knock, reveal; give an effect to invent a new one) and cast_spell whenever they cast. The engine checks mana and applies the effect; mana recovers with time.
+ 13. For quests: Use create_quest when someone asks the player for help. Fetch, reach and defeat objectives tick off by themselves; use update_quest(complete_objective) for custom ones and complete_quest when the player turns a quest in.
+
+ Available tools: move_to, open_exit, block_exit, update_location_description, generate_turn_narrative, create_item, add_item_to_inventory, remove_item_from_inventory, add_item_to_location, remove_item_from_location, use_item, equip_item, unequip_item, combine_items, break_item, add_item_to_container, remove_item_to_container, start_combat, attack_actor, defend, flee, use_item_in_combat, end_turn, create_quest, update_quest, complete_quest, pass_time, schedule_event, cancel_event, create_faction, set_actor_faction, adjust_reputation, trade_item, learn_spell, cast_spell"#
         ));
 
         LlmMessage {
@@ -287,20 +415,28 @@ impl Agent {
         }
     }
 
-    fn get_adjacent_info(&self, x: i32, y: i32) -> String {
-        let directions = [
-            ("north", x, y + 1),
-            ("south", x, y - 1),
-            ("east", x + 1, y),
-            ("west", x - 1, y),
-        ];
+    fn get_adjacent_info(&self, x: i32, y: i32, z: i32) -> String {
+        let exits = self.world.locations.get(&(x, y, z)).map(|l| &l.exits);
+
+        // Level changes only exist where a stair or doorway has been placed.
+        let mut directions: Vec<&str> = COMPASS_DIRECTIONS.to_vec();
+        directions.extend(["up", "down", "enter", "exit"].iter()
+            .filter(|dir| exits.is_some_and(|e| e.contains_key(**dir))));
 
         directions.iter()
-            .map(|(dir, dx, dy)| {
-                let status = self.world.locations.get(&(*dx, *dy))
+            .map(|dir| {
+                let target = exits.and_then(|e| e.get(*dir)).and_then(|e| e.target())
+                    .or_else(|| direction_offset(dir).map(|(dx, dy, dz)| (x + dx, y + dy, z + dz)));
+                let status = target.and_then(|pos| self.world.locations.get(&pos))
                     .map(|l| l.name.as_str())
                     .unwrap_or("unexplored");
-                format!("{}: {}", dir, status)
+                match exits.and_then(|e| e.get(*dir)) {
+                    Some(Exit::Blocked) => format!("{}: blocked", dir),
+                    Some(Exit::Door { locked: true, .. }) => format!("{}: {} (locked door)", dir, status),
+                    Some(Exit::Hidden { .. }) => format!("{}: {} (hidden passage)", dir, status),
+                    Some(Exit::OneWay { .. }) => format!("{}: {} (one-way)", dir, status),
+                    _ => format!("{}: {}", dir, status),
+                }
             })
             .collect::<Vec<_>>()
             .join(", ")
@@ -313,6 +449,8 @@ impl Agent {
 
 let result = match name.as_str() {
             "move_to" => self.execute_move_to(arguments).await?,
+            "open_exit" => self.execute_open_exit(arguments)?,
+            "block_exit" => self.execute_block_exit(arguments)?,
             "update_location_description" => self.execute_update_location_description(arguments)?,
             "generate_turn_narrative" => self.execute_generate_turn_narrative(arguments)?,
             "create_item" => self.execute_create_item(arguments)?,
@@ -334,12 +472,32 @@ let result = match name.as_str() {
             "use_item_in_combat" => self.execute_use_item_in_combat(arguments)?,
             "end_turn" => self.execute_end_turn(arguments)?,
             "inspect_object" => self.execute_inspect_object(arguments)?,
+            "create_quest" => self.execute_create_quest(arguments)?,
+            "update_quest" => self.execute_update_quest(arguments)?,
+            "complete_quest" => self.execute_complete_quest(arguments)?,
+            "pass_time" => self.execute_pass_time(arguments)?,
+            "schedule_event" => self.execute_schedule_event(arguments)?,
+            "cancel_event" => self.execute_cancel_event(arguments)?,
+            "create_faction" => self.execute_create_faction(arguments)?,
+            "set_actor_faction" => self.execute_set_actor_faction(arguments)?,
+            "adjust_reputation" => self.execute_adjust_reputation(arguments)?,
+            "trade_item" => self.execute_trade_item(arguments)?,
+            "learn_spell" => self.execute_learn_spell(arguments)?,
+            "cast_spell" => self.execute_cast_spell(arguments)?,
             _ => return Err(anyhow::anyhow!("Unknown tool: {}", name)),
         };
 
+        let mut messages = self.world.record_defeats();
+        messages.extend(self.world.check_quests());
+        let mut content = result;
+        for message in &messages {
+            self.log(message);
+        }
+        self.append_notes(&mut content, messages);
+
         Ok(ToolResult {
             tool_call_id: tool_call.id.clone(),
-            content: result,
+            content,
         })
     }
 
@@ -563,97 +721,24 @@ let result = match name.as_str() {
         let args: serde_json::Value = serde_json::from_str(arguments)?;
         let direction = args["direction"].as_str().ok_or_else(|| anyhow::anyhow!("Missing direction"))?;
 
-        let (current_x, current_y) = self.world.current_pos;
-        let target_pos = match direction {
-            "north" => (current_x, current_y + 1),
-            "south" => (current_x, current_y - 1),
-            "east" => (current_x + 1, current_y),
-            "west" => (current_x - 1, current_y),
-            _ => return Err(anyhow::anyhow!("Invalid direction")),
-        };
+        // Level changes need a stair or doorway, which the DM places with open_exit.
+        let target_pos = self.world.resolve_move(direction).map_err(|reason| anyhow::anyhow!(reason))?;
 
-        let opposite = get_opposite_direction(direction);
+        if self.world.locations.contains_key(&target_pos) {
+            let from = self.world.current_pos;
+            self.world.link_exits(from, target_pos, direction);
+        } else {
+            self.log(&format!("Generating new location at ({}, {}, {}) heading {}", target_pos.0, target_pos.1, target_pos.2, direction));
 
-        if !self.world.locations.contains_key(&target_pos) {
-            self.log(&format!("Generating new location at ({}, {}) heading {}", target_pos.0, target_pos.1, direction));
-
-            let current_loc = self.world.locations.get(&self.world.current_pos)
-                .ok_or_else(|| anyhow::anyhow!("Current location not found"))?;
-
-            let prompt = format!(
-                r#"Current Location: {} at ({}, {})
-Description: {}
-
-The player is heading {} toward coordinates ({}, {}).
-This grid cell is currently EMPTY and needs to be generated.
-
-Create a new location at ({}, {}) that fits thematically with current location.
-IMPORTANT: All exits must be null (blocked). The game will create actual exit connections automatically.
-
-Return ONLY a valid JSON object:
-{{
-  "name": "Location name",
-  "description": "Description of what the player sees",
-  "image_prompt": "Visual description for generating an image",
-  "exits": {{"north": null, "south": null, "east": null, "west": null}},
-  "items": [],
-  "actors": []
-}}
-
-CRITICAL:
-- exits MUST be null objects (blocked), NOT strings or booleans
-- items MUST be an empty array []
-- actors MUST be an empty array []
-- NO narrative text, NO extra commentary
-
-Just the JSON. Nothing else."#,
-                current_loc.name,
-                current_x,
-                current_y,
-                current_loc.description,
-                direction, target_pos.0, target_pos.1,
-                target_pos.0, target_pos.1
-            );
-
-            let system_prompt = "You are a world generator for a text adventure game. Create interesting, thematically consistent locations. You MUST output valid JSON only.";
-
-            match self.llm_client.generate_location(system_prompt, &prompt).await {
-                Ok(mut location) => {
-                    location.visited = true;
-                    self.world.locations.insert(target_pos, location.clone());
-                    self.log(&format!("Created location at ({}, {}): {}", target_pos.0, target_pos.1, location.name));
-
-                    if let Some(current_loc) = self.world.locations.get_mut(&self.world.current_pos) {
-                        current_loc.exits.insert(direction.to_string(), Some(target_pos));
-                    }
-                    if let Some(new_loc) = self.world.locations.get_mut(&target_pos) {
-                        new_loc.exits.insert(opposite, Some(self.world.current_pos));
-                    }
-                }
-                Err(e) => {
-                    self.log(&format!("Failed to generate location: {}", e));
-
-                    let fallback_loc = Location {
-                        name: format!("Mysterious area ({}, {})", target_pos.0, target_pos.1),
-                        description: "A mysterious place that appeared suddenly.".to_string(),
-                        items: vec![],
-                        actors: vec![],
-                        exits: std::collections::HashMap::new(),
-                        cached_image_path: None,
-                        image_prompt: "A mysterious location with undefined characteristics.".to_string(),
-                        visited: true,
-                    };
-
-                    self.world.locations.insert(target_pos, fallback_loc.clone());
-                    self.log(&format!("Used fallback location at ({}, {})", target_pos.0, target_pos.1));
-
-                    if let Some(current_loc) = self.world.locations.get_mut(&self.world.current_pos) {
-                        current_loc.exits.insert(direction.to_string(), Some(target_pos));
-                    }
-                    if let Some(new_loc) = self.world.locations.get_mut(&target_pos) {
-                        new_loc.exits.insert(opposite, Some(self.world.current_pos));
-                    }
-                }
+            let report = worldgen::generate_location(&self.llm_client, &mut self.world, target_pos, direction).await;
+            for note in &report.notes {
+                self.log(note);
+            }
+            if report.used_fallback {
+                self.log(&format!("Used fallback location at ({}, {}, {})", target_pos.0, target_pos.1, target_pos.2));
+            } else {
+                self.log(&format!("Created location at ({}, {}, {}): {}", target_pos.0, target_pos.1, target_pos.2, report.name));
+                self.turn_notes.push(format!("Discovered {}.", report.name));
             }
         }
 
@@ -663,9 +748,89 @@ Just the JSON. Nothing else."#,
         }
 
         let loc_name = self.world.locations.get(&target_pos)
-            .map(|l| l.name.as_str())
-            .unwrap_or("Unknown");
-        Ok(format!("Moved {} to ({}, {}) - {}", direction, target_pos.0, target_pos.1, loc_name))
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let mut result = format!("Moved {} to ({}, {}, {}) - {}", direction, target_pos.0, target_pos.1, target_pos.2, loc_name);
+        let events = self.world.advance_time(clock::MINUTES_PER_STEP);
+        self.append_notes(&mut result, events);
+        Ok(result)
+    }
+
+    fn exit_target(&self, direction: &str) -> Result<(i32, i32, i32)> {
+        let existing = self.world.locations.get(&self.world.current_pos)
+            .and_then(|l| l.exits.get(direction))
+            .and_then(|e| e.target());
+        if let Some(target) = existing {
+            return Ok(target);
+        }
+        let (dx, dy, dz) = direction_offset(direction)
+            .ok_or_else(|| anyhow::anyhow!("Invalid direction"))?;
+        let (x, y, z) = self.world.current_pos;
+        Ok((x + dx, y + dy, z + dz))
+    }
+
+    fn set_exit(&mut self, pos: (i32, i32, i32), direction: &str, exit: Exit) {
+        if let Some(loc) = self.world.locations.get_mut(&pos) {
+            loc.exits.insert(direction.to_string(), exit);
+        }
+    }
+
+    fn execute_open_exit(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let direction = args["direction"].as_str().ok_or_else(|| anyhow::anyhow!("Missing direction"))?;
+        let one_way = args["one_way"].as_bool().unwrap_or(false);
+
+        let here = self.world.current_pos;
+        let current = self.world.locations.get(&here)
+            .ok_or_else(|| anyhow::anyhow!("Current location not found"))?
+            .exits.get(direction).cloned();
+
+        if let Some(Exit::Door { locked: true, key_id: Some(key_id), .. }) = &current {
+            if !self.world.player.inventory.contains(key_id) {
+                return Err(anyhow::anyhow!("The door {} needs key {}", direction, key_id));
+            }
+        }
+
+        let to = self.exit_target(direction)?;
+        let opposite = get_opposite_direction(direction);
+        if one_way {
+            self.set_exit(here, direction, Exit::OneWay { to });
+            self.set_exit(to, &opposite, Exit::Blocked);
+            Ok(format!("Opened a one-way passage {}", direction))
+        } else {
+            self.set_exit(here, direction, Exit::Open { to });
+            self.set_exit(to, &opposite, Exit::Open { to: here });
+            Ok(format!("Opened the way {}", direction))
+        }
+    }
+
+    fn execute_block_exit(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let direction = args["direction"].as_str().ok_or_else(|| anyhow::anyhow!("Missing direction"))?;
+        let kind = args["kind"].as_str().ok_or_else(|| anyhow::anyhow!("Missing kind"))?;
+        let key_id = args["key_id"].as_str().map(|k| k.to_string());
+
+        if !self.world.locations.contains_key(&self.world.current_pos) {
+            return Err(anyhow::anyhow!("Current location not found"));
+        }
+
+        let here = self.world.current_pos;
+        let to = self.exit_target(direction)?;
+        let opposite = get_opposite_direction(direction);
+        match kind {
+            "blocked" => {
+                self.set_exit(here, direction, Exit::Blocked);
+                self.set_exit(to, &opposite, Exit::Blocked);
+            }
+            "locked" => {
+                self.set_exit(here, direction, Exit::Door { to, locked: true, key_id: key_id.clone() });
+                self.set_exit(to, &opposite, Exit::Door { to: here, locked: true, key_id });
+            }
+            // A secret door is only hidden from this side.
+            "hidden" => self.set_exit(here, direction, Exit::Hidden { to }),
+            _ => return Err(anyhow::anyhow!("Unknown exit kind: {}", kind)),
+        }
+        Ok(format!("Exit {} is now {}", direction, kind))
     }
 
     fn execute_update_location_description(&mut self, arguments: &str) -> Result<String> {
@@ -711,7 +876,8 @@ Just the JSON. Nothing else."#,
             weapon_id: None,
             armor_id: None,
             initiative: rand::random::<u32>() % 20 + 1,
-            status_effects: Vec::new(),
+            // Frostbite or burns picked up on the way carry into the fight
+            status_effects: self.world.player.status_effects.clone(),
             temp_defense: 0,
         });
 
@@ -872,7 +1038,12 @@ Just the JSON. Nothing else."#,
     fn execute_end_turn(&mut self, arguments: &str) -> Result<String> {
         let args: serde_json::Value = serde_json::from_str(arguments)?;
         let actor_id = args["actor_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing actor_id"))?;
+        self.end_turn(actor_id)
+    }
 
+    /// Pass the turn on from `actor_id`, running end-of-round effects when
+    /// everyone has acted.
+    fn end_turn(&mut self, actor_id: &str) -> Result<String> {
         if !self.world.combat.active {
             return Err(anyhow::anyhow!("Combat is not active"));
         }
@@ -907,8 +1078,10 @@ Just the JSON. Nothing else."#,
             new_turn_index += 1;
         }
 
+        let mut events = Vec::new();
         if new_turn_index >= self.world.combat.combatants.len() {
             self.world.combat.round_number += 1;
+            events = self.world.advance_time(clock::MINUTES_PER_COMBAT_ROUND);
 
             for combatant in &mut self.world.combat.combatants {
                 let mut new_effects = Vec::new();
@@ -936,6 +1109,7 @@ Just the JSON. Nothing else."#,
                 combatant.status_effects = new_effects;
             }
 
+            events.extend(self.world.record_defeats());
             self.world.combat.combatants.retain(|c| c.hp > 0);
 
             let player_alive = self.world.combat.combatants.iter().any(|c| c.is_player);
@@ -943,7 +1117,9 @@ Just the JSON. Nothing else."#,
 
             if !player_alive || !enemies_alive {
                 self.world.combat.active = false;
-                return Ok("Combat ended".to_string());
+                let mut result = "Combat ended".to_string();
+                self.append_notes(&mut result, events);
+                return Ok(result);
             }
 
             new_turn_index = 0;
@@ -967,7 +1143,9 @@ Just the JSON. Nothing else."#,
             .map(|c| c.id.as_str())
             .unwrap_or("none");
 
-        Ok(format!("Turn ended. Next: {}", next_combatant))
+        let mut result = format!("Turn ended. Next: {}", next_combatant);
+        self.append_notes(&mut result, events);
+        Ok(result)
     }
 
     fn execute_inspect_object(&mut self, arguments: &str) -> Result<String> {
@@ -991,6 +1169,413 @@ Just the JSON. Nothing else."#,
         Err(anyhow::anyhow!("Object {} not found", id))
     }
 
+    fn execute_create_quest(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let id = args["id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing id"))?;
+        let title = args["title"].as_str().ok_or_else(|| anyhow::anyhow!("Missing title"))?;
+        let description = args["description"].as_str().ok_or_else(|| anyhow::anyhow!("Missing description"))?;
+
+        if self.world.quests.contains_key(id) {
+            return Err(anyhow::anyhow!("Quest {} already exists", id));
+        }
+
+        let giver = args["giver_id"].as_str().map(|s| s.to_string());
+        if let Some(giver_id) = &giver {
+            if !self.world.actors.contains_key(giver_id) {
+                return Err(anyhow::anyhow!("Actor {} not found", giver_id));
+            }
+        }
+
+        let objectives = self.parse_objectives(&args["objectives"])?;
+        if objectives.is_empty() {
+            return Err(anyhow::anyhow!("A quest needs at least one objective"));
+        }
+
+        let reward_items: Vec<String> = args["reward_item_ids"].as_array()
+            .map(|ids| ids.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        if let Some(missing) = reward_items.iter().find(|id| !self.world.items.contains_key(*id)) {
+            return Err(anyhow::anyhow!("Reward item {} not found; create it first", missing));
+        }
+
+        self.world.quests.insert(id.to_string(), Quest {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            giver,
+            objectives,
+            reward_money: args["reward_money"].as_u64().unwrap_or(0) as u32,
+            reward_items,
+            status: QuestStatus::Active,
+        });
+
+        Ok(format!("Started quest '{}'", title))
+    }
+
+    fn execute_update_quest(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let quest_id = args["quest_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing quest_id"))?;
+
+        let new_objectives = match args.get("add_objectives") {
+            Some(value) => self.parse_objectives(value)?,
+            None => Vec::new(),
+        };
+
+        let quest = self.world.quests.get_mut(quest_id)
+            .ok_or_else(|| anyhow::anyhow!("Quest {} not found", quest_id))?;
+        if quest.status != QuestStatus::Active {
+            return Err(anyhow::anyhow!("Quest {} is already {:?}", quest_id, quest.status));
+        }
+
+        if let Some(description) = args["description"].as_str() {
+            quest.description = description.to_string();
+        }
+        quest.objectives.extend(new_objectives);
+
+        if let Some(index) = args["complete_objective"].as_u64() {
+            let objective = quest.objectives.get_mut(index as usize)
+                .ok_or_else(|| anyhow::anyhow!("Quest {} has no objective {}", quest_id, index))?;
+            objective.done = true;
+        }
+
+        match args["status"].as_str() {
+            Some("failed") => quest.status = QuestStatus::Failed,
+            Some("active") | None => {}
+            Some(other) => return Err(anyhow::anyhow!("Unknown status: {}", other)),
+        }
+
+        Ok(format!("Updated quest '{}'", quest.title))
+    }
+
+    fn execute_complete_quest(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let quest_id = args["quest_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing quest_id"))?;
+
+        match self.world.quests.get(quest_id).map(|q| &q.status) {
+            None => Err(anyhow::anyhow!("Quest {} not found", quest_id)),
+            Some(QuestStatus::Active) => self.world.complete_quest(quest_id)
+                .ok_or_else(|| anyhow::anyhow!("Quest {} could not be completed", quest_id)),
+            Some(status) => Err(anyhow::anyhow!("Quest {} is already {:?}", quest_id, status)),
+        }
+    }
+
+    fn execute_pass_time(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let minutes = args["hours"].as_u64().unwrap_or(0) * clock::MINUTES_PER_HOUR
+            + args["minutes"].as_u64().unwrap_or(0);
+
+        if minutes == 0 {
+            return Err(anyhow::anyhow!("Missing hours or minutes"));
+        }
+        if minutes > 7 * clock::MINUTES_PER_DAY {
+            return Err(anyhow::anyhow!("Cannot pass more than a week at once"));
+        }
+
+        let events = self.world.advance_time(minutes);
+        let mut result = format!(
+            "{} passed{}. It is now {}",
+            crate::travel::format_duration(minutes),
+            args["activity"].as_str().map(|a| format!(" while the player {}", a)).unwrap_or_default(),
+            self.world.clock.describe()
+        );
+        // A proper rest makes this the place the player comes round after a defeat
+        if minutes >= clock::MINUTES_PER_HOUR && !self.world.combat.active {
+            self.world.rest_point = self.world.current_pos;
+            result.push_str(". Rest point set here");
+        }
+        self.append_notes(&mut result, events);
+        Ok(result)
+    }
+
+    fn execute_schedule_event(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let id = args["id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing id"))?;
+        let description = args["description"].as_str().ok_or_else(|| anyhow::anyhow!("Missing description"))?;
+
+        let delay = match (args["in_hours"].as_u64(), args["at_hour"].as_u64()) {
+            (Some(hours), _) => hours.max(1) * clock::MINUTES_PER_HOUR,
+            (None, Some(hour)) if hour < 24 => self.world.clock.minutes_until_hour(hour),
+            (None, Some(hour)) => return Err(anyhow::anyhow!("at_hour must be 0-23, got {}", hour)),
+            (None, None) => return Err(anyhow::anyhow!("Missing in_hours or at_hour")),
+        };
+
+        let at = self.world.clock.minutes + delay;
+        self.world.schedule_event(ScheduledEvent {
+            id: id.to_string(),
+            description: description.to_string(),
+            at,
+            repeat_every: args["repeat_every_hours"].as_u64().filter(|h| *h > 0).map(|h| h * clock::MINUTES_PER_HOUR),
+            location: args["here"].as_bool().unwrap_or(false).then_some(self.world.current_pos),
+        });
+
+        let when = clock::WorldClock { minutes: at };
+        Ok(format!("Scheduled '{}' for {}", id, when.describe()))
+    }
+
+    fn execute_cancel_event(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let id = args["id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing id"))?;
+
+        let before = self.world.events.len();
+        self.world.events.retain(|e| e.id != id);
+        if self.world.events.len() == before {
+            return Err(anyhow::anyhow!("Event {} not found", id));
+        }
+        Ok(format!("Cancelled event '{}'", id))
+    }
+
+    fn execute_create_faction(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let id = args["id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing id"))?;
+        let name = args["name"].as_str().ok_or_else(|| anyhow::anyhow!("Missing name"))?;
+        let description = args["description"].as_str().ok_or_else(|| anyhow::anyhow!("Missing description"))?;
+
+        if self.world.factions.contains_key(id) {
+            return Err(anyhow::anyhow!("Faction {} already exists", id));
+        }
+
+        let members: Vec<&str> = args["member_ids"].as_array()
+            .map(|ids| ids.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        if let Some(missing) = members.iter().find(|m| !self.world.actors.contains_key(**m)) {
+            return Err(anyhow::anyhow!("Actor {} not found", missing));
+        }
+
+        self.world.factions.insert(id.to_string(), Faction {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+        });
+        for member in &members {
+            if let Some(actor) = self.world.actors.get_mut(*member) {
+                actor.faction = Some(id.to_string());
+            }
+        }
+
+        Ok(format!("Created faction '{}' with {} member(s)", name, members.len()))
+    }
+
+    fn execute_set_actor_faction(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let actor_id = args["actor_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing actor_id"))?;
+        let faction_id = args["faction_id"].as_str();
+
+        if let Some(faction_id) = faction_id {
+            if !self.world.factions.contains_key(faction_id) {
+                return Err(anyhow::anyhow!("Faction {} not found", faction_id));
+            }
+        }
+        let actor = self.world.actors.get_mut(actor_id)
+            .ok_or_else(|| anyhow::anyhow!("Actor {} not found", actor_id))?;
+        actor.faction = faction_id.map(|f| f.to_string());
+
+        Ok(match faction_id {
+            Some(faction_id) => format!("{} joined {}", actor_id, faction_id),
+            None => format!("{} left their faction", actor_id),
+        })
+    }
+
+    fn execute_adjust_reputation(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let faction_id = args["faction_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing faction_id"))?;
+        let amount = args["amount"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing amount"))?;
+
+        let amount = amount.clamp(-(faction::MAX_REPUTATION as i64), faction::MAX_REPUTATION as i64) as i32;
+        self.world.adjust_reputation(faction_id, amount)
+            .ok_or_else(|| anyhow::anyhow!("Faction {} not found", faction_id))
+    }
+
+    fn execute_trade_item(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let actor_id = args["actor_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing actor_id"))?;
+        let item_id = args["item_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing item_id"))?;
+
+        let buying = match args["action"].as_str() {
+            Some("buy") => true,
+            Some("sell") => false,
+            other => return Err(anyhow::anyhow!("Unknown action: {:?}", other)),
+        };
+        self.world.trade(actor_id, item_id, buying).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn execute_learn_spell(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let spell_id = args["spell_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing spell_id"))?;
+
+        if self.world.player.known_spells.iter().any(|s| s == spell_id) {
+            return Err(anyhow::anyhow!("Player already knows {}", spell_id));
+        }
+
+        let spell = match (magic::find_spell(&self.world, spell_id), args.get("effect")) {
+            (Some(spell), _) => spell,
+            (None, Some(effect)) => {
+                let spell = Spell {
+                    id: spell_id.to_string(),
+                    name: args["name"].as_str().unwrap_or(spell_id).to_string(),
+                    description: args["description"].as_str().unwrap_or_default().to_string(),
+                    mana_cost: args["mana_cost"].as_u64().ok_or_else(|| anyhow::anyhow!("Missing mana_cost"))? as u32,
+                    effect: serde_json::from_value(effect.clone())
+                        .map_err(|e| anyhow::anyhow!("Invalid effect: {}", e))?,
+                };
+                spell.validate().map_err(|e| anyhow::anyhow!(e))?;
+                self.world.spells.insert(spell.id.clone(), spell.clone());
+                spell
+            }
+            (None, None) => return Err(anyhow::anyhow!("Unknown spell {}; give an effect to invent it", spell_id)),
+        };
+
+        self.world.player.known_spells.push(spell.id.clone());
+        Ok(format!("Learned {} ({} mana): {}", spell.name, spell.mana_cost, spell.description))
+    }
+
+    fn execute_cast_spell(&mut self, arguments: &str) -> Result<String> {
+        let args: serde_json::Value = serde_json::from_str(arguments)?;
+        let spell_id = args["spell_id"].as_str().ok_or_else(|| anyhow::anyhow!("Missing spell_id"))?;
+
+        if !self.world.player.known_spells.iter().any(|s| s == spell_id) {
+            return Err(anyhow::anyhow!("Player does not know {}", spell_id));
+        }
+        let spell = magic::find_spell(&self.world, spell_id)
+            .ok_or_else(|| anyhow::anyhow!("Spell {} not found", spell_id))?;
+        if self.world.player.mana < spell.mana_cost {
+            return Err(anyhow::anyhow!("{} needs {} mana; player has {}", spell.name, spell.mana_cost, self.world.player.mana));
+        }
+
+        let in_combat = self.world.combat.active;
+        if in_combat {
+            let current = self.world.combat.combatants.get(self.world.combat.current_turn_index);
+            if !current.is_some_and(|c| c.is_player) {
+                return Err(anyhow::anyhow!("It is not the player's turn"));
+            }
+        } else if spell.effect.is_offensive() {
+            return Err(anyhow::anyhow!("{} can only be cast in combat; use start_combat first", spell.name));
+        }
+
+        let outcome = match &spell.effect {
+            SpellEffect::Damage { amount } => {
+                let target = self.spell_target(&args, false)?;
+                let combatant = &mut self.world.combat.combatants[target];
+                combatant.hp = combatant.hp.saturating_sub(*amount);
+                format!("{} hits {} for {} damage", spell.name, combatant.id, amount)
+            }
+            SpellEffect::Heal { amount } if in_combat => {
+                let target = self.spell_target(&args, true)?;
+                let combatant = &mut self.world.combat.combatants[target];
+                combatant.hp = (combatant.hp + amount).min(combatant.max_hp);
+                format!("{} heals {} for {}", spell.name, combatant.id, amount)
+            }
+            SpellEffect::Heal { .. } => {
+                let cured = self.world.player.status_effects.len();
+                self.world.player.status_effects.clear();
+                format!("{} cures {} lasting effect(s)", spell.name, cured)
+            }
+            SpellEffect::ApplyStatus { status, duration, severity } => {
+                let target = self.spell_target(&args, false)?;
+                let combatant = &mut self.world.combat.combatants[target];
+                combatant.status_effects.push(StatusEffect {
+                    effect_type: status.clone(),
+                    duration: *duration,
+                    severity: *severity,
+                });
+                format!("{} leaves {} {:?} for {} turns", spell.name, combatant.id, status, duration)
+            }
+            SpellEffect::Utility { utility: Utility::Light } => {
+                format!("{} casts a steady light around the player", spell.name)
+            }
+            SpellEffect::Utility { utility: Utility::Unlock } => {
+                let direction = args["direction"].as_str().ok_or_else(|| anyhow::anyhow!("Unlock needs a direction"))?;
+                if self.world.unlock_door(direction).is_none() {
+                    return Err(anyhow::anyhow!("There is no locked door {}", direction));
+                }
+                format!("{} unlocks the door {}", spell.name, direction)
+            }
+            SpellEffect::Utility { utility: Utility::Reveal } => {
+                let here = self.world.current_pos;
+                let hidden: Vec<(String, (i32, i32, i32))> = self.world.locations.get(&here)
+                    .map(|l| l.exits.iter()
+                        .filter_map(|(dir, exit)| match exit {
+                            Exit::Hidden { to } => Some((dir.clone(), *to)),
+                            _ => None,
+                        })
+                        .collect())
+                    .unwrap_or_default();
+                for (direction, to) in &hidden {
+                    self.set_exit(here, direction, Exit::Open { to: *to });
+                    self.set_exit(*to, &get_opposite_direction(direction), Exit::Open { to: here });
+                }
+                let mut found: Vec<&str> = hidden.iter().map(|(d, _)| d.as_str()).collect();
+                found.sort();
+                if found.is_empty() {
+                    format!("{} finds no hidden ways here", spell.name)
+                } else {
+                    format!("{} reveals passages: {}", spell.name, found.join(", "))
+                }
+            }
+        };
+
+        self.world.player.mana -= spell.mana_cost;
+        let mut result = format!("{} (mana {}/{})", outcome, self.world.player.mana, self.world.player.max_mana);
+
+        // Casting is the player's action for this turn.
+        if in_combat {
+            result.push('\n');
+            result.push_str(&self.end_turn("player")?);
+        }
+        Ok(result)
+    }
+
+    /// Index of the combatant a spell is aimed at: `target_id` if given,
+    /// otherwise the player for friendly spells. Offensive spells need a target.
+    fn spell_target(&self, args: &serde_json::Value, friendly: bool) -> Result<usize> {
+        let target_id = match args["target_id"].as_str() {
+            Some(id) => id,
+            None if friendly => "player",
+            None => return Err(anyhow::anyhow!("Missing target_id")),
+        };
+        self.world.combat.combatants.iter()
+            .position(|c| c.id == target_id)
+            .ok_or_else(|| anyhow::anyhow!("Target {} not in combat", target_id))
+    }
+
+    fn parse_objectives(&self, value: &serde_json::Value) -> Result<Vec<QuestObjective>> {
+        let Some(entries) = value.as_array() else {
+            return Ok(Vec::new());
+        };
+
+        entries.iter().map(|entry| {
+            let kind = entry["type"].as_str().ok_or_else(|| anyhow::anyhow!("Objective missing type"))?;
+            let goal = match kind {
+                "fetch_item" => {
+                    let item_id = entry["item_id"].as_str().ok_or_else(|| anyhow::anyhow!("fetch_item needs item_id"))?;
+                    if !self.world.items.contains_key(item_id) {
+                        return Err(anyhow::anyhow!("Item {} not found; create it first", item_id));
+                    }
+                    ObjectiveGoal::FetchItem { item_id: item_id.to_string() }
+                }
+                "reach_location" => {
+                    let coord = |key: &str| entry[key].as_i64().map(|v| v as i32);
+                    let (x, y) = coord("x").zip(coord("y"))
+                        .ok_or_else(|| anyhow::anyhow!("reach_location needs x and y"))?;
+                    ObjectiveGoal::ReachLocation { pos: (x, y, coord("z").unwrap_or(0)) }
+                }
+                "defeat_actor" => {
+                    let actor_id = entry["actor_id"].as_str().ok_or_else(|| anyhow::anyhow!("defeat_actor needs actor_id"))?;
+                    if !self.world.actors.contains_key(actor_id) {
+                        return Err(anyhow::anyhow!("Actor {} not found", actor_id));
+                    }
+                    ObjectiveGoal::DefeatActor { actor_id: actor_id.to_string() }
+                }
+                "custom" => ObjectiveGoal::Custom,
+                _ => return Err(anyhow::anyhow!("Unknown objective type: {}", kind)),
+            };
+            Ok(QuestObjective {
+                description: entry["description"].as_str().unwrap_or(kind).to_string(),
+                goal,
+                done: false,
+            })
+        }).collect()
+    }
+
     fn extract_suggested_actions(&self, narrative: &str) -> Vec<String> {
         let mut actions = Vec::new();
         for line in narrative.lines() {
@@ -1013,16 +1598,6 @@ Just the JSON. Nothing else."#,
     }
 }
 
-fn get_opposite_direction(direction: &str) -> String {
-    match direction {
-        "north" => "south".to_string(),
-        "south" => "north".to_string(),
-        "east" => "west".to_string(),
-        "west" => "east".to_string(),
-        _ => direction.to_string(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1073,7 +1648,7 @@ mod tests {
     async fn test_execute_update_location_description() {
         let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
         let mut world = WorldState::new();
-        world.locations.insert((0, 0), Location {
+        world.locations.insert((0, 0, 0), Location {
             name: "Test Location".to_string(),
             description: "Old description".to_string(),
             items: vec![],
@@ -1089,11 +1664,56 @@ mod tests {
         assert!(result.contains("updated"));
 
         assert_eq!(
-            agent.world.locations.get(&(0, 0)).unwrap().description,
+            agent.world.locations.get(&(0, 0, 0)).unwrap().description,
             "New description"
         );
     }
 
+    fn exit_test_world() -> WorldState {
+        let mut world = WorldState::new();
+        for (pos, name) in [((0, 0, 0), "Hall"), ((1, 0, 0), "Vault")] {
+            world.locations.insert(pos, Location {
+                name: name.to_string(),
+                description: String::new(),
+                items: vec![],
+                actors: vec![],
+                exits: std::collections::HashMap::new(),
+                cached_image_path: None,
+                image_prompt: String::new(),
+                visited: true,
+            });
+        }
+        world
+    }
+
+    #[tokio::test]
+    async fn test_block_exit_stops_movement() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut agent = Agent::new(llm_client, exit_test_world());
+
+        agent.execute_block_exit(r#"{"direction":"east","kind":"blocked"}"#).unwrap();
+        assert_eq!(agent.world.locations[&(1, 0, 0)].exits.get("west"), Some(&Exit::Blocked));
+        assert!(agent.execute_move_to(r#"{"direction":"east"}"#).await.is_err());
+        assert_eq!(agent.world.current_pos, (0, 0, 0));
+
+        agent.execute_open_exit(r#"{"direction":"east"}"#).unwrap();
+        agent.execute_move_to(r#"{"direction":"east"}"#).await.unwrap();
+        assert_eq!(agent.world.current_pos, (1, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_locked_door_needs_key() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut agent = Agent::new(llm_client, exit_test_world());
+
+        agent.execute_block_exit(r#"{"direction":"east","kind":"locked","key_id":"brass_key"}"#).unwrap();
+        assert!(agent.execute_open_exit(r#"{"direction":"east"}"#).is_err());
+
+        agent.world.player.inventory.push("brass_key".to_string());
+        agent.execute_open_exit(r#"{"direction":"east"}"#).unwrap();
+        assert_eq!(agent.world.locations[&(0, 0, 0)].exits.get("east"), Some(&Exit::Open { to: (1, 0, 0) }));
+    }
+
     #[tokio::test]
     async fn test_execute_generate_turn_narrative() {
         let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
@@ -1104,4 +1724,163 @@ mod tests {
         assert!(result.contains("generated"));
         assert_eq!(agent.turn_narrative, Some("You see a treasure chest.".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_quest_tools() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut agent = Agent::new(llm_client, exit_test_world());
+
+        assert!(agent.execute_create_quest(r#"{"id":"q","title":"Q","description":"d","objectives":[{"type":"fetch_item","item_id":"ghost","description":"x"}]}"#).is_err());
+
+        let tool_call = ToolCall {
+            id: "call_1".to_string(),
+            call_type: "function".to_string(),
+            function: ToolFunction {
+                name: "create_quest".to_string(),
+                arguments: r#"{"id":"vault_run","title":"Vault Run","description":"Get into the vault.","reward_money":10,
+                    "objectives":[{"type":"reach_location","x":1,"y":0,"description":"Enter the vault"},{"type":"custom","description":"Keep quiet"}]}"#.to_string(),
+            },
+        };
+        agent.execute_tool_call(&tool_call).await.unwrap();
+        assert_eq!(agent.world.quests["vault_run"].status, QuestStatus::Active);
+
+        agent.world.current_pos = (1, 0, 0);
+        let result = agent.execute_tool_call(&ToolCall {
+            id: "call_2".to_string(),
+            call_type: "function".to_string(),
+            function: ToolFunction {
+                name: "update_quest".to_string(),
+                arguments: r#"{"quest_id":"vault_run","complete_objective":1}"#.to_string(),
+            },
+        }).await.unwrap();
+        assert!(result.content.contains("Quest complete: Vault Run"));
+        assert_eq!(agent.world.player.money, 10);
+        // Quest progress goes into the turn's notes for the journal.
+        let notes = agent.respond("Done.".to_string()).notes;
+        assert!(notes.iter().any(|n| n.contains("Quest complete: Vault Run")), "{:?}", notes);
+        assert!(agent.execute_complete_quest(r#"{"quest_id":"vault_run"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_time_tools() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut agent = Agent::new(llm_client, exit_test_world());
+
+        let result = agent.execute_schedule_event(r#"{"id":"bells","description":"Bells ring.","at_hour":12,"here":true}"#).unwrap();
+        assert!(result.contains("12:00"));
+        assert_eq!(agent.world.events[0].location, Some((0, 0, 0)));
+
+        let result = agent.execute_pass_time(r#"{"hours":5,"activity":"naps"}"#).unwrap();
+        assert!(result.contains("Event (Hall): Bells ring."));
+        assert!(result.contains("13:00"));
+        assert!(agent.world.events.is_empty());
+
+        agent.execute_move_to(r#"{"direction":"east"}"#).await.unwrap();
+        assert_eq!(agent.world.clock.minutes, 5 * clock::MINUTES_PER_HOUR + clock::MINUTES_PER_STEP);
+        assert!(agent.execute_cancel_event(r#"{"id":"bells"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_faction_tools() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut world = exit_test_world();
+        world.actors.insert("guard".to_string(), crate::model::Actor {
+            id: "guard".to_string(),
+            name: "Guard".to_string(),
+            description: String::new(),
+            current_pos: (0, 0, 0),
+            inventory: vec![],
+            money: 0,
+            faction: None,
+        });
+        world.locations.get_mut(&(0, 0, 0)).unwrap().actors.push("guard".to_string());
+        let mut agent = Agent::new(llm_client, world);
+
+        assert!(agent.execute_create_faction(r#"{"id":"watch","name":"City Watch","description":"d","member_ids":["ghost"]}"#).is_err());
+        agent.execute_create_faction(r#"{"id":"watch","name":"City Watch","description":"d","member_ids":["guard"]}"#).unwrap();
+        assert_eq!(agent.world.actors["guard"].faction.as_deref(), Some("watch"));
+
+        let result = agent.execute_adjust_reputation(r#"{"faction_id":"watch","amount":-60,"reason":"theft"}"#).unwrap();
+        assert!(result.contains("hostile"));
+        assert!(agent.execute_trade_item(r#"{"actor_id":"guard","item_id":"x","action":"haggle"}"#).is_err());
+
+        let system = agent.build_system_message().content.unwrap();
+        assert!(system.contains("Guard (guard; City Watch, hostile)"));
+        assert!(system.contains("- watch (City Watch): -60 (hostile)"));
+
+        agent.execute_set_actor_faction(r#"{"actor_id":"guard"}"#).unwrap();
+        assert_eq!(agent.world.disposition_of("guard"), Disposition::Neutral);
+    }
+
+    #[test]
+    fn test_system_message_recalls_recent_turns() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut world = exit_test_world();
+        for turn in 1..=7 {
+            world.turns = turn;
+            world.record_turn(&format!("action {}", turn), &"word ".repeat(100), vec![]);
+        }
+        world.journal.last_mut().unwrap().events.push("Discovered the Well.".to_string());
+        let agent = Agent::new(llm_client, world);
+
+        let system = agent.build_system_message().content.unwrap();
+        assert!(!system.contains("\"action 2\""));
+        assert!(system.contains("- Turn 3 at "));
+        assert!(system.contains("\"action 7\" -> word word"));
+        assert!(system.contains("... [Discovered the Well.]"));
+    }
+
+    fn combatant(id: &str, is_player: bool, hp: u32) -> Combatant {
+        Combatant {
+            id: id.to_string(),
+            is_player,
+            hp,
+            max_hp: hp.max(50),
+            weapon_id: None,
+            armor_id: None,
+            initiative: 10,
+            status_effects: Vec::new(),
+            temp_defense: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spells_cost_mana_and_end_the_turn() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut agent = Agent::new(llm_client, exit_test_world());
+
+        assert!(agent.execute_cast_spell(r#"{"spell_id":"firebolt"}"#).is_err());
+        agent.execute_learn_spell(r#"{"spell_id":"firebolt"}"#).unwrap();
+        assert!(agent.execute_cast_spell(r#"{"spell_id":"firebolt","target_id":"wolf"}"#).is_err());
+        assert!(agent.execute_learn_spell(r#"{"spell_id":"smite","mana_cost":1,"effect":{"type":"damage","amount":30}}"#).is_err());
+
+        agent.world.combat = CombatState {
+            active: true,
+            combatants: vec![combatant("player", true, 100), combatant("wolf", false, 30)],
+            current_turn_index: 0,
+            round_number: 1,
+        };
+        let result = agent.execute_cast_spell(r#"{"spell_id":"firebolt","target_id":"wolf"}"#).unwrap();
+        assert!(result.contains("Next: wolf"));
+        assert_eq!(agent.world.combat.combatants[1].hp, 18);
+        assert_eq!(agent.world.player.mana, magic::STARTING_MANA - 5);
+        // Not the player's turn any more
+        assert!(agent.execute_cast_spell(r#"{"spell_id":"firebolt","target_id":"wolf"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_utility_spells() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut agent = Agent::new(llm_client, exit_test_world());
+        agent.world.player.known_spells = vec!["knock".to_string(), "reveal".to_string()];
+
+        agent.execute_block_exit(r#"{"direction":"east","kind":"locked","key_id":"lost_key"}"#).unwrap();
+        agent.execute_cast_spell(r#"{"spell_id":"knock","direction":"east"}"#).unwrap();
+        assert!(agent.world.resolve_move("east").is_ok());
+
+        agent.world.locations.get_mut(&(0, 0, 0)).unwrap().exits.insert("north".to_string(), Exit::Hidden { to: (0, 1, 0) });
+        let result = agent.execute_cast_spell(r#"{"spell_id":"reveal"}"#).unwrap();
+        assert!(result.contains("reveals passages: north"));
+        assert_eq!(agent.world.player.mana, magic::STARTING_MANA - 10);
+    }
 }
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::model::{direction_offset, COMPASS_DIRECTIONS};
+
+/// Width, in tiles, of one noise cell. Larger values give broader regions.
+const REGION_SCALE: f64 = 8.0;
+
+/// How many tiles from the origin it takes for danger to climb one step.
+const DANGER_DISTANCE: f64 = 25.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Biome {
+    Lake,
+    Swamp,
+    Plains,
+    Forest,
+    Desert,
+    Hills,
+    Mountains,
+    Tundra,
+    Cavern,
+}
+
+impl Biome {
+    /// Character used for this biome on unexplored parts of the map.
+    pub fn glyph(self) -> char {
+        match self {
+            Biome::Lake => '~',
+            Biome::Swamp => '%',
+            Biome::Plains => ',',
+            Biome::Forest => 'f',
+            Biome::Desert => ':',
+            Biome::Hills => 'n',
+            Biome::Mountains => '^',
+            Biome::Tundra => '*',
+            Biome::Cavern => '=',
+        }
+    }
+
+    /// What a location in this biome must look like, phrased for the generation prompt.
+    pub fn constraint(self) -> &'static str {
+        match self {
+            Biome::Lake => "shoreline or shallow water: beaches, reeds, piers, islands",
+            Biome::Swamp => "wet, low marshland: bogs, mangroves, stilt huts, fog",
+            Biome::Plains => "open grassland: meadows, farms, roads, scattered villages",
+            Biome::Forest => "woodland: dense trees, clearings, hunting trails, woodcutters",
+            Biome::Desert => "hot dry land: sand, dunes, scrub, oases, sun-bleached ruins",
+            Biome::Hills => "rolling hills: rocky slopes, barrows, sheep pasture, watchtowers",
+            Biome::Mountains => "high mountains: cliffs, passes, thin air, mines",
+            Biome::Tundra => "frozen land: snow, ice, bare rock, bitter wind",
+            Biome::Cavern => "underground: tunnels, caves, mines, crypts; no sky or daylight",
+        }
+    }
+}
+
+impl fmt::Display for Biome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The fixed character of a grid cell, derived from the world seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Terrain {
+    pub biome: Biome,
+    /// 0 (sea level) to 100 (highest peaks).
+    pub elevation: u8,
+    /// 1 (safe) to 5 (deadly).
+    pub danger: u8,
+}
+
+impl Terrain {
+    /// Short summary, e.g. "Forest, elevation 48, danger 2/5".
+    pub fn summary(&self) -> String {
+        format!("{}, elevation {}, danger {}/5", self.biome, self.elevation, self.danger)
+    }
+}
+
+/// Work out the terrain at `pos` for the world with this seed.
+///
+/// The same seed and position always give the same answer, so terrain never
+/// needs to be stored. Biome depends only on (x, y) at and above the surface;
+/// everything below ground is cavern, growing more dangerous with depth.
+pub fn terrain_at(seed: u64, pos: (i32, i32, i32)) -> Terrain {
+    let (x, y, z) = pos;
+    let elevation = fractal_noise(seed, 1, x, y);
+    let moisture = fractal_noise(seed, 2, x, y);
+    let temperature = fractal_noise(seed, 3, x, y);
+    let hostility = fractal_noise(seed, 4, x, y);
+
+    let biome = if z < 0 {
+        Biome::Cavern
+    } else {
+        classify(elevation, moisture, temperature)
+    };
+
+    let distance = ((x as f64).powi(2) + (y as f64).powi(2)).sqrt();
+    let biome_risk = match biome {
+        Biome::Swamp | Biome::Mountains | Biome::Tundra | Biome::Cavern => 1.0,
+        _ => 0.0,
+    };
+    let depth = (-z).max(0) as f64;
+    let danger = 1.0 + hostility * 2.0 + distance / DANGER_DISTANCE + biome_risk + depth;
+
+    Terrain {
+        biome,
+        elevation: (elevation * 100.0).round() as u8,
+        danger: danger.clamp(1.0, 5.0) as u8,
+    }
+}
+
+/// Describe the terrain at `pos` and around it as rules the generator must follow.
+pub fn terrain_constraints(seed: u64, pos: (i32, i32, i32)) -> String {
+    let terrain = terrain_at(seed, pos);
+    let mut text = format!(
+        "Terrain (HARD CONSTRAINTS - the location MUST match these):\n- Biome: {} ({})\n- Elevation: {}/100\n- Danger: {}/5 (1 is safe and settled, 5 is deadly wilderness)",
+        terrain.biome,
+        terrain.biome.constraint(),
+        terrain.elevation,
+        terrain.danger
+    );
+
+    let neighbours: Vec<String> = COMPASS_DIRECTIONS.iter()
+        .filter_map(|dir| direction_offset(dir).map(|(dx, dy, _)| {
+            let neighbour = terrain_at(seed, (pos.0 + dx, pos.1 + dy, pos.2));
+            format!("{}: {}", dir, neighbour.biome)
+        }))
+        .collect();
+    text.push_str(&format!("\n- Surrounding terrain: {}", neighbours.join(", ")));
+    text.push_str("\nBlend toward neighbouring biomes at the edges, but never contradict this cell's biome.");
+    text
+}
+
+fn classify(elevation: f64, moisture: f64, temperature: f64) -> Biome {
+    if elevation < 0.28 {
+        Biome::Lake
+    } else if elevation > 0.78 {
+        if temperature < 0.35 { Biome::Tundra } else { Biome::Mountains }
+    } else if elevation > 0.64 {
+        Biome::Hills
+    } else if temperature < 0.22 {
+        Biome::Tundra
+    } else if moisture > 0.65 {
+        if elevation < 0.4 { Biome::Swamp } else { Biome::Forest }
+    } else if moisture < 0.3 && temperature > 0.55 {
+        Biome::Desert
+    } else if moisture > 0.48 {
+        Biome::Forest
+    } else {
+        Biome::Plains
+    }
+}
+
+/// Two octaves of value noise, roughly in 0.0..=1.0.
+fn fractal_noise(seed: u64, channel: u64, x: i32, y: i32) -> f64 {
+    let (fx, fy) = (x as f64, y as f64);
+    let coarse = value_noise(seed, channel, fx / REGION_SCALE, fy / REGION_SCALE);
+    let fine = value_noise(seed, channel + 100, fx / (REGION_SCALE / 2.0), fy / (REGION_SCALE / 2.0));
+    coarse * 0.7 + fine * 0.3
+}
+
+/// Smoothly interpolated lattice noise.
+fn value_noise(seed: u64, channel: u64, x: f64, y: f64) -> f64 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (smoothstep(x - x0), smoothstep(y - y0));
+    let (ix, iy) = (x0 as i64, y0 as i64);
+
+    let corner = |dx: i64, dy: i64| lattice(seed, channel, ix + dx, iy + dy);
+    let top = lerp(corner(0, 0), corner(1, 0), tx);
+    let bottom = lerp(corner(0, 1), corner(1, 1), tx);
+    lerp(top, bottom, ty)
+}
+
+fn lattice(seed: u64, channel: u64, x: i64, y: i64) -> f64 {
+    let mut h = seed ^ channel.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= (x as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^= (y as u64).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (splitmix64(h) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub(crate) fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terrain_is_deterministic_per_seed() {
+        for pos in [(0, 0, 0), (5, -3, 0), (-40, 17, 2)] {
+            assert_eq!(terrain_at(42, pos), terrain_at(42, pos));
+        }
+        let differs = (0..50).any(|x| terrain_at(1, (x, 0, 0)).biome != terrain_at(2, (x, 0, 0)).biome);
+        assert!(differs, "different seeds should give different worlds");
+    }
+
+    #[test]
+    fn test_neighbouring_tiles_are_coherent() {
+        // Adjacent tiles mostly share a biome rather than flipping every step.
+        let same = (0..100)
+            .filter(|x| terrain_at(7, (*x, 0, 0)).biome == terrain_at(7, (x + 1, 0, 0)).biome)
+            .count();
+        assert!(same > 60, "only {} of 100 steps kept the same biome", same);
+    }
+
+    #[test]
+    fn test_underground_is_cavern_and_deeper_is_riskier() {
+        let surface = terrain_at(3, (2, 2, 0));
+        let shallow = terrain_at(3, (2, 2, -1));
+        let deep = terrain_at(3, (2, 2, -3));
+        assert_eq!(shallow.biome, Biome::Cavern);
+        assert!(deep.danger >= shallow.danger);
+        assert!(shallow.danger > surface.danger || shallow.danger == 5);
+        assert!((1..=5).contains(&deep.danger));
+    }
+
+    #[test]
+    fn test_constraints_mention_biome() {
+        let text = terrain_constraints(9, (0, 0, 0));
+        assert!(text.contains(&terrain_at(9, (0, 0, 0)).biome.to_string()));
+        assert!(text.contains("northeast:"));
+    }
+}
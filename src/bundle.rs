@@ -0,0 +1,458 @@
+//! Portable world bundles for sharing a world between players.
+//!
+//! A bundle is a gzipped tar archive:
+//!
+//! ```text
+//! manifest.json            what's inside, and the world's save header
+//! world.json               the world's main save, as stored
+//! checkpoints/<name>.json  each named checkpoint
+//! images/<x>_<y>_<z>.png   cached location images
+//! ```
+//!
+//! Importing checks every file before anything is written, so a damaged or
+//! hand-edited bundle is rejected whole. The saves are written before the
+//! images, and a failure part way through removes whatever was written.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use crate::image::image_filename;
+use crate::model::{SaveFormat, WorldState};
+use crate::save::{self, SaveHeader, SaveManager};
+
+/// Newest bundle layout this build reads and the one it writes.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Appended to the world name to name an exported bundle.
+pub const BUNDLE_EXTENSION: &str = ".adventure.tgz";
+
+/// Largest total size of the files in a bundle, to stop a small archive
+/// from unpacking into gigabytes.
+const MAX_UNPACKED_BYTES: u64 = 64 * 1024 * 1024;
+
+const MANIFEST: &str = "manifest.json";
+const WORLD: &str = "world.json";
+
+type Pos = (i32, i32, i32);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub bundle_version: u32,
+    pub world_name: String,
+    pub exported: DateTime<Local>,
+    /// Header of the main save; `None` for worlds saved before headers existed.
+    pub header: Option<SaveHeader>,
+    pub checkpoints: Vec<String>,
+    pub images: Vec<String>,
+}
+
+/// A world unpacked from a bundle and saved.
+pub struct Imported {
+    pub filename: String,
+    pub world: WorldState,
+    /// Upgrades applied to the main save, as returned by `SaveManager::load_save`.
+    pub migrations: Vec<String>,
+    /// Anything the player should know, such as a rename to avoid a clash.
+    pub notes: Vec<String>,
+}
+
+/// Pack a world, its checkpoints and its cached images into a bundle.
+pub fn export_world(manager: &SaveManager, world_file: &str) -> Result<Vec<u8>> {
+    let world_json = manager.load_json(world_file)
+        .context(format!("Could not read {}", world_file))?;
+    let (world, _) = SaveManager::parse_save(world_json.as_bytes())?;
+
+    let mut files = vec![(WORLD.to_string(), world_json.into_bytes())];
+    let mut checkpoints = Vec::new();
+    for info in manager.list_checkpoints(world_file)? {
+        let Some(name) = info.checkpoint() else { continue };
+        files.push((format!("checkpoints/{}.json", name), manager.load_json(&info.filename)?.into_bytes()));
+        checkpoints.push(name.to_string());
+    }
+    let mut images = Vec::new();
+    for (pos, location) in &world.locations {
+        if let Some(data) = location.cached_image_path.as_deref().and_then(read_image) {
+            let path = format!("images/{}", image_filename(pos));
+            files.push((path.clone(), data));
+            images.push(path);
+        }
+    }
+    images.sort();
+
+    let manifest = Manifest {
+        bundle_version: BUNDLE_VERSION,
+        world_name: world_file.strip_suffix(".json").unwrap_or(world_file).to_string(),
+        exported: Local::now(),
+        header: manager.read_header(world_file),
+        checkpoints,
+        images,
+    };
+    files.insert(0, (MANIFEST.to_string(), serde_json::to_vec_pretty(&manifest)?));
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.exported.timestamp().max(0) as u64);
+        builder.append_data(&mut header, &path, data.as_slice())?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Check a bundle and add its world to `manager`. A world whose name is
+/// taken is imported under the next free name rather than replacing it.
+/// The world and its checkpoints are saved in `save_format`, this player's
+/// choice, rather than whatever the exporting player used.
+pub fn import_world(manager: &SaveManager, bundle: &[u8], save_format: SaveFormat) -> Result<Imported> {
+    let mut files = unpack(bundle)?;
+
+    let manifest: Manifest = serde_json::from_slice(&files.remove(MANIFEST).context("Bundle has no manifest")?)
+        .context("Bundle manifest is not valid")?;
+    if manifest.bundle_version == 0 || manifest.bundle_version > BUNDLE_VERSION {
+        bail!("Bundle version {} is not supported (this build reads up to {})", manifest.bundle_version, BUNDLE_VERSION);
+    }
+
+    let (mut world, migrations) = SaveManager::parse_save(&files.remove(WORLD).context("Bundle has no world.json")?)
+        .context("The world in the bundle is not a valid save")?;
+    let mut checkpoints = Vec::new();
+    for name in &manifest.checkpoints {
+        let path = format!("checkpoints/{}.json", name);
+        let content = files.remove(&path).context(format!("Bundle is missing {}", path))?;
+        let (checkpoint, _) = SaveManager::parse_save(&content)
+            .context(format!("Checkpoint '{}' in the bundle is not a valid save", name))?;
+        checkpoints.push((name.clone(), checkpoint));
+    }
+    let mut images = Vec::new();
+    for path in &manifest.images {
+        let data = files.remove(path).context(format!("Bundle is missing {}", path))?;
+        images.push((image_pos(path).context(format!("Bad image name in bundle: {}", path))?, data));
+    }
+    if let Some(extra) = files.keys().next() {
+        bail!("Bundle contains {}, which its manifest does not list", extra);
+    }
+
+    let mut notes = Vec::new();
+    let name = manager.unique_name(&manifest.world_name);
+    if save::sanitize_name(&manifest.world_name).as_deref() != Some(name.as_str()) {
+        notes.push(format!("A world called '{}' already exists, so this one was imported as '{}'.", manifest.world_name, name));
+    }
+    let filename = format!("{}.json", name);
+
+    let image_paths = image_paths(manager, &name, &images);
+    if image_paths.len() < images.len() {
+        notes.push(format!("{} location images were left out: this build keeps no image files.", images.len()));
+    }
+    // Image paths from the exporting machine mean nothing here.
+    for world in std::iter::once(&mut world).chain(checkpoints.iter_mut().map(|(_, w)| w)) {
+        world.save_format = save_format;
+        for (pos, location) in world.locations.iter_mut() {
+            location.cached_image_path = image_paths.get(pos).cloned();
+        }
+    }
+
+    let written = checkpoints.iter()
+        .try_for_each(|(checkpoint_name, checkpoint)| manager.save_checkpoint(&filename, checkpoint_name, checkpoint).map(|_| ()))
+        .and_then(|_| manager.save_game(&filename, &world))
+        .and_then(|_| write_images(&image_paths, &images));
+    if let Err(e) = written {
+        // Deleting the world takes its checkpoints and image directory with it.
+        let _ = manager.delete_save(&filename);
+        return Err(e.context("Import failed; nothing was kept"));
+    }
+    notes.push(format!("Imported {} checkpoints and {} images.", checkpoints.len(), image_paths.len()));
+    Ok(Imported { filename, world, migrations, notes })
+}
+
+/// Deliver an exported file such as a bundle: write it to `target` (a file
+/// or directory; the current directory when empty) and say where it went.
+/// An existing file is never replaced: a named file that exists is an error,
+/// and in a directory the first free name of `file_name`, `<stem>-2.<ext>`,
+/// ... is used.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_export(file_name: &str, target: &str, data: &[u8]) -> Result<String> {
+    use std::io::Write;
+
+    let dir = match target {
+        "" => Some(Path::new(".")),
+        target if Path::new(target).is_dir() => Some(Path::new(target)),
+        _ => None,
+    };
+    let path = match dir {
+        Some(dir) => (1..)
+            .map(|n| dir.join(numbered_file_name(file_name, n)))
+            .find(|path| !path.exists())
+            .context("No free file name")?,
+        None => Path::new(target).to_path_buf(),
+    };
+    let mut file = match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            bail!("{} already exists; give another path or move it away first", path_string(&path))
+        }
+        file => file.context(format!("Failed to create {:?}", path))?,
+    };
+    file.write_all(data).context(format!("Failed to write {:?}", path))?;
+    let path = path.strip_prefix(".").map(Path::to_path_buf).unwrap_or(path);
+    Ok(path_string(&path))
+}
+
+/// `Castle.adventure.tgz`, then `Castle-2.adventure.tgz`, `Castle-3...`.
+#[cfg(not(target_arch = "wasm32"))]
+fn numbered_file_name(file_name: &str, n: usize) -> String {
+    match (n, file_name.split_once('.')) {
+        (1, _) => file_name.to_string(),
+        (_, Some((stem, ext))) => format!("{}-{}.{}", stem, n, ext),
+        (_, None) => format!("{}-{}", file_name, n),
+    }
+}
+
+/// In the browser the file is offered as a download instead.
+#[cfg(target_arch = "wasm32")]
+pub fn write_export(file_name: &str, _target: &str, data: &[u8]) -> Result<String> {
+    crate::wasm::download(file_name, data)?;
+    Ok(format!("your downloads as {}", file_name))
+}
+
+/// Read a bundle to import from `source`, or when it's empty the newest
+/// bundle in the current directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_bundle(source: &str) -> Result<Vec<u8>> {
+    let path = if source.is_empty() {
+        std::fs::read_dir(".")?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(BUNDLE_EXTENSION))
+            .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+            .map(|entry| entry.path())
+            .context(format!("No *{} bundle in the current directory; use 'import <path>'", BUNDLE_EXTENSION))?
+    } else {
+        Path::new(source).to_path_buf()
+    };
+    std::fs::read(&path).context(format!("Failed to read {:?}", path))
+}
+
+/// In the browser the bundle is the file last picked with the page's Import button.
+#[cfg(target_arch = "wasm32")]
+pub fn read_bundle(_source: &str) -> Result<Vec<u8>> {
+    crate::wasm::take_upload().context("Choose a bundle with the Import button first")
+}
+
+/// Read every file out of a bundle, refusing anything that isn't one of
+/// the paths a bundle can hold.
+fn unpack(bundle: &[u8]) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bundle));
+    let mut files = BTreeMap::new();
+    let mut total = 0;
+    for entry in archive.entries().context("Not a world bundle")? {
+        let mut entry = entry.context("Bundle is damaged")?;
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().into_owned();
+        if !entry.header().entry_type().is_file() || !allowed_path(&path) {
+            bail!("Unexpected entry in bundle: {}", path);
+        }
+        total += entry.size();
+        if total > MAX_UNPACKED_BYTES {
+            bail!("Bundle unpacks to more than {} MB", MAX_UNPACKED_BYTES / (1024 * 1024));
+        }
+        let mut data = Vec::new();
+        entry.by_ref().take(MAX_UNPACKED_BYTES).read_to_end(&mut data)?;
+        if files.insert(path.clone(), data).is_some() {
+            bail!("Bundle contains {} twice", path);
+        }
+    }
+    Ok(files)
+}
+
+fn allowed_path(path: &str) -> bool {
+    if path == MANIFEST || path == WORLD {
+        return true;
+    }
+    if let Some(name) = path.strip_prefix("checkpoints/").and_then(|p| p.strip_suffix(".json")) {
+        return save::sanitize_name(name).as_deref() == Some(name);
+    }
+    image_pos(path).is_some()
+}
+
+/// Position an `images/<x>_<y>_<z>.png` entry belongs to.
+fn image_pos(path: &str) -> Option<Pos> {
+    let stem = path.strip_prefix("images/")?.strip_suffix(".png")?;
+    let mut parts = stem.split('_').map(|n| n.parse::<i32>().ok());
+    let pos = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(pos)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_image(path: &str) -> Option<Vec<u8>> {
+    std::fs::read(path).ok()
+}
+
+/// The browser build's image cache holds no files to bundle.
+#[cfg(target_arch = "wasm32")]
+fn read_image(_path: &str) -> Option<Vec<u8>> {
+    None
+}
+
+/// Where each of a bundle's images will go in the image directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn image_paths(manager: &SaveManager, world_name: &str, images: &[(Pos, Vec<u8>)]) -> HashMap<Pos, String> {
+    let Some(dir) = manager.world_image_dir(world_name) else {
+        return HashMap::new();
+    };
+    images.iter()
+        .map(|(pos, _)| (*pos, path_string(&dir.join(image_filename(pos)))))
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn image_paths(_manager: &SaveManager, _world_name: &str, _images: &[(Pos, Vec<u8>)]) -> HashMap<Pos, String> {
+    HashMap::new()
+}
+
+/// Write a bundle's images to the paths picked by `image_paths`.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_images(paths: &HashMap<Pos, String>, images: &[(Pos, Vec<u8>)]) -> Result<()> {
+    for (pos, data) in images {
+        let Some(path) = paths.get(pos).map(Path::new) else { continue };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).context(format!("Failed to create image directory: {:?}", dir))?;
+        }
+        std::fs::write(path, data).context(format!("Failed to write image: {:?}", path))?;
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_images(_paths: &HashMap<Pos, String>, _images: &[(Pos, Vec<u8>)]) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::save::temp_manager;
+    use crate::worldgen::fallback_location;
+
+    fn pack(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, data) in files {
+            // Set the name by hand: `append_data` refuses paths with `..`.
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_brings_checkpoints_and_images() {
+        let (source, source_dir) = temp_manager();
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), fallback_location((0, 0, 0)));
+        world.locations.insert((1, 0, 0), fallback_location((1, 0, 0)));
+        std::fs::create_dir_all(&source_dir).unwrap();
+        let image = source_dir.join("cached.png");
+        std::fs::write(&image, b"png bytes").unwrap();
+        world.locations.get_mut(&(1, 0, 0)).unwrap().cached_image_path = Some(path_string(&image));
+        world.player.money = 8;
+        world.save_format = SaveFormat::Gzip;
+        source.save_game("Castle.json", &world).unwrap();
+        source.save_checkpoint("Castle.json", "gate", &world).unwrap();
+        let bundle = export_world(&source, "Castle.json").unwrap();
+
+        let (target, target_dir) = temp_manager();
+        target.save_game("Castle.json", &WorldState::new()).unwrap();
+        let imported = import_world(&target, &bundle, SaveFormat::Json).unwrap();
+        assert_eq!(imported.filename, "Castle-2.json");
+        assert!(imported.notes[0].contains("'Castle-2'"));
+        assert_eq!(imported.world.player.money, 8);
+        assert_eq!(target.list_checkpoints("Castle-2.json").unwrap()[0].checkpoint(), Some("gate"));
+
+        let (loaded, _) = target.load_save("Castle-2.json").unwrap();
+        let copied = loaded.locations[&(1, 0, 0)].cached_image_path.clone().unwrap();
+        assert!(copied.starts_with(&path_string(&target_dir)));
+        assert_eq!(std::fs::read(copied).unwrap(), b"png bytes");
+        assert_eq!(loaded.locations[&(0, 0, 0)].cached_image_path, None);
+        assert_eq!(loaded.save_format, SaveFormat::Json);
+
+        // Exporting again never replaces the first bundle.
+        let exported = write_export("Castle.adventure.tgz", &path_string(&target_dir), &bundle).unwrap();
+        let again = write_export("Castle.adventure.tgz", &path_string(&target_dir), b"other").unwrap();
+        assert!(again.ends_with("Castle-2.adventure.tgz"), "{}", again);
+        assert!(write_export("Castle.adventure.tgz", &exported, b"other").is_err());
+        assert_eq!(std::fs::read(&exported).unwrap(), bundle);
+
+        // Deleting the world removes the images unpacked for it.
+        target.delete_save("Castle-2.json").unwrap();
+        assert!(!target_dir.join("images").join("Castle-2").exists());
+
+        std::fs::remove_dir_all(source_dir).unwrap();
+        std::fs::remove_dir_all(target_dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_import_leaves_nothing_behind() {
+        let (source, source_dir) = temp_manager();
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), fallback_location((0, 0, 0)));
+        std::fs::create_dir_all(&source_dir).unwrap();
+        let image = source_dir.join("cached.png");
+        std::fs::write(&image, b"png bytes").unwrap();
+        world.locations.get_mut(&(0, 0, 0)).unwrap().cached_image_path = Some(path_string(&image));
+        source.save_game("Castle.json", &world).unwrap();
+        source.save_checkpoint("Castle.json", "gate", &world).unwrap();
+        let bundle = export_world(&source, "Castle.json").unwrap();
+
+        // A file where the image directory should be makes the images fail
+        // after the saves were written.
+        let (mut target, target_dir) = temp_manager();
+        std::fs::create_dir_all(&target_dir).unwrap();
+        std::fs::write(target_dir.join("blocked"), b"").unwrap();
+        target.image_dir = Some(target_dir.join("blocked"));
+        assert!(import_world(&target, &bundle, SaveFormat::Json).is_err());
+        assert!(target.list_saves().unwrap().is_empty());
+
+        std::fs::remove_dir_all(source_dir).unwrap();
+        std::fs::remove_dir_all(target_dir).unwrap();
+    }
+
+    #[test]
+    fn test_bad_bundles_are_rejected_before_writing() {
+        let (manager, dir) = temp_manager();
+        let manifest = serde_json::to_vec(&Manifest {
+            bundle_version: BUNDLE_VERSION,
+            world_name: "Bad".to_string(),
+            exported: Local::now(),
+            header: None,
+            checkpoints: vec!["missing".to_string()],
+            images: vec![],
+        }).unwrap();
+        let world = serde_json::to_vec(&WorldState::new()).unwrap();
+
+        assert!(import_world(&manager, b"not a bundle", SaveFormat::Json).is_err());
+        assert!(import_world(&manager, &pack(&[(MANIFEST, &manifest), ("../escape.json", b"{}")]), SaveFormat::Json).is_err());
+        assert!(import_world(&manager, &pack(&[(MANIFEST, &manifest), (WORLD, b"{ broken")]), SaveFormat::Json).is_err());
+        let missing = import_world(&manager, &pack(&[(MANIFEST, &manifest), (WORLD, &world)]), SaveFormat::Json);
+        assert!(missing.err().unwrap().to_string().contains("checkpoints/missing.json"));
+        assert!(manager.list_saves().unwrap().is_empty());
+
+        assert_eq!(image_pos("images/-3_4_0.png"), Some((-3, 4, 0)));
+        assert_eq!(image_pos("images/1_2.png"), None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,304 @@
+//! A world's story written up for reading: the turn journal, a gazetteer of
+//! the places visited and a map of each level, as Markdown or as a single
+//! self-contained HTML page.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::model::{describe_level, Exit, JournalEntry, Location, WorldState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChronicleFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl ChronicleFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChronicleFormat::Markdown => "md",
+            ChronicleFormat::Html => "html",
+        }
+    }
+}
+
+impl fmt::Display for ChronicleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChronicleFormat::Markdown => write!(f, "markdown"),
+            ChronicleFormat::Html => write!(f, "html"),
+        }
+    }
+}
+
+impl FromStr for ChronicleFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "md" | "markdown" => Ok(ChronicleFormat::Markdown),
+            "html" | "htm" => Ok(ChronicleFormat::Html),
+            other => Err(format!("Unknown chronicle format '{}' (expected markdown or html)", other)),
+        }
+    }
+}
+
+/// Write up `world` under the title `name`. `earlier` holds the turns
+/// archived out of the world's journal, oldest first.
+pub fn render(world: &WorldState, name: &str, format: ChronicleFormat, earlier: &[JournalEntry]) -> String {
+    let story: Vec<&JournalEntry> = earlier.iter().chain(&world.journal).collect();
+    match format {
+        ChronicleFormat::Markdown => render_markdown(world, name, &story),
+        ChronicleFormat::Html => render_html(world, name, &story),
+    }
+}
+
+/// One line about the world as it stands.
+fn summary(world: &WorldState) -> String {
+    let visited = world.locations.values().filter(|l| l.visited).count();
+    format!("{} turns · {} places visited · {} coins · {}",
+        world.turns, visited, world.player.money, world.clock.describe())
+}
+
+/// Visited places in the order the journal first reaches them, then any
+/// the journal never mentions (such as the starting point of an old save).
+fn gazetteer<'a>(world: &'a WorldState, story: &[&JournalEntry]) -> Vec<((i32, i32, i32), &'a Location)> {
+    let mut order: Vec<(i32, i32, i32)> = Vec::new();
+    for entry in story {
+        if !order.contains(&entry.pos) {
+            order.push(entry.pos);
+        }
+    }
+    let mut rest: Vec<(i32, i32, i32)> = world.locations.keys().filter(|p| !order.contains(p)).copied().collect();
+    rest.sort();
+    order.into_iter()
+        .chain(rest)
+        .filter_map(|pos| world.locations.get(&pos).filter(|l| l.visited).map(|l| (pos, l)))
+        .collect()
+}
+
+/// Levels with a visited place on them, the surface first.
+fn levels(world: &WorldState) -> Vec<i32> {
+    let levels: BTreeSet<i32> = world.locations.iter().filter(|(_, l)| l.visited).map(|(p, _)| p.2).collect();
+    let mut levels: Vec<i32> = levels.into_iter().collect();
+    levels.sort_by_key(|z| (z.abs(), -z));
+    levels
+}
+
+/// Visited places on one level as text: `#` for a place, `@` for the
+/// player, north at the top.
+fn map(world: &WorldState, level: i32) -> String {
+    let tiles: Vec<(i32, i32)> = world.locations.iter()
+        .filter(|(p, l)| p.2 == level && l.visited)
+        .map(|(p, _)| (p.0, p.1))
+        .collect();
+    let (Some(min_x), Some(max_x)) = (tiles.iter().map(|t| t.0).min(), tiles.iter().map(|t| t.0).max()) else {
+        return String::new();
+    };
+    let min_y = tiles.iter().map(|t| t.1).min().unwrap_or(0);
+    let max_y = tiles.iter().map(|t| t.1).max().unwrap_or(0);
+
+    let mut rows = Vec::new();
+    for y in (min_y..=max_y).rev() {
+        let row: String = (min_x..=max_x)
+            .map(|x| match (x, y, level) {
+                pos if pos == world.current_pos => '@',
+                _ if tiles.contains(&(x, y)) => '#',
+                _ => '.',
+            })
+            .flat_map(|c| [c, ' '])
+            .collect();
+        rows.push(row.trim_end().to_string());
+    }
+    rows.join("\n")
+}
+
+fn exits(location: &Location) -> String {
+    let mut exits: Vec<&str> = location.exits.iter()
+        .filter(|(_, exit)| !matches!(exit, Exit::Hidden { .. }))
+        .map(|(dir, _)| dir.as_str())
+        .collect();
+    exits.sort();
+    exits.join(", ")
+}
+
+/// Say so when the story does not go back to the first turn, such as for a
+/// world whose archive was lost.
+fn earlier_turns_note(story: &[&JournalEntry]) -> Option<String> {
+    let first = story.first()?.turn;
+    (first > 1).then(|| format!("Turns before {} have faded from the journal.", first))
+}
+
+fn entry_heading(entry: &JournalEntry) -> String {
+    format!("Turn {} · {}", entry.turn, entry.location)
+}
+
+/// Player and LLM text is full of things Markdown would act on: a `<tag>`,
+/// `*stars*`, a line opening with `#` or `>`. Backslash them so it reads as typed.
+fn md_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '#' | '<' | '>' | '[' | ']') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn render_markdown(world: &WorldState, name: &str, story: &[&JournalEntry]) -> String {
+    let mut out = format!("# The Chronicle of {}\n\n*{}*\n\n## The Story\n", md_escape(name), summary(world));
+    if story.is_empty() {
+        out.push_str("\nNothing has happened here yet.\n");
+    }
+    if let Some(note) = earlier_turns_note(story) {
+        out.push_str(&format!("\n*{}*\n", note));
+    }
+    for entry in story {
+        out.push_str(&format!("\n### {}\n\n*{}*\n\n", md_escape(&entry_heading(entry)), md_escape(&entry.time)));
+        if !entry.input.is_empty() {
+            out.push_str(&format!("> {}\n\n", md_escape(&entry.input)));
+        }
+        for paragraph in entry.narrative.split('\n').filter(|p| !p.trim().is_empty()) {
+            out.push_str(&format!("{}\n\n", md_escape(paragraph.trim())));
+        }
+        for event in &entry.events {
+            out.push_str(&format!("- {}\n", md_escape(event)));
+        }
+        if !entry.events.is_empty() {
+            out.push('\n');
+        }
+    }
+
+    out.push_str("\n## Gazetteer\n");
+    for ((x, y, z), location) in gazetteer(world, story) {
+        out.push_str(&format!("\n### {}\n\n*({}, {}) on {}*\n\n{}\n",
+            md_escape(&location.name), x, y, describe_level(z), md_escape(&location.description)));
+        let exits = exits(location);
+        if !exits.is_empty() {
+            out.push_str(&format!("\nExits: {}\n", md_escape(&exits)));
+        }
+    }
+
+    out.push_str("\n## Map\n");
+    for level in levels(world) {
+        out.push_str(&format!("\n### {}\n\n```text\n{}\n```\n", capitalize(&describe_level(level)), map(world, level)));
+    }
+    out
+}
+
+const STYLE: &str = "body { max-width: 46em; margin: 2em auto; padding: 0 1em; font-family: Georgia, serif; line-height: 1.6; background: #fdfaf3; color: #2b2118; }
+h1, h2 { font-variant: small-caps; } h2 { border-bottom: 1px solid #c9b99a; }
+.time { color: #7a6a55; font-style: italic; } blockquote { margin: 0 0 1em; padding-left: 1em; border-left: 3px solid #c9b99a; color: #5a4a35; }
+.events { color: #6b4f2a; } pre { background: #f1e9d8; padding: 1em; line-height: 1.2; }";
+
+fn render_html(world: &WorldState, name: &str, story: &[&JournalEntry]) -> String {
+    let mut body = format!("<h1>The Chronicle of {}</h1>\n<p class=\"time\">{}</p>\n<h2>The Story</h2>\n",
+        escape(name), escape(&summary(world)));
+    if story.is_empty() {
+        body.push_str("<p>Nothing has happened here yet.</p>\n");
+    }
+    if let Some(note) = earlier_turns_note(story) {
+        body.push_str(&format!("<p class=\"time\">{}</p>\n", note));
+    }
+    for entry in story {
+        body.push_str(&format!("<section>\n<h3>{}</h3>\n<p class=\"time\">{}</p>\n", escape(&entry_heading(entry)), escape(&entry.time)));
+        if !entry.input.is_empty() {
+            body.push_str(&format!("<blockquote>{}</blockquote>\n", escape(&entry.input)));
+        }
+        for paragraph in entry.narrative.split('\n').filter(|p| !p.trim().is_empty()) {
+            body.push_str(&format!("<p>{}</p>\n", escape(paragraph.trim())));
+        }
+        if !entry.events.is_empty() {
+            body.push_str("<ul class=\"events\">\n");
+            for event in &entry.events {
+                body.push_str(&format!("<li>{}</li>\n", escape(event)));
+            }
+            body.push_str("</ul>\n");
+        }
+        body.push_str("</section>\n");
+    }
+
+    body.push_str("<h2>Gazetteer</h2>\n");
+    for ((x, y, z), location) in gazetteer(world, story) {
+        body.push_str(&format!("<h3>{}</h3>\n<p class=\"time\">({}, {}) on {}</p>\n<p>{}</p>\n",
+            escape(&location.name), x, y, describe_level(z), escape(&location.description)));
+        let exits = exits(location);
+        if !exits.is_empty() {
+            body.push_str(&format!("<p>Exits: {}</p>\n", escape(&exits)));
+        }
+    }
+
+    body.push_str("<h2>Map</h2>\n");
+    for level in levels(world) {
+        body.push_str(&format!("<h3>{}</h3>\n<pre>{}</pre>\n", capitalize(&describe_level(level)), escape(&map(world, level))));
+    }
+
+    format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>The Chronicle of {}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(name), STYLE, body)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worldgen::fallback_location;
+
+    fn played_world() -> WorldState {
+        let mut world = WorldState::new();
+        let mut square = fallback_location((0, 0, 0));
+        square.name = "Village Square".to_string();
+        square.visited = true;
+        let mut forge = fallback_location((1, 0, 0));
+        forge.name = "The <Forge>".to_string();
+        forge.visited = true;
+        world.locations.insert((0, 0, 0), square);
+        world.locations.insert((1, 0, 0), forge);
+        world.locations.insert((0, 1, 0), fallback_location((0, 1, 0)));
+
+        world.turns = 1;
+        world.current_pos = (1, 0, 0);
+        world.record_turn("go east", "You walk into the forge.\nSparks fly.", vec!["Discovered The <Forge>.".to_string()]);
+        world.turns = 2;
+        world.record_turn("> shout *loudly*", "# The bellows roar.", Vec::new());
+        world
+    }
+
+    #[test]
+    fn test_markdown_has_story_gazetteer_and_map() {
+        let text = render(&played_world(), "Ashford", ChronicleFormat::Markdown, &[]);
+        assert!(text.starts_with("# The Chronicle of Ashford"));
+        assert!(text.contains("### Turn 1 · The \\<Forge\\>\n"));
+        assert!(text.contains("> go east\n\nYou walk into the forge.\n\nSparks fly.\n\n- Discovered The \\<Forge\\>.\n"));
+        // What the player and the DM wrote can't turn into Markdown of its own.
+        assert!(text.contains("> \\> shout \\*loudly\\*\n\n\\# The bellows roar.\n"));
+        assert!(!text.contains("<Forge>"));
+        // Gazetteer follows the journal, then the rest; unvisited places are left out.
+        let forge = text.find("\n### The \\<Forge\\>\n").unwrap();
+        let square = text.find("\n### Village Square\n").unwrap();
+        assert!(forge < square);
+        assert_eq!(text.matches("\n### ").count(), 5);
+        assert!(text.contains("```text\n# @\n```"));
+    }
+
+    #[test]
+    fn test_html_is_escaped_and_standalone() {
+        let html = render(&played_world(), "Ash & Iron", ChronicleFormat::Html, &[]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>The Chronicle of Ash &amp; Iron</title>"));
+        assert!(html.contains("<li>Discovered The &lt;Forge&gt;.</li>"));
+        assert!(!html.contains("<Forge>"));
+        assert!(html.contains("<pre># @</pre>"));
+        assert_eq!("htm".parse::<ChronicleFormat>(), Ok(ChronicleFormat::Html));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::game::Game;
 use crate::commands::Command;
-use crate::model::ItemState;
+use crate::model::{ItemState, Exit};
 use anyhow::Result;
 use std::io::{self, Write};
 use tokio::io::{AsyncBufReadExt, BufReader};
@@ -24,6 +24,9 @@ impl Cli {
     ///
     /// ## Commands
     /// - `/north`, `/south`, `/east`, `/west` - Quick movement (instant if location exists)
+    /// - `/ne`, `/nw`, `/se`, `/sw` - Quick diagonal movement
+    /// - `/travel <place or x,y>` - Fast travel to a visited location
+    /// - `/up`, `/down`, `/in`, `/out` - Change level through stairs or doorways
     /// - `/exit` - Terminate cleanly
     /// - `1`, `2`, `3`... - Select from suggested_actions list
     /// - Any text - Pass to game.process_input() for LLM interpretation
@@ -36,7 +39,9 @@ impl Cli {
     ///
     /// --- Location ---
     /// Name: ...
-    /// Position: (x, y)
+    /// Position: (x, y, z)
+    /// Region: Name - description (if in one)
+    /// Terrain: Biome, elevation N, danger N/5
     /// Description: ...
     /// Visited: true/false
     ///
@@ -47,7 +52,7 @@ impl Cli {
     ///   - ActorName
     ///
     /// --- Exits --- (if any)
-    ///   - direction: (x, y) - Name
+    ///   - direction: (x, y, z) - Name
     ///
     /// --- Player Inventory --- (if any)
     ///   - ItemName (Type) [state]
@@ -78,7 +83,7 @@ impl Cli {
         let mut line = String::new();
 
         println!("=== LLM Debug Mode ===");
-        println!("Special commands: /north, /south, /east, /west, /exit");
+        println!("Special commands: /north, /south, /east, /west, /ne, /nw, /se, /sw, /up, /down, /in, /out, /travel <place>, /save <name>, /checkpoint [name], /export [path], /import [path], /chronicle [md|html] [path], /exit");
         println!("Type any text to interact with the game.\n");
 
         loop {
@@ -111,6 +116,12 @@ impl Cli {
                         "load" | "l" => {
                             game.process_input("load").await?;
                         }
+                        "restore" | "r" => {
+                            game.process_input("restore").await?;
+                        }
+                        import if import == "import" || import.starts_with("import ") => {
+                            game.process_input(import).await?;
+                        }
                         num if num.parse::<usize>().is_ok() => {
                             let idx: usize = num.parse()?;
                             if idx < game.save_list.len() {
@@ -119,7 +130,7 @@ impl Cli {
                             }
                         }
                         _ => {
-                            println!("Use 'new', 'load', or a number to select a save.");
+                            println!("Use 'new', 'load', 'restore', 'import [path]', or a number to select a save.");
                         }
                     }
                 }
@@ -147,6 +158,32 @@ impl Cli {
                             "/south" => Command::MoveSouth,
                             "/east" => Command::MoveEast,
                             "/west" => Command::MoveWest,
+                            "/ne" | "/northeast" => Command::MoveNorthEast,
+                            "/nw" | "/northwest" => Command::MoveNorthWest,
+                            "/se" | "/southeast" => Command::MoveSouthEast,
+                            "/sw" | "/southwest" => Command::MoveSouthWest,
+                            "/up" => Command::MoveUp,
+                            "/down" => Command::MoveDown,
+                            "/in" => Command::MoveIn,
+                            "/out" => Command::MoveOut,
+                            travel if travel.starts_with("/travel ") => {
+                                Command::TravelTo(travel["/travel ".len()..].trim().to_string())
+                            }
+                            save if save.starts_with("/save ") => {
+                                Command::SaveAs(save["/save ".len()..].trim().to_string())
+                            }
+                            load if load.starts_with("/checkpoint") => {
+                                Command::LoadCheckpoint(load["/checkpoint".len()..].trim().to_string())
+                            }
+                            export if export.starts_with("/export") => {
+                                Command::Export(export["/export".len()..].trim().to_string())
+                            }
+                            import if import.starts_with("/import") => {
+                                Command::Import(import["/import".len()..].trim().to_string())
+                            }
+                            chronicle if chronicle.starts_with("/chronicle") => {
+                                Command::Chronicle(chronicle["/chronicle".len()..].trim().to_string())
+                            }
                             _ => Command::TextInput(input.to_string()),
                         }
                     };
@@ -155,6 +192,9 @@ impl Cli {
                         println!("Error processing input: {}", e);
                     }
                 }
+                GameState::GameOver => {
+                    game.process_command(Command::Enter).await?;
+                }
                 _ => {
                     println!("Game is processing, please wait...");
                 }
@@ -169,12 +209,31 @@ impl Cli {
         println!("WORLD STATE");
         println!("========================================");
 
-        let (x, y) = game.world.current_pos;
+        if game.state == crate::game::GameState::SplashScreen {
+            println!("\n--- Saves ---");
+            for (i, save) in game.save_list.iter().enumerate() {
+                match save.checkpoint() {
+                    Some(name) => println!("  {}.   checkpoint '{}' ({})", i, name, save.modified.format("%Y-%m-%d %H:%M")),
+                    None => println!("  {}. {} ({})", i, save.filename, save.modified.format("%Y-%m-%d %H:%M")),
+                }
+                if let Some(header) = &save.header {
+                    println!("       {}", header.summary());
+                }
+            }
+        }
+
+        let (x, y, z) = game.world.current_pos;
 
-        if let Some(loc) = game.world.locations.get(&(x, y)) {
+        if let Some(loc) = game.world.locations.get(&(x, y, z)) {
             println!("\n--- Location ---");
             println!("Name: {}", loc.name);
-            println!("Position: ({}, {})", x, y);
+            println!("Position: ({}, {}, {})", x, y, z);
+            if let Some(region) = game.world.region_at((x, y, z)) {
+                println!("Region: {} - {}", region.name, region.description);
+            }
+            println!("Terrain: {}", game.world.terrain_at((x, y, z)).summary());
+            println!("Time: {}", game.world.clock.describe());
+            println!("Weather: {}", game.world.weather.summary());
             println!("Description: {}", loc.description);
             println!("Visited: {}", loc.visited);
 
@@ -199,16 +258,25 @@ impl Cli {
 
             if !loc.exits.is_empty() {
                 println!("\n--- Exits ---");
-                for (dir, target) in &loc.exits {
-                    match target {
-                        Some((tx, ty)) => {
-                            let name = game.world.locations.get(&(*tx, *ty))
-                                .map(|l| l.name.as_str())
-                                .unwrap_or("Unknown");
-                            println!("  - {}: ({}, {}) - {}", dir, tx, ty, name);
+                for (dir, exit) in &loc.exits {
+                    let (tx, ty, tz) = match exit.target() {
+                        Some(target) => target,
+                        None => {
+                            println!("  - {}: blocked", dir);
+                            continue;
                         }
-                        None => println!("  - {}: blocked", dir),
-                    }
+                    };
+                    let name = game.world.locations.get(&(tx, ty, tz))
+                        .map(|l| l.name.as_str())
+                        .unwrap_or("Unknown");
+                    let note = match exit {
+                        Exit::Door { locked: true, key_id, .. } => format!(" [locked door, key: {:?}]", key_id),
+                        Exit::Door { .. } => " [door]".to_string(),
+                        Exit::Hidden { .. } => " [hidden]".to_string(),
+                        Exit::OneWay { .. } => " [one-way]".to_string(),
+                        _ => String::new(),
+                    };
+                    println!("  - {}: ({}, {}, {}) - {}{}", dir, tx, ty, tz, name, note);
                 }
             }
         }
@@ -225,6 +293,34 @@ impl Cli {
 
         println!("\n--- Player Stats ---");
         println!("Money: {}", game.world.player.money);
+        println!("Mana: {}/{}", game.world.player.mana, game.world.player.max_mana);
+        if !game.world.player.known_spells.is_empty() {
+            println!("Spells: {}", game.world.player.known_spells.join(", "));
+        }
+        for effect in &game.world.player.status_effects {
+            println!("Status: {:?} ({} turns)", effect.effect_type, effect.duration);
+        }
+
+        if !game.world.factions.is_empty() {
+            println!("\n--- Reputation ---");
+            let mut factions: Vec<_> = game.world.factions.values().collect();
+            factions.sort_by(|a, b| a.name.cmp(&b.name));
+            for faction in factions {
+                let score = game.world.reputation_with(&faction.id);
+                println!("  - {}: {} ({})", faction.name, score, crate::faction::Disposition::from_reputation(score));
+            }
+        }
+
+        let quests = game.world.quest_log();
+        if !quests.is_empty() {
+            println!("\n--- Quests ---");
+            for quest in quests {
+                println!("  - {} [{:?}]: {}", quest.title, quest.status, quest.description);
+                for objective in &quest.objectives {
+                    println!("      [{}] {}", if objective.done { "x" } else { " " }, objective.description);
+                }
+            }
+        }
 
         println!("\n--- Narrative ---");
         let turn_narrative = game.last_narrative.clone();
@@ -253,6 +349,9 @@ impl Cli {
         println!("\n--- Game State ---");
         println!("State: {:?}", game.state);
         println!("Save Path: {:?}", game.current_save_path);
+        if !game.status_message.is_empty() {
+            println!("Status: {}", game.status_message);
+        }
     }
 }
 
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+pub const MINUTES_PER_HOUR: u64 = 60;
+pub const MINUTES_PER_DAY: u64 = 24 * MINUTES_PER_HOUR;
+pub const DAYS_PER_MONTH: u64 = 30;
+
+/// In-game minutes spent crossing one tile.
+pub const MINUTES_PER_STEP: u64 = 15;
+
+/// In-game minutes a full round of combat takes.
+pub const MINUTES_PER_COMBAT_ROUND: u64 = 1;
+
+/// Worlds begin at this hour of their first day.
+const START_HOUR: u64 = 8;
+
+const MONTHS: [&str; 12] = [
+    "Deepwinter", "Thawing", "Seedtime", "Rainfall", "Blossom", "Highsun",
+    "Midsummer", "Harvest", "Leaffall", "Mistmoon", "Frostfall", "Longnight",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayPhase {
+    Dawn,
+    Morning,
+    Afternoon,
+    Dusk,
+    Evening,
+    Night,
+}
+
+impl DayPhase {
+    pub fn from_hour(hour: u64) -> Self {
+        match hour {
+            5..=6 => DayPhase::Dawn,
+            7..=11 => DayPhase::Morning,
+            12..=16 => DayPhase::Afternoon,
+            17..=18 => DayPhase::Dusk,
+            19..=21 => DayPhase::Evening,
+            _ => DayPhase::Night,
+        }
+    }
+
+    pub fn is_dark(&self) -> bool {
+        matches!(self, DayPhase::Evening | DayPhase::Night)
+    }
+}
+
+impl std::fmt::Display for DayPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            DayPhase::Dawn => "dawn",
+            DayPhase::Morning => "morning",
+            DayPhase::Afternoon => "afternoon",
+            DayPhase::Dusk => "dusk",
+            DayPhase::Evening => "evening",
+            DayPhase::Night => "night",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// In-game time, counted in minutes since the world began at 08:00 on the
+/// first day of Deepwinter, year 1.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct WorldClock {
+    pub minutes: u64,
+}
+
+impl WorldClock {
+    pub fn advance(&mut self, minutes: u64) {
+        self.minutes += minutes;
+    }
+
+    fn since_midnight_of_day_one(&self) -> u64 {
+        self.minutes + START_HOUR * MINUTES_PER_HOUR
+    }
+
+    pub fn hour(&self) -> u64 {
+        self.since_midnight_of_day_one() % MINUTES_PER_DAY / MINUTES_PER_HOUR
+    }
+
+    pub fn minute(&self) -> u64 {
+        self.since_midnight_of_day_one() % MINUTES_PER_HOUR
+    }
+
+    /// Days elapsed, counting the first day as day 1.
+    pub fn day(&self) -> u64 {
+        self.since_midnight_of_day_one() / MINUTES_PER_DAY + 1
+    }
+
+    pub fn phase(&self) -> DayPhase {
+        DayPhase::from_hour(self.hour())
+    }
+
+    /// "14:05"
+    pub fn time_of_day(&self) -> String {
+        format!("{:02}:{:02}", self.hour(), self.minute())
+    }
+
+    /// Month of the year, 0 (Deepwinter) to 11 (Longnight).
+    pub fn month(&self) -> u64 {
+        (self.day() - 1) / DAYS_PER_MONTH % MONTHS.len() as u64
+    }
+
+    /// "3 Thawing, year 1"
+    pub fn date(&self) -> String {
+        let days = self.day() - 1;
+        format!(
+            "{} {}, year {}",
+            days % DAYS_PER_MONTH + 1,
+            MONTHS[self.month() as usize],
+            days / DAYS_PER_MONTH / MONTHS.len() as u64 + 1
+        )
+    }
+
+    /// "14:05 (afternoon), 3 Thawing, year 1"
+    pub fn describe(&self) -> String {
+        format!("{} ({}), {}", self.time_of_day(), self.phase(), self.date())
+    }
+
+    /// Minutes from now until the next time the clock reads `hour`:00.
+    /// An hour that has already started today means tomorrow.
+    pub fn minutes_until_hour(&self, hour: u64) -> u64 {
+        let now = self.since_midnight_of_day_one() % MINUTES_PER_DAY;
+        let target = (hour % 24) * MINUTES_PER_HOUR;
+        if target > now {
+            target - now
+        } else {
+            MINUTES_PER_DAY - now + target
+        }
+    }
+}
+
+/// Something that happens at a set time whether or not the player is there,
+/// such as a shop closing or a caravan arriving.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduledEvent {
+    pub id: String,
+    pub description: String,
+    /// Clock minutes at which the event next fires.
+    pub at: u64,
+    /// Fire again this many minutes later, e.g. daily opening hours.
+    #[serde(default)]
+    pub repeat_every: Option<u64>,
+    /// Where it happens, if anywhere in particular.
+    #[serde(default)]
+    pub location: Option<(i32, i32, i32)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar() {
+        let mut clock = WorldClock::default();
+        assert_eq!(clock.describe(), "08:00 (morning), 1 Deepwinter, year 1");
+
+        clock.advance(14 * MINUTES_PER_HOUR + 30);
+        assert_eq!(clock.time_of_day(), "22:30");
+        assert_eq!(clock.phase(), DayPhase::Night);
+        assert!(clock.phase().is_dark());
+
+        clock.advance(2 * MINUTES_PER_HOUR);
+        assert_eq!(clock.day(), 2);
+        assert_eq!(clock.date(), "2 Deepwinter, year 1");
+
+        clock.advance(360 * MINUTES_PER_DAY);
+        assert_eq!(clock.date(), "2 Deepwinter, year 2");
+    }
+
+    #[test]
+    fn test_minutes_until_hour() {
+        let clock = WorldClock::default();
+        assert_eq!(clock.minutes_until_hour(18), 10 * MINUTES_PER_HOUR);
+        assert_eq!(clock.minutes_until_hour(8), MINUTES_PER_DAY);
+        assert_eq!(clock.minutes_until_hour(6), 22 * MINUTES_PER_HOUR);
+    }
+}
@@ -7,6 +7,7 @@ pub enum Command {
     Up,
     Down,
     Delete,
+    RestoreBackup,
     Enter,
     Back,
     Backspace,
@@ -14,6 +15,26 @@ pub enum Command {
     MoveSouth,
     MoveEast,
     MoveWest,
+    MoveNorthEast,
+    MoveNorthWest,
+    MoveSouthEast,
+    MoveSouthWest,
+    MoveUp,
+    MoveDown,
+    MoveIn,
+    MoveOut,
+    TravelTo(String),
+    SaveAs(String),
+    LoadCheckpoint(String),
+    /// Export the current world as a bundle, to a path or the default file name.
+    Export(String),
+    /// Import a bundle from a path (native) or the last upload (browser).
+    Import(String),
+    /// Write the world's story up as Markdown or HTML, optionally to a path.
+    Chronicle(String),
+    /// Scroll the Narrative panel this many lines back through the journal, or forward again.
+    ScrollBack(usize),
+    ScrollForward(usize),
     SelectOption(usize),
     TextInput(String),
     None,
@@ -21,7 +42,9 @@ pub enum Command {
 
 impl Command {
     pub fn from_str(input: &str) -> Self {
-        let input = input.trim().to_lowercase();
+        // Paths and checkpoint names keep their case.
+        let raw = input.trim();
+        let input = raw.to_lowercase();
 
         match input.as_str() {
             "new" => Command::New,
@@ -29,6 +52,7 @@ impl Command {
             "up" => Command::Up,
             "down" => Command::Down,
             "delete" => Command::Delete,
+            "restore" | "restore backup" => Command::RestoreBackup,
             "enter" => Command::Enter,
             "back" => Command::Back,
             "backspace" => Command::Backspace,
@@ -36,8 +60,28 @@ impl Command {
             "go south" | "south" => Command::MoveSouth,
             "go east" | "east" => Command::MoveEast,
             "go west" | "west" => Command::MoveWest,
+            "go northeast" | "northeast" | "ne" => Command::MoveNorthEast,
+            "go northwest" | "northwest" | "nw" => Command::MoveNorthWest,
+            "go southeast" | "southeast" | "se" => Command::MoveSouthEast,
+            "go southwest" | "southwest" | "sw" => Command::MoveSouthWest,
+            "go up" | "climb up" | "upstairs" => Command::MoveUp,
+            "go down" | "climb down" | "downstairs" => Command::MoveDown,
+            "go in" | "go inside" | "inside" => Command::MoveIn,
+            "go out" | "go outside" | "outside" | "leave" | "exit" => Command::MoveOut,
             _ => {
-                if let Ok(num) = input.parse::<usize>() {
+                if let Some(destination) = input.strip_prefix("travel to ") {
+                    Command::TravelTo(destination.trim().to_string())
+                } else if input.starts_with("save as ") {
+                    Command::SaveAs(raw["save as ".len()..].trim().to_string())
+                } else if input.starts_with("load checkpoint") {
+                    Command::LoadCheckpoint(raw["load checkpoint".len()..].trim().to_string())
+                } else if input == "export" || input.starts_with("export ") {
+                    Command::Export(raw["export".len()..].trim().to_string())
+                } else if input == "import" || input.starts_with("import ") {
+                    Command::Import(raw["import".len()..].trim().to_string())
+                } else if input == "chronicle" || input.starts_with("chronicle ") {
+                    Command::Chronicle(raw["chronicle".len()..].trim().to_string())
+                } else if let Ok(num) = input.parse::<usize>() {
                     Command::SelectOption(num)
                 } else {
                     Command::TextInput(input.to_string())
@@ -45,4 +89,34 @@ impl Command {
             }
         }
     }
+
+    /// While playing there is no menu to steer, so the menu words `up`,
+    /// `down` and `enter` mean moves.
+    pub fn in_play(self) -> Self {
+        match self {
+            Command::Up => Command::MoveUp,
+            Command::Down => Command::MoveDown,
+            Command::Enter => Command::MoveIn,
+            other => other,
+        }
+    }
+
+    /// The direction a quick-move command walks, as `WorldState::resolve_move` takes it.
+    pub fn move_direction(&self) -> Option<&'static str> {
+        match self {
+            Command::MoveNorth => Some("north"),
+            Command::MoveSouth => Some("south"),
+            Command::MoveEast => Some("east"),
+            Command::MoveWest => Some("west"),
+            Command::MoveNorthEast => Some("northeast"),
+            Command::MoveNorthWest => Some("northwest"),
+            Command::MoveSouthEast => Some("southeast"),
+            Command::MoveSouthWest => Some("southwest"),
+            Command::MoveUp => Some("up"),
+            Command::MoveDown => Some("down"),
+            Command::MoveIn => Some("enter"),
+            Command::MoveOut => Some("exit"),
+            _ => None,
+        }
+    }
 }
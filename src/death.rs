@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::clock::MINUTES_PER_HOUR;
+use crate::model::{CombatState, ItemState, WorldState};
+
+/// Share of the player's money lost when they respawn.
+pub const RESPAWN_MONEY_PENALTY_PERCENT: u32 = 50;
+
+/// Hours that pass before the player comes round at their rest point.
+pub const RESPAWN_HOURS: u64 = 6;
+
+/// What happens when the player is defeated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DeathPolicy {
+    /// The world ends and its save is deleted.
+    Permadeath,
+    /// Wake at the last rest point, having lost some money.
+    #[default]
+    Respawn,
+    /// Wake at the last rest point; everything carried is left where the player fell.
+    DropInventory,
+}
+
+impl fmt::Display for DeathPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DeathPolicy::Permadeath => "permadeath",
+            DeathPolicy::Respawn => "respawn",
+            DeathPolicy::DropInventory => "drop-inventory",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for DeathPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "permadeath" => Ok(DeathPolicy::Permadeath),
+            "respawn" => Ok(DeathPolicy::Respawn),
+            "drop-inventory" | "drop" => Ok(DeathPolicy::DropInventory),
+            other => Err(format!("Unknown death policy '{}' (expected permadeath, respawn or drop-inventory)", other)),
+        }
+    }
+}
+
+/// Apply the world's death policy after the player has been defeated and
+/// describe what happened. Deleting the save under permadeath is left to
+/// the caller, which owns the save file.
+pub fn apply_defeat(world: &mut WorldState) -> String {
+    let fell_at = world.current_pos;
+    let fell_name = world.locations.get(&fell_at).map(|l| l.name.clone()).unwrap_or_else(|| "the wilds".to_string());
+
+    world.combat = CombatState::default();
+    world.player_defeated = false;
+    world.deaths += 1;
+    world.player.status_effects.clear();
+
+    let mut summary = match world.death_policy {
+        DeathPolicy::Permadeath => {
+            return format!("You fell at {}. Your story ends here, and this world is lost with you.", fell_name);
+        }
+        DeathPolicy::Respawn => {
+            let lost = world.player.money * RESPAWN_MONEY_PENALTY_PERCENT / 100;
+            world.player.money -= lost;
+            format!("You fell at {}. Someone dragged you to safety, relieving you of {} coins for the trouble.", fell_name, lost)
+        }
+        DeathPolicy::DropInventory => {
+            let dropped = std::mem::take(&mut world.player.inventory);
+            for item_id in &dropped {
+                if let Some(item) = world.items.get_mut(item_id) {
+                    if item.state == ItemState::Equipped {
+                        item.state = ItemState::Normal;
+                    }
+                }
+            }
+            let count = dropped.len();
+            if let Some(loc) = world.locations.get_mut(&fell_at) {
+                loc.items.extend(dropped);
+            }
+            format!("You fell at {}. Your {} belonging(s) lie where you dropped them.", fell_name, count)
+        }
+    };
+
+    let rest_point = if world.locations.contains_key(&world.rest_point) { world.rest_point } else { (0, 0, 0) };
+    world.current_pos = rest_point;
+    world.player.mana = world.player.max_mana;
+    world.advance_time(RESPAWN_HOURS * MINUTES_PER_HOUR);
+
+    let rest_name = world.locations.get(&rest_point).map(|l| l.name.as_str()).unwrap_or("a familiar place");
+    summary.push_str(&format!(" You come round at {}, {}.", rest_name, world.clock.describe()));
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Item, ItemProperties, ItemType};
+
+    fn fallen_world(policy: DeathPolicy) -> WorldState {
+        let mut world = WorldState::new();
+        for (pos, name) in [((0, 0, 0), "Inn"), ((3, 0, 0), "Crypt")] {
+            let mut loc = crate::worldgen::fallback_location(pos);
+            loc.name = name.to_string();
+            world.locations.insert(pos, loc);
+        }
+        world.items.insert("sword".to_string(), Item {
+            id: "sword".to_string(),
+            name: "Sword".to_string(),
+            description: String::new(),
+            item_type: ItemType::Weapon,
+            state: ItemState::Equipped,
+            properties: ItemProperties::default(),
+        });
+        world.player.inventory.push("sword".to_string());
+        world.player.money = 40;
+        world.current_pos = (3, 0, 0);
+        world.combat.active = true;
+        world.player_defeated = true;
+        world.death_policy = policy;
+        world
+    }
+
+    #[test]
+    fn test_respawn_costs_money() {
+        let mut world = fallen_world(DeathPolicy::Respawn);
+        let summary = apply_defeat(&mut world);
+        assert!(summary.contains("20 coins"));
+        assert!(summary.contains("come round at Inn"));
+        assert_eq!(world.current_pos, (0, 0, 0));
+        assert_eq!(world.player.money, 20);
+        assert_eq!(world.player.inventory, vec!["sword".to_string()]);
+        assert!(!world.combat.active && !world.player_defeated);
+        assert_eq!(world.deaths, 1);
+    }
+
+    #[test]
+    fn test_drop_inventory_leaves_items_at_death_tile() {
+        let mut world = fallen_world(DeathPolicy::DropInventory);
+        apply_defeat(&mut world);
+        assert!(world.player.inventory.is_empty());
+        assert_eq!(world.locations[&(3, 0, 0)].items, vec!["sword".to_string()]);
+        assert_eq!(world.items["sword"].state, ItemState::Normal);
+        assert_eq!(world.player.money, 40);
+    }
+
+    #[test]
+    fn test_permadeath_leaves_player_where_they_fell() {
+        let mut world = fallen_world(DeathPolicy::Permadeath);
+        assert!(apply_defeat(&mut world).contains("world is lost"));
+        assert_eq!(world.current_pos, (3, 0, 0));
+        assert_eq!("drop_inventory".parse(), Ok(DeathPolicy::DropInventory));
+        assert!("reincarnate".parse::<DeathPolicy>().is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Reputation is kept between these bounds.
+pub const MIN_REPUTATION: i32 = -100;
+pub const MAX_REPUTATION: i32 = 100;
+
+/// Reputation lost with a faction when the player defeats one of its members.
+pub const KILL_PENALTY: i32 = 30;
+
+/// A group actors can belong to, such as a guild, a town watch or a bandit clan.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Faction {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// How members of a faction feel about the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Disposition {
+    Hostile,
+    Unfriendly,
+    Neutral,
+    Friendly,
+    Allied,
+}
+
+impl Disposition {
+    pub fn from_reputation(reputation: i32) -> Self {
+        match reputation {
+            i32::MIN..=-50 => Disposition::Hostile,
+            -49..=-10 => Disposition::Unfriendly,
+            -9..=9 => Disposition::Neutral,
+            10..=49 => Disposition::Friendly,
+            _ => Disposition::Allied,
+        }
+    }
+
+    /// Whether members will trade with the player at all.
+    pub fn will_trade(self) -> bool {
+        self != Disposition::Hostile
+    }
+}
+
+impl fmt::Display for Disposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Disposition::Hostile => "hostile",
+            Disposition::Unfriendly => "unfriendly",
+            Disposition::Neutral => "neutral",
+            Disposition::Friendly => "friendly",
+            Disposition::Allied => "allied",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What the player pays for an item worth `value`: up to 30% off with
+/// allies, up to 30% more with a faction that dislikes them.
+pub fn buy_price(value: u32, reputation: i32) -> u32 {
+    let factor = 1.0 - reputation.clamp(MIN_REPUTATION, MAX_REPUTATION) as f64 * 0.003;
+    ((value as f64 * factor).round() as u32).max(1)
+}
+
+/// What the player is paid for an item worth `value`. Merchants keep a
+/// margin, so selling never pays more than buying costs.
+pub fn sell_price(value: u32, reputation: i32) -> u32 {
+    let factor = 0.5 + reputation.clamp(MIN_REPUTATION, MAX_REPUTATION) as f64 * 0.0015;
+    (value as f64 * factor).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disposition_bands() {
+        assert_eq!(Disposition::from_reputation(-100), Disposition::Hostile);
+        assert_eq!(Disposition::from_reputation(-10), Disposition::Unfriendly);
+        assert_eq!(Disposition::from_reputation(0), Disposition::Neutral);
+        assert_eq!(Disposition::from_reputation(10), Disposition::Friendly);
+        assert_eq!(Disposition::from_reputation(75), Disposition::Allied);
+        assert!(!Disposition::Hostile.will_trade());
+    }
+
+    #[test]
+    fn test_prices_follow_reputation() {
+        assert_eq!(buy_price(100, 0), 100);
+        assert_eq!(buy_price(100, 100), 70);
+        assert_eq!(buy_price(100, -100), 130);
+        assert_eq!(sell_price(100, 0), 50);
+        for reputation in [-100, -30, 0, 30, 100] {
+            assert!(sell_price(100, reputation) < buy_price(100, reputation));
+        }
+    }
+}
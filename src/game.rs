@@ -1,8 +1,15 @@
-use crate::model::{WorldState, Location};
+use crate::model::{WorldState, Location, SaveFormat, direction_phrase};
 use crate::llm::LlmClient;
 use crate::agent::Agent;
-use crate::save::{SaveManager, SaveInfo};
+use crate::save::{self, SaveManager, SaveInfo};
+use crate::bundle;
+use crate::chronicle::{self, ChronicleFormat};
 use crate::commands::Command;
+use crate::worldgen;
+use crate::travel;
+use crate::clock;
+use crate::pregen::Pregenerator;
+use crate::death::{self, DeathPolicy};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -16,6 +23,8 @@ pub enum GameState {
     Processing,
     UpdatingWorld,
     Rendering,
+    /// The player has been defeated; `last_narrative` says what happened next.
+    GameOver,
 }
 
 pub struct Game {
@@ -31,11 +40,34 @@ pub struct Game {
     pub current_options: Vec<String>,
     pub status_message: String,
     pub new_world_name: String,
+    pub pregenerator: Pregenerator,
+    /// Death policy given to newly created worlds; loaded worlds keep their own.
+    pub death_policy: DeathPolicy,
+    /// Save format given to newly created worlds.
+    pub save_format: SaveFormat,
+    /// When playtime was last added to the world.
+    last_active: DateTime<Local>,
+    /// Save file the player has been warned already exists; pressing Enter
+    /// again with the same name overwrites it.
+    pending_overwrite: Option<String>,
+    /// How many lines from the end of the earlier turns the Narrative panel
+    /// is scrolled back; 0 shows the current turn.
+    pub narrative_scroll: usize,
+    /// Events of the turn in progress, for its journal entry.
+    turn_events: Vec<String>,
+    /// Where the turn's notes start in `last_narrative`, so the journal
+    /// keeps them apart from the narrative.
+    notes_start: Option<usize>,
 }
 
+/// Longest gap between saves that still counts as playtime; beyond this the
+/// player is assumed to have stepped away.
+const MAX_IDLE_SECS: i64 = 300;
+
 impl Game {
     pub fn new(llm_client: LlmClient) -> Self {
-        let save_manager = SaveManager::new();
+        let mut save_manager = SaveManager::new();
+        save_manager.model_name = llm_client.model_name.clone();
         let save_list = save_manager.list_saves().unwrap_or_default();
 
         Self {
@@ -51,7 +83,35 @@ impl Game {
             current_options: Vec::new(),
             status_message: "".to_string(),
             new_world_name: String::new(),
+            pregenerator: Pregenerator::new(),
+            death_policy: DeathPolicy::default(),
+            save_format: SaveFormat::default(),
+            last_active: Local::now(),
+            pending_overwrite: None,
+            narrative_scroll: 0,
+            turn_events: Vec::new(),
+            notes_start: None,
+        }
+    }
+
+    /// Switch to another save storage, e.g. one picked on the command line.
+    pub fn set_save_manager(&mut self, mut save_manager: SaveManager) {
+        save_manager.model_name = self.llm_client.model_name.clone();
+        self.save_list = save_manager.list_saves().unwrap_or_default();
+        self.selected_save_index = 0;
+        self.save_manager = save_manager;
+    }
+
+    /// Called every frame by the UI. While the player is idle, stores any
+    /// finished background generation and keeps the frontier queue topped up.
+    pub fn tick(&mut self) {
+        if self.state != GameState::WaitingForInput {
+            return;
+        }
+        for note in self.pregenerator.collect(&mut self.world) {
+            self.log(&note);
         }
+        self.pregenerator.fill(&self.world, &self.llm_client);
     }
 
     pub fn log(&mut self, message: &str) {
@@ -61,6 +121,53 @@ impl Game {
         }
     }
 
+    /// Spend the time a single step takes and tick off quest objectives,
+    /// mentioning fired events and quest progress under the narrative.
+    fn finish_step(&mut self) {
+        let mut notes = self.world.advance_time(clock::MINUTES_PER_STEP);
+        notes.extend(self.world.check_quests());
+        self.append_notes(notes);
+    }
+
+    /// Count a finished player action, write it to the journal and save.
+    fn complete_turn(&mut self, input: &str) {
+        self.world.turns += 1;
+        let end = self.notes_start.take().unwrap_or(self.last_narrative.len());
+        let events = std::mem::take(&mut self.turn_events);
+        self.world.record_turn(input, &self.last_narrative[..end], events);
+        self.autosave();
+    }
+
+    /// Save the current world, first adding the time played since the last save
+    /// and archiving journal entries that no longer fit in it.
+    /// A failed save is logged and shown to the player rather than dropped.
+    fn autosave(&mut self) {
+        let now = Local::now();
+        let elapsed = (now - self.last_active).num_seconds().clamp(0, MAX_IDLE_SECS);
+        self.world.playtime_secs += elapsed as u64;
+        self.last_active = now;
+        let Some(path) = self.current_save_path.clone() else { return };
+        let saved = self.save_manager.archive_journal(&path, &self.world.faded_journal)
+            .map(|_| self.world.faded_journal.clear())
+            .and_then(|_| self.save_manager.save_game(&path, &self.world));
+        if let Err(e) = saved {
+            let message = format!("Could not save {}: {:#}", path, e);
+            self.log(&message);
+            self.last_narrative.push_str(&format!("\n\n{}", message));
+            self.status_message = message;
+        }
+    }
+
+    fn append_notes(&mut self, notes: Vec<String>) {
+        for note in notes {
+            self.log(&note);
+            self.notes_start.get_or_insert(self.last_narrative.len());
+            self.turn_events.push(note.clone());
+            self.last_narrative.push('\n');
+            self.last_narrative.push_str(&note);
+        }
+    }
+
     pub async fn process_input(&mut self, input: &str) -> Result<()> {
         let command = Command::from_str(input);
         self.process_command(command).await
@@ -71,142 +178,116 @@ impl Game {
             GameState::SplashScreen => self.handle_splash_command(command).await,
             GameState::NamingWorld => self.handle_naming_command(command).await,
             GameState::WaitingForInput => self.handle_game_command(command).await,
+            GameState::GameOver => self.handle_game_over_command(command),
             _ => Ok(()),
         }
     }
 
-    pub async fn generate_and_move_to(&mut self, target_pos: (i32, i32), direction: &str) -> Result<()> {
-        let (target_x, target_y) = target_pos;
-
-        self.log(&format!("Generating location at ({}, {}) heading {}", target_x, target_y, direction));
+    pub async fn generate_and_move_to(&mut self, target_pos: (i32, i32, i32), direction: &str) -> Result<()> {
+        let (target_x, target_y, target_z) = target_pos;
 
-        let current_loc = self.world.locations.get(&self.world.current_pos)
-            .ok_or_else(|| anyhow::anyhow!("Current location not found"))?;
+        self.log(&format!("Generating location at ({}, {}, {}) heading {}", target_x, target_y, target_z, direction));
 
-        // Collect information about existing neighbors to provide context
-        let mut neighbor_info = String::new();
-        let neighbors = [
-            ("North", (target_x, target_y + 1)),
-            ("South", (target_x, target_y - 1)),
-            ("East", (target_x + 1, target_y)),
-            ("West", (target_x - 1, target_y)),
-        ];
+        self.state = GameState::Processing;
+        self.status_message = format!("Exploring {}...", direction_phrase(direction));
 
-        for (dir, pos) in neighbors.iter() {
-            if let Some(loc) = self.world.locations.get(pos) {
-                neighbor_info.push_str(&format!("- To the {} is '{}': {}\n", dir, loc.name, loc.description));
-            }
+        let report = worldgen::generate_location(&self.llm_client, &mut self.world, target_pos, direction).await;
+        for note in &report.notes {
+            self.log(note);
         }
 
-        if neighbor_info.is_empty() {
-            neighbor_info = "None known.".to_string();
+        self.world.current_pos = target_pos;
+        if let Some(loc) = self.world.locations.get_mut(&target_pos) {
+            loc.visited = true;
         }
 
-        let prompt = format!(
-            r#"Current Location: {} at ({}, {})
-Description: {}
-
-Known Adjacent Locations (for context):
-{}
-
-The player is heading {} toward coordinates ({}, {}).
-This grid cell is currently EMPTY and needs to be generated.
-
-Create a new location at ({}, {}) that fits thematically with the current location and connects logically to any adjacent locations listed above.
-IMPORTANT: All exits must be null (blocked). The game will create actual exit connections automatically.
-
-Return ONLY a valid JSON object:
-{{
-  "name": "Location name",
-  "description": "Description of what the player sees",
-  "image_prompt": "Visual description for generating an image",
-  "exits": {{"north": null, "south": null, "east": null, "west": null}},
-  "items": [],
-  "actors": []
-}}
-
-CRITICAL: 
-- exits MUST be null objects (blocked), NOT strings or booleans
-- items MUST be an empty array []
-- actors MUST be an empty array []
-- NO narrative text, NO extra commentary
-
-Just the JSON. Nothing else."#,
-            current_loc.name,
-            self.world.current_pos.0,
-            self.world.current_pos.1,
-            current_loc.description,
-            neighbor_info,
-            direction, target_x, target_y,
-            target_x, target_y
-        );
-
-        let system_prompt = "You are a world generator for a text adventure game. Create interesting, thematically consistent locations. You MUST output valid JSON only.";
-
-        self.state = GameState::Processing;
-        self.status_message = format!("Exploring {}...", direction);
-
-        match self.llm_client.generate_location(system_prompt, &prompt).await {
-            Ok(mut location) => {
-                location.visited = true;
-                self.world.locations.insert(target_pos, location);
-                self.world.current_pos = target_pos;
-
-                let loc = self.world.locations.get(&target_pos).unwrap();
-                self.last_narrative = format!("You travel {} to {}.\n{}", direction, loc.name, loc.description);
-                self.log(&format!("Created and moved to ({}, {})", target_x, target_y));
-
-                if let Some(path) = &self.current_save_path {
-                    let _ = self.save_manager.save_game(path, &self.world);
-                }
-            }
-            Err(e) => {
-                self.log(&format!("Failed to generate location: {}", e));
-
-                let fallback_loc = Location {
-                    name: format!("Mysterious area ({}, {})", target_x, target_y),
-                    description: "A mysterious place that appeared suddenly.".to_string(),
-                    items: vec![],
-                    actors: vec![],
-                    exits: HashMap::new(),
-                    cached_image_path: None,
-                    image_prompt: "A mysterious location with undefined characteristics.".to_string(),
-                    visited: true,
-                };
-
-                self.world.locations.insert(target_pos, fallback_loc);
-                self.world.current_pos = target_pos;
-
-                let loc = self.world.locations.get(&target_pos).unwrap();
-                self.last_narrative = format!("You travel {} into the unknown.\n{}", direction, loc.description);
-                self.log(&format!("Used fallback location at ({}, {})", target_x, target_y));
-
-                if let Some(path) = &self.current_save_path {
-                    let _ = self.save_manager.save_game(path, &self.world);
-                }
-            }
+        let loc = self.world.locations.get(&target_pos).unwrap();
+        if report.used_fallback {
+            self.last_narrative = format!("You travel {} into the unknown.\n{}", direction_phrase(direction), loc.description);
+            self.log(&format!("Used fallback location at ({}, {}, {})", target_x, target_y, target_z));
+        } else {
+            self.last_narrative = format!("You travel {} to {}.\n{}", direction_phrase(direction), loc.name, loc.description);
+            self.turn_events.push(format!("Discovered {}.", loc.name));
+            self.log(&format!("Created and moved to ({}, {}, {})", target_x, target_y, target_z));
         }
+        self.finish_step();
+        self.complete_turn(&format!("go {}", direction));
 
         self.state = GameState::WaitingForInput;
         self.status_message = "".to_string();
         Ok(())
     }
 
+    /// Switch to a world read from a save. Leaves any upgrade notes in
+    /// `last_narrative` for the caller to put its greeting in front of.
+    fn enter_loaded_world(&mut self, filename: &str, world: WorldState, migrations: Vec<String>) {
+        self.pregenerator.cancel_all();
+        self.world = world;
+        self.current_save_path = Some(filename.to_string());
+        self.last_active = Local::now();
+        self.state = GameState::WaitingForInput;
+        self.status_message.clear();
+        self.last_narrative.clear();
+        self.turn_events.clear();
+        self.notes_start = None;
+        self.narrative_scroll = 0;
+        if !migrations.is_empty() {
+            self.last_narrative.push_str("\n\nUpgraded the save to the current format:");
+            for step in &migrations {
+                self.last_narrative.push_str(&format!("\n  - {}", step));
+            }
+        }
+    }
+
     async fn handle_splash_command(&mut self, command: Command) -> Result<()> {
         match command {
             Command::New => {
                 self.new_world_name.clear();
+                self.pending_overwrite = None;
+                self.status_message.clear();
                 self.state = GameState::NamingWorld;
             }
             Command::Load => {
                 if !self.save_list.is_empty() {
-                    let save = &self.save_list[self.selected_save_index];
-                    self.world = self.save_manager.load_save(&save.filename)?;
-                    self.current_save_path = Some(save.filename.clone());
-                    self.state = GameState::WaitingForInput;
-                    self.last_narrative = format!("Loaded world: {}. What do you want to do?", save.filename);
+                    let filename = self.save_list[self.selected_save_index].filename.clone();
+                    match self.save_manager.load_save(&filename) {
+                        Ok((world, migrations)) => {
+                            let info = &self.save_list[self.selected_save_index];
+                            let (world_file, checkpoint) = (info.world_file(), info.checkpoint().map(str::to_string));
+                            self.enter_loaded_world(&world_file, world, migrations);
+                            self.last_narrative = match checkpoint {
+                                // Carry on from the checkpoint in the world's main save.
+                                Some(name) => {
+                                    self.autosave();
+                                    format!("Returned to checkpoint '{}' of {}. What do you want to do?{}", name, world_file, self.last_narrative)
+                                }
+                                None => format!("Loaded world: {}. What do you want to do?{}", filename, self.last_narrative),
+                            };
+                        }
+                        Err(e) => {
+                            self.log(&format!("Failed to load {}: {:#}", filename, e));
+                            let backups = self.save_manager.list_backups(&filename).unwrap_or_default();
+                            self.status_message = if backups.is_empty() {
+                                format!("Could not load {}: {}", filename, e)
+                            } else {
+                                format!("Could not load {}: {}. Press R to restore a backup ({} kept).", filename, e, backups.len())
+                            };
+                        }
+                    }
                 }
             }
+            Command::RestoreBackup if self.selected_save_index < self.save_list.len() => {
+                let filename = self.save_list[self.selected_save_index].filename.clone();
+                match self.save_manager.restore_backup(&filename) {
+                    Ok((world, migrations, backup)) => {
+                        self.log(&format!("Restored {} from {}", filename, backup));
+                        self.enter_loaded_world(&filename, world, migrations);
+                        self.last_narrative = format!("Restored world {} from backup {}. What do you want to do?{}", filename, backup, self.last_narrative);
+                    }
+                    Err(e) => self.status_message = format!("Could not restore {}: {}", filename, e),
+                }
+            }
+            Command::Import(source) => self.handle_import(&source),
             Command::Up => {
                 if self.selected_save_index > 0 {
                     self.selected_save_index -= 1;
@@ -239,29 +320,56 @@ Just the JSON. Nothing else."#,
     async fn handle_naming_command(&mut self, command: Command) -> Result<()> {
         match command {
             Command::Enter => {
-                if !self.new_world_name.trim().is_empty() {
-                    self.world = WorldState::new();
-                    let start_loc = Location {
-                        name: "The Beginning".to_string(),
-                        description: "You stand in a void of potential. Anything can happen here.".to_string(),
-                        items: vec![],
-                        actors: vec![],
-                        exits: HashMap::new(),
-                        cached_image_path: None,
-                        image_prompt: "A swirling void of colors and shapes, representing potential.".to_string(),
-                        visited: true,
-                    };
-                    self.world.locations.insert((0, 0), start_loc);
-                    let save_name = self.new_world_name.trim();
-                    self.current_save_path = Some(self.save_manager.create_new_save(save_name, &self.world)?);
-                    self.state = GameState::WaitingForInput;
-                    self.last_narrative = format!("Created new world: '{}'. What do you want to do?", save_name);
-                    self.log(&format!("Created new world: {}", save_name));
+                let save_name = self.new_world_name.trim().to_string();
+                let Some(filename) = SaveManager::world_filename(&save_name) else {
+                    if !save_name.is_empty() {
+                        self.status_message = "World names need at least one letter or digit.".to_string();
+                    }
+                    return Ok(());
+                };
+                let overwrite = self.pending_overwrite.as_deref() == Some(filename.as_str());
+                if !overwrite && self.save_manager.save_exists(&filename) {
+                    self.status_message = format!(
+                        "World '{}' already exists. Press Enter again to overwrite it, or edit the name (e.g. '{}').",
+                        filename.trim_end_matches(".json"), self.save_manager.unique_name(&save_name));
+                    self.pending_overwrite = Some(filename);
+                    return Ok(());
+                }
+                self.world = WorldState::new();
+                let start_loc = Location {
+                    name: "The Beginning".to_string(),
+                    description: "You stand in a void of potential. Anything can happen here.".to_string(),
+                    items: vec![],
+                    actors: vec![],
+                    exits: HashMap::new(),
+                    cached_image_path: None,
+                    image_prompt: "A swirling void of colors and shapes, representing potential.".to_string(),
+                    visited: true,
+                };
+                self.world.locations.insert((0, 0, 0), start_loc);
+                self.world.death_policy = self.death_policy;
+                self.world.save_format = self.save_format;
+                self.world.refresh_weather();
+                self.pregenerator.cancel_all();
+                self.last_active = Local::now();
+                self.pending_overwrite = None;
+                match self.save_manager.create_new_save(&save_name, &self.world, overwrite) {
+                    Ok(filename) => self.current_save_path = Some(filename),
+                    Err(e) => {
+                        self.status_message = format!("Could not create world: {}", e);
+                        return Ok(());
+                    }
                 }
+                self.status_message.clear();
+                self.state = GameState::WaitingForInput;
+                self.last_narrative = format!("Created new world: '{}'. What do you want to do?", save_name);
+                self.log(&format!("Created new world: {}", save_name));
             }
             Command::Back => {
                 self.state = GameState::SplashScreen;
                 self.new_world_name.clear();
+                self.pending_overwrite = None;
+                self.status_message.clear();
             }
             Command::Backspace => {
                 self.new_world_name.pop();
@@ -275,18 +383,20 @@ Just the JSON. Nothing else."#,
     }
 
     async fn handle_game_command(&mut self, command: Command) -> Result<()> {
+        let command = command.in_play();
+        // Anything but paging brings the Narrative panel back to the present.
+        if !matches!(command, Command::ScrollBack(_) | Command::ScrollForward(_)) {
+            self.narrative_scroll = 0;
+        }
+        if let Some(direction) = command.move_direction() {
+            return self.handle_quick_movement(direction).await;
+        }
         match command {
-            Command::MoveNorth => {
-                self.handle_quick_movement("north").await?;
-            }
-            Command::MoveSouth => {
-                self.handle_quick_movement("south").await?;
-            }
-            Command::MoveEast => {
-                self.handle_quick_movement("east").await?;
+            Command::ScrollBack(lines) => {
+                self.narrative_scroll += lines;
             }
-            Command::MoveWest => {
-                self.handle_quick_movement("west").await?;
+            Command::ScrollForward(lines) => {
+                self.narrative_scroll = self.narrative_scroll.saturating_sub(lines);
             }
             Command::SelectOption(idx) => {
                 if idx > 0 && idx <= self.current_options.len() {
@@ -295,8 +405,29 @@ Just the JSON. Nothing else."#,
                     self.handle_agent_action(&selected_action).await?;
                 }
             }
+            Command::TravelTo(destination) => {
+                self.handle_travel(&destination);
+            }
+            Command::SaveAs(name) => self.handle_save_as(&name),
+            Command::LoadCheckpoint(name) => self.handle_load_checkpoint(&name),
+            Command::Export(target) => self.handle_export(&target),
+            Command::Import(source) => self.handle_import(&source),
+            Command::Chronicle(args) => self.handle_chronicle(&args),
             Command::TextInput(text) => {
-                self.handle_agent_action(&text).await?;
+                // Typed input arrives raw, so pick out engine commands before asking the DM
+                let command = Command::from_str(&text).in_play();
+                if let Some(direction) = command.move_direction() {
+                    return self.handle_quick_movement(direction).await;
+                }
+                match command {
+                    Command::TravelTo(destination) => self.handle_travel(&destination),
+                    Command::SaveAs(name) => self.handle_save_as(&name),
+                    Command::LoadCheckpoint(name) => self.handle_load_checkpoint(&name),
+                    Command::Export(target) => self.handle_export(&target),
+                    Command::Import(source) => self.handle_import(&source),
+                    Command::Chronicle(args) => self.handle_chronicle(&args),
+                    _ => self.handle_agent_action(&text).await?,
+                }
             }
             _ => {}
         }
@@ -304,6 +435,12 @@ Just the JSON. Nothing else."#,
     }
 
     async fn handle_agent_action(&mut self, action: &str) -> Result<()> {
+        // Keep what the background queue finished, then free the LLM for this turn
+        for note in self.pregenerator.collect(&mut self.world) {
+            self.log(&note);
+        }
+        self.pregenerator.cancel_all();
+
         self.state = GameState::Processing;
         self.status_message = "Thinking...".to_string();
 
@@ -327,14 +464,16 @@ Just the JSON. Nothing else."#,
                         self.last_narrative = response.narrative;
                     } else {
                         self.world = agent.take_world();
-                        if let Some(path) = &self.current_save_path {
-                            let _ = self.save_manager.save_game(path, &self.world);
-                        }
                         self.last_narrative = response.narrative;
+                        self.append_notes(response.notes);
+                        self.complete_turn(action);
                     }
                     self.current_options = response.suggested_actions;
                     self.state = GameState::WaitingForInput;
                     self.status_message = "".to_string();
+                    if self.world.player_defeated {
+                        self.handle_defeat();
+                    }
                     break;
                 }
                 Err(e) => {
@@ -365,29 +504,289 @@ Just the JSON. Nothing else."#,
         Ok(())
     }
 
+    /// Carry out the world's death policy and show the game-over screen.
+    fn handle_defeat(&mut self) {
+        let policy = self.world.death_policy;
+        let summary = death::apply_defeat(&mut self.world);
+        self.log(&format!("Player defeated ({})", policy));
+        if let Some(entry) = self.world.journal.last_mut() {
+            entry.events.push(summary.clone());
+        }
+        self.pregenerator.cancel_all();
+
+        if policy == DeathPolicy::Permadeath {
+            if let Some(path) = self.current_save_path.take() {
+                match self.save_manager.delete_save(&path) {
+                    Ok(()) => self.log(&format!("Permadeath: deleted save {}", path)),
+                    Err(e) => self.log(&format!("Permadeath: failed to delete save {}: {}", path, e)),
+                }
+            }
+        } else {
+            self.autosave();
+        }
+
+        self.last_narrative = format!("{}\n\n{}", self.last_narrative, summary);
+        self.current_options.clear();
+        self.state = GameState::GameOver;
+    }
+
+    fn handle_game_over_command(&mut self, command: Command) -> Result<()> {
+        if !matches!(command, Command::Enter | Command::TextInput(_)) {
+            return Ok(());
+        }
+        if self.world.death_policy != DeathPolicy::Permadeath {
+            self.state = GameState::WaitingForInput;
+            self.current_options = vec!["Look around".to_string()];
+        } else {
+            self.save_list = self.save_manager.list_saves().unwrap_or_default();
+            self.selected_save_index = 0;
+            self.world = WorldState::new();
+            self.last_narrative = "Welcome to the Infinite Text Adventure.".to_string();
+            self.state = GameState::SplashScreen;
+        }
+        Ok(())
+    }
+
+    /// `save as <name>`: snapshot the world as a named checkpoint.
+    fn handle_save_as(&mut self, raw_name: &str) {
+        let Some(world_file) = self.current_save_path.clone() else {
+            self.last_narrative = "This world has no save file to checkpoint.".to_string();
+            return;
+        };
+        let Some(name) = save::sanitize_name(raw_name) else {
+            self.last_narrative = "Give the checkpoint a name, e.g. 'save as before-the-bridge'.".to_string();
+            return;
+        };
+        // Names match whatever their case, so saving over one keeps its spelling.
+        let name = self.save_manager.list_checkpoints(&world_file).unwrap_or_default().iter()
+            .filter_map(|c| c.checkpoint())
+            .find(|c| c.eq_ignore_ascii_case(&name))
+            .map(str::to_string)
+            .unwrap_or(name);
+        self.last_narrative = match self.save_manager.save_checkpoint(&world_file, &name, &self.world) {
+            Ok(_) => format!("Saved checkpoint '{}'. Use 'load checkpoint {}' to come back here.", name, name),
+            Err(e) => format!("Could not save checkpoint '{}': {}", name, e),
+        };
+        self.log(&self.last_narrative.clone());
+    }
+
+    /// `load checkpoint <name>`: go back to a checkpoint, or list them when no name is given.
+    fn handle_load_checkpoint(&mut self, raw_name: &str) {
+        let Some(world_file) = self.current_save_path.clone() else {
+            self.last_narrative = "This world has no checkpoints.".to_string();
+            return;
+        };
+        let checkpoints = self.save_manager.list_checkpoints(&world_file).unwrap_or_default();
+        let wanted = save::sanitize_name(raw_name);
+        let found = wanted.as_deref().and_then(|name| checkpoints.iter()
+            .find(|c| c.checkpoint().is_some_and(|c| c.eq_ignore_ascii_case(name))));
+
+        let Some(checkpoint) = found else {
+            let names: Vec<&str> = checkpoints.iter().filter_map(|c| c.checkpoint()).collect();
+            self.last_narrative = match (wanted, names.is_empty()) {
+                (_, true) => "No checkpoints yet. Use 'save as <name>' to make one.".to_string(),
+                (Some(name), false) => format!("No checkpoint called '{}'. Checkpoints: {}", name, names.join(", ")),
+                (None, false) => format!("Checkpoints: {}. Use 'load checkpoint <name>' to return to one.", names.join(", ")),
+            };
+            return;
+        };
+
+        let name = checkpoint.checkpoint().unwrap_or_default().to_string();
+        match self.save_manager.load_save(&checkpoint.filename) {
+            Ok((world, migrations)) => {
+                self.enter_loaded_world(&world_file, world, migrations);
+                self.autosave();
+                self.last_narrative = format!("Returned to checkpoint '{}'.{}", name, self.last_narrative);
+                self.current_options.clear();
+                self.log(&format!("Loaded checkpoint {}", checkpoint.filename));
+            }
+            Err(e) => self.last_narrative = format!("Could not load checkpoint '{}': {}", name, e),
+        }
+    }
+
+    /// `export [path]`: bundle the world with its checkpoints and images to share it.
+    fn handle_export(&mut self, target: &str) {
+        let Some(world_file) = self.current_save_path.clone() else {
+            self.last_narrative = "This world has no save file to export.".to_string();
+            return;
+        };
+        self.autosave();
+        let file_name = format!("{}{}", world_file.strip_suffix(".json").unwrap_or(&world_file), bundle::BUNDLE_EXTENSION);
+        let result = bundle::export_world(&self.save_manager, &world_file)
+            .and_then(|data| Ok((bundle::write_export(&file_name, target, &data)?, data.len())));
+        self.last_narrative = match result {
+            Ok((place, size)) => format!("Exported this world to {} ({} KB). Anyone can add it with 'import'.", place, size.div_ceil(1024)),
+            Err(e) => format!("Could not export the world: {:#}", e),
+        };
+        self.log(&self.last_narrative.clone());
+    }
+
+    /// `import [path]`: add a world from a bundle and switch to it.
+    fn handle_import(&mut self, source: &str) {
+        let result = bundle::read_bundle(source)
+            .and_then(|data| bundle::import_world(&self.save_manager, &data, self.save_format));
+        let imported = match result {
+            Ok(imported) => imported,
+            Err(e) => {
+                let message = format!("Could not import the bundle: {:#}", e);
+                self.log(&message);
+                if self.state == GameState::SplashScreen {
+                    self.status_message = message;
+                } else {
+                    self.last_narrative = message;
+                }
+                return;
+            }
+        };
+        if self.state != GameState::SplashScreen {
+            self.autosave();
+        }
+        self.enter_loaded_world(&imported.filename, imported.world, imported.migrations);
+        self.save_list = self.save_manager.list_saves().unwrap_or_default();
+        self.current_options.clear();
+        self.last_narrative = format!("Imported world: {}. {} What do you want to do?{}",
+            imported.filename, imported.notes.join(" "), self.last_narrative);
+        self.log(&format!("Imported {}", imported.filename));
+    }
+
+    /// `chronicle [markdown|html] [path]`: write the journal, a gazetteer and a map up as a
+    /// document, never over an earlier one.
+    fn handle_chronicle(&mut self, args: &str) {
+        let (format, target) = args.split_once(char::is_whitespace)
+            .map(|(format, target)| (format, target.trim()))
+            .unwrap_or((args, ""));
+        let format = match format.parse::<ChronicleFormat>() {
+            Ok(format) => format,
+            Err(e) => {
+                self.last_narrative = e;
+                return;
+            }
+        };
+        let name = self.current_save_path.as_deref()
+            .map(|file| file.strip_suffix(".json").unwrap_or(file))
+            .unwrap_or("world")
+            .to_string();
+        // Turns that no longer fit in the save come from its journal archive.
+        let first_kept = self.world.journal.first().map_or(self.world.turns + 1, |e| e.turn);
+        let earlier = match self.current_save_path.clone() {
+            Some(file) => self.save_manager.journal_archive(&file, first_kept).unwrap_or_else(|e| {
+                self.log(&format!("Could not read the journal archive: {:#}", e));
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let text = chronicle::render(&self.world, &name, format, &earlier);
+        let file_name = format!("{}.chronicle.{}", name, format.extension());
+        let turns = earlier.len() + self.world.journal.len();
+        self.last_narrative = match bundle::write_export(&file_name, target, text.as_bytes()) {
+            Ok(place) => format!("Wrote the chronicle of {} turns to {}.", turns, place),
+            Err(e) => format!("Could not write the chronicle: {:#}", e),
+        };
+        self.log(&self.last_narrative.clone());
+    }
+
+    /// Walk the shortest known route to a visited location in one action,
+    /// spending `clock::MINUTES_PER_STEP` per tile and stopping early on an encounter.
+    fn handle_travel(&mut self, destination: &str) {
+        if self.world.combat.active {
+            self.last_narrative = "You can't travel in the middle of a fight.".to_string();
+            return;
+        }
+        let target = match travel::resolve_destination(&self.world, destination) {
+            Ok(pos) => pos,
+            Err(reason) => {
+                self.last_narrative = reason;
+                return;
+            }
+        };
+        let target_name = self.world.locations.get(&target)
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| format!("({}, {}, {})", target.0, target.1, target.2));
+
+        let path = match self.world.find_path(target) {
+            Some(path) if path.is_empty() => {
+                self.last_narrative = format!("You are already at {}.", target_name);
+                return;
+            }
+            Some(path) => path,
+            None => {
+                self.last_narrative = format!("You don't know a way to {} from here.", target_name);
+                return;
+            }
+        };
+
+        let mut steps = 0;
+        let mut interruption = None;
+        let mut notes = Vec::new();
+        for (i, (direction, pos)) in path.iter().enumerate() {
+            // resolve_move unlocks doors for which the player holds the key
+            if self.world.resolve_move(direction) != Ok(*pos) {
+                interruption = Some(format!("The way {} is barred.", direction));
+                break;
+            }
+            self.world.current_pos = *pos;
+            if let Some(loc) = self.world.locations.get_mut(pos) {
+                loc.visited = true;
+            }
+            steps += 1;
+            notes.extend(self.world.advance_time(clock::MINUTES_PER_STEP));
+            // Objectives along the way count, not just the destination
+            notes.extend(self.world.check_quests());
+
+            if i + 1 < path.len() {
+                if let Some(reason) = travel::encounter_at(&self.world, *pos, rand::random()) {
+                    interruption = Some(reason);
+                    break;
+                }
+            }
+        }
+
+        let duration = travel::format_duration(steps * clock::MINUTES_PER_STEP);
+        let here = self.world.locations.get(&self.world.current_pos).cloned()
+            .unwrap_or_else(|| worldgen::fallback_location(self.world.current_pos));
+        self.last_narrative = match interruption {
+            None => format!("You travel to {} ({} steps, {}).\n{}", here.name, steps, duration, here.description),
+            Some(reason) => {
+                self.current_options = vec!["Look around".to_string(), format!("travel to {}", target_name)];
+                format!("You set out for {} but stop at {} after {} steps ({}). {}\n{}",
+                    target_name, here.name, steps, duration, reason, here.description)
+            }
+        };
+        self.append_notes(notes);
+        self.log(&format!("Travelled {} of {} steps towards ({}, {}, {})", steps, path.len(), target.0, target.1, target.2));
+
+        self.complete_turn(&format!("travel to {}", destination));
+    }
+
     async fn handle_quick_movement(&mut self, direction: &str) -> Result<()> {
-        let (x, y) = self.world.current_pos;
-        let target_pos = match direction {
-            "north" => (x, y + 1),
-            "south" => (x, y - 1),
-            "east" => (x + 1, y),
-            "west" => (x - 1, y),
-            _ => return Ok(()),
+        for note in self.pregenerator.collect(&mut self.world) {
+            self.log(&note);
+        }
+
+        let target_pos = match self.world.resolve_move(direction) {
+            Ok(pos) => pos,
+            Err(reason) => {
+                self.last_narrative = reason;
+                self.log(&format!("Quick move {} refused by exit", direction));
+                return Ok(());
+            }
         };
 
         // If location exists, quick move (no LLM)
         if let Some(target_loc) = self.world.locations.get(&target_pos).cloned() {
+            let from = self.world.current_pos;
+            self.world.link_exits(from, target_pos, direction);
             self.world.current_pos = target_pos;
             if let Some(loc) = self.world.locations.get_mut(&target_pos) {
                 loc.visited = true;
             }
-            self.last_narrative = format!("You move {} to {}.\n{}", direction, target_loc.name, target_loc.description);
-            self.log(&format!("Quick move {} to existing location ({}, {})", direction, target_pos.0, target_pos.1));
-            if let Some(path) = &self.current_save_path {
-                let _ = self.save_manager.save_game(path, &self.world);
-            }
+            self.last_narrative = format!("You move {} to {}.\n{}", direction_phrase(direction), target_loc.name, target_loc.description);
+            self.log(&format!("Quick move {} to existing location ({}, {}, {})", direction, target_pos.0, target_pos.1, target_pos.2));
+            self.finish_step();
+            self.complete_turn(&format!("go {}", direction));
         } else {
             // New location - must use LLM
+            self.pregenerator.cancel_all();
             self.generate_and_move_to(target_pos, direction).await?;
         }
 
@@ -399,6 +798,14 @@ Just the JSON. Nothing else."#,
 mod tests {
     use super::*;
 
+    /// A game saving into a fresh temp directory, which the caller removes.
+    fn game_with_temp_saves() -> (Game, std::path::PathBuf) {
+        let (save_manager, dir) = crate::save::temp_manager();
+        let mut game = Game::new(LlmClient::new("http://localhost:11434".to_string(), "test".to_string()));
+        game.set_save_manager(save_manager);
+        (game, dir)
+    }
+
     #[test]
     fn test_game_creation() {
         let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
@@ -430,4 +837,265 @@ mod tests {
         assert!(!game.debug_log.iter().any(|msg| msg.contains("Message 0")));
         assert!(game.debug_log.iter().any(|msg| msg.contains("Message 104")));
     }
+
+    #[tokio::test]
+    async fn test_tick_only_pregenerates_while_idle() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut game = Game::new(llm_client);
+        game.world.locations.insert((0, 0, 0), crate::worldgen::fallback_location((0, 0, 0)));
+
+        game.tick();
+        assert_eq!(game.pregenerator.in_flight(), 0);
+
+        game.state = GameState::WaitingForInput;
+        game.tick();
+        assert_eq!(game.pregenerator.in_flight(), crate::pregen::MAX_IN_FLIGHT);
+        game.pregenerator.cancel_all();
+    }
+
+    #[tokio::test]
+    async fn test_travel_command_moves_and_passes_time() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut game = Game::new(llm_client);
+        game.state = GameState::WaitingForInput;
+        for (pos, name) in [((0, 0, 0), "Camp"), ((0, 1, 0), "Watchtower")] {
+            let mut loc = worldgen::fallback_location(pos);
+            loc.name = name.to_string();
+            loc.visited = true;
+            game.world.locations.insert(pos, loc);
+        }
+        game.world.link_exits((0, 0, 0), (0, 1, 0), "north");
+
+        game.process_command(Command::TextInput("Travel to watchtower".to_string())).await.unwrap();
+        assert_eq!(game.world.current_pos, (0, 1, 0));
+        assert_eq!(game.world.clock.minutes, clock::MINUTES_PER_STEP);
+
+        game.process_command(Command::TravelTo("Nowhere".to_string())).await.unwrap();
+        assert_eq!(game.world.current_pos, (0, 1, 0));
+        assert!(game.last_narrative.contains("Nowhere"));
+
+        game.world.combat.active = true;
+        game.process_command(Command::TravelTo("Camp".to_string())).await.unwrap();
+        assert_eq!(game.world.current_pos, (0, 1, 0));
+        assert!(game.last_narrative.contains("fight"), "{}", game.last_narrative);
+        assert_eq!(game.world.clock.minutes, clock::MINUTES_PER_STEP);
+    }
+
+    #[tokio::test]
+    async fn test_bare_up_and_down_move_between_levels_in_play() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut game = Game::new(llm_client);
+        game.state = GameState::WaitingForInput;
+        for pos in [(0, 0, 0), (0, 0, 1)] {
+            game.world.locations.insert(pos, worldgen::fallback_location(pos));
+        }
+        game.world.locations.get_mut(&(0, 0, 0)).unwrap()
+            .exits.insert("up".to_string(), crate::model::Exit::Open { to: (0, 0, 1) });
+
+        game.process_input("up").await.unwrap();
+        assert_eq!(game.world.current_pos, (0, 0, 1));
+        game.process_command(Command::TextInput("Down".to_string())).await.unwrap();
+        assert_eq!(game.world.current_pos, (0, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_turns_are_journaled() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut game = Game::new(llm_client);
+        game.state = GameState::WaitingForInput;
+        for (pos, name) in [((0, 0, 0), "Camp"), ((0, 1, 0), "Watchtower")] {
+            let mut loc = worldgen::fallback_location(pos);
+            loc.name = name.to_string();
+            loc.visited = true;
+            game.world.locations.insert(pos, loc);
+        }
+        game.world.link_exits((0, 0, 0), (0, 1, 0), "north");
+
+        game.process_command(Command::MoveNorth).await.unwrap();
+        game.last_narrative = "You wait.".to_string();
+        game.append_notes(vec!["A bell tolls.".to_string()]);
+        game.complete_turn("wait");
+
+        let journal = &game.world.journal;
+        assert_eq!(journal.len(), 2);
+        assert_eq!((journal[0].turn, journal[0].input.as_str(), journal[0].location.as_str()), (1, "go north", "Watchtower"));
+        assert_eq!(journal[1].narrative, "You wait.");
+        assert_eq!(journal[1].events, vec!["A bell tolls."]);
+
+        game.process_command(Command::ScrollBack(10)).await.unwrap();
+        game.process_command(Command::ScrollBack(3)).await.unwrap();
+        assert_eq!(game.narrative_scroll, 13);
+        game.process_command(Command::ScrollForward(10)).await.unwrap();
+        assert_eq!(game.narrative_scroll, 3);
+        game.process_command(Command::ScrollForward(10)).await.unwrap();
+        assert_eq!(game.narrative_scroll, 0);
+        game.process_command(Command::ScrollBack(2)).await.unwrap();
+        game.process_command(Command::MoveSouth).await.unwrap();
+        assert_eq!(game.narrative_scroll, 0);
+    }
+
+    #[tokio::test]
+    async fn test_defeat_shows_game_over_then_resumes() {
+        let llm_client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let mut game = Game::new(llm_client);
+        game.world.locations.insert((0, 0, 0), worldgen::fallback_location((0, 0, 0)));
+        game.world.locations.insert((1, 0, 0), worldgen::fallback_location((1, 0, 0)));
+        game.world.current_pos = (1, 0, 0);
+        game.world.player_defeated = true;
+        game.state = GameState::WaitingForInput;
+
+        game.handle_defeat();
+        assert_eq!(game.state, GameState::GameOver);
+        assert_eq!(game.world.current_pos, (0, 0, 0));
+
+        game.process_command(Command::MoveNorth).await.unwrap();
+        assert_eq!(game.state, GameState::GameOver);
+        game.process_command(Command::Enter).await.unwrap();
+        assert_eq!(game.state, GameState::WaitingForInput);
+
+        game.world.death_policy = DeathPolicy::Permadeath;
+        game.world.player_defeated = true;
+        game.handle_defeat();
+        game.process_command(Command::Enter).await.unwrap();
+        assert_eq!(game.state, GameState::SplashScreen);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_branch_and_return() {
+        let (mut game, dir) = game_with_temp_saves();
+        game.world.locations.insert((0, 0, 0), worldgen::fallback_location((0, 0, 0)));
+        game.current_save_path = Some(game.save_manager.create_new_save("w", &game.world, false).unwrap());
+        game.state = GameState::WaitingForInput;
+
+        game.world.player.money = 3;
+        game.process_command(Command::TextInput("save as At the Gate".to_string())).await.unwrap();
+        assert!(game.last_narrative.contains("Saved checkpoint 'At-the-Gate'"));
+        // Saving again in another case replaces it rather than adding a second one.
+        game.process_input("save as at the gate").await.unwrap();
+        assert!(game.last_narrative.contains("Saved checkpoint 'At-the-Gate'"));
+        assert_eq!(game.save_manager.list_checkpoints("w.json").unwrap().len(), 1);
+
+        game.world.player.money = 50;
+        game.process_input("load checkpoint").await.unwrap();
+        assert!(game.last_narrative.contains("At-the-Gate"));
+        assert_eq!(game.world.player.money, 50);
+
+        game.process_input("load checkpoint at-the-gate").await.unwrap();
+        assert_eq!(game.world.player.money, 3);
+        assert_eq!(game.current_save_path.as_deref(), Some("w.json"));
+        assert_eq!(game.save_manager.load_save("w.json").unwrap().0.player.money, 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_existing_world_needs_confirmation() {
+        let (mut game, dir) = game_with_temp_saves();
+        game.world.player.money = 99;
+        game.save_manager.save_game("Keep.json", &game.world).unwrap();
+
+        game.state = GameState::NamingWorld;
+        game.new_world_name = "Keep".to_string();
+        game.process_command(Command::Enter).await.unwrap();
+        assert_eq!(game.state, GameState::NamingWorld);
+        assert!(game.status_message.contains("'Keep-2'"));
+        assert_eq!(game.save_manager.load_save("Keep.json").unwrap().0.player.money, 99);
+
+        game.process_command(Command::Enter).await.unwrap();
+        assert_eq!(game.state, GameState::WaitingForInput);
+        assert_eq!(game.current_save_path.as_deref(), Some("Keep.json"));
+        assert_eq!(game.save_manager.load_save("Keep.json").unwrap().0.player.money, 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_switches_world() {
+        let (mut game, dir) = game_with_temp_saves();
+        game.world.locations.insert((0, 0, 0), worldgen::fallback_location((0, 0, 0)));
+        game.world.player.money = 21;
+        game.current_save_path = Some(game.save_manager.create_new_save("Shared", &game.world, false).unwrap());
+        game.state = GameState::WaitingForInput;
+
+        std::fs::create_dir_all(&dir).unwrap();
+        game.process_input(&format!("export {}", dir.display())).await.unwrap();
+        let bundle = dir.join("Shared.adventure.tgz");
+        assert!(bundle.exists(), "{}", game.last_narrative);
+
+        game.world.player.money = 0;
+        game.process_input(&format!("import {}", bundle.display())).await.unwrap();
+        assert_eq!(game.current_save_path.as_deref(), Some("Shared-2.json"));
+        assert_eq!(game.world.player.money, 21);
+        assert!(game.save_list.iter().any(|s| s.filename == "Shared-2.json"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chronicle_never_replaces_an_earlier_one() {
+        let (mut game, dir) = game_with_temp_saves();
+        game.current_save_path = Some("Ashford.json".to_string());
+        game.state = GameState::WaitingForInput;
+        std::fs::create_dir_all(&dir).unwrap();
+
+        game.process_input(&format!("chronicle md {}", dir.display())).await.unwrap();
+        game.process_input(&format!("chronicle markdown {}", dir.display())).await.unwrap();
+        assert!(dir.join("Ashford.chronicle.md").exists());
+        assert!(dir.join("Ashford-2.chronicle.md").exists(), "{}", game.last_narrative);
+
+        game.process_input(&format!("chronicle html {}", dir.join("Ashford.chronicle.md").display())).await.unwrap();
+        assert!(game.last_narrative.starts_with("Could not write the chronicle"), "{}", game.last_narrative);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chronicle_includes_turns_archived_from_the_save() {
+        use crate::model::MAX_JOURNAL_ENTRIES;
+        let (mut game, dir) = game_with_temp_saves();
+        game.current_save_path = Some("Ashford.json".to_string());
+        game.state = GameState::WaitingForInput;
+        for turn in 1..=(MAX_JOURNAL_ENTRIES as u32 + 5) {
+            game.world.turns = turn;
+            game.world.record_turn("wait", &format!("Moment {}.", turn), Vec::new());
+        }
+        game.autosave();
+        assert!(game.world.faded_journal.is_empty());
+
+        game.process_input(&format!("chronicle md {}", dir.display())).await.unwrap();
+        assert!(game.last_narrative.contains(&format!("{} turns", MAX_JOURNAL_ENTRIES + 5)), "{}", game.last_narrative);
+        let chronicle = std::fs::read_to_string(dir.join("Ashford.chronicle.md")).unwrap();
+        assert!(chronicle.contains("Moment 1.") && chronicle.contains("Moment 505."));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_autosave_is_reported() {
+        let (mut game, blocker) = game_with_temp_saves();
+        // A file where the save directory should be makes every write fail.
+        std::fs::write(&blocker, "").unwrap();
+        game.current_save_path = Some("w.json".to_string());
+
+        game.autosave();
+        assert!(game.status_message.starts_with("Could not save w.json"), "{}", game.status_message);
+        assert!(game.last_narrative.contains("Could not save w.json"));
+        std::fs::remove_file(blocker).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_save_offers_backup() {
+        let (mut game, dir) = game_with_temp_saves();
+        game.world.player.money = 9;
+        game.save_manager.save_game("w.json", &game.world).unwrap();
+        game.save_manager.save_game("w.json", &game.world).unwrap();
+        std::fs::write(dir.join("w.json"), "{").unwrap();
+        game.save_list = game.save_manager.list_saves().unwrap();
+
+        game.process_command(Command::Load).await.unwrap();
+        assert_eq!(game.state, GameState::SplashScreen);
+        assert!(game.status_message.contains("restore a backup"));
+
+        game.world = WorldState::new();
+        game.process_command(Command::RestoreBackup).await.unwrap();
+        assert_eq!(game.state, GameState::WaitingForInput);
+        assert_eq!(game.world.player.money, 9);
+        assert!(game.last_narrative.contains("w.json.bak1"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
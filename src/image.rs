@@ -5,8 +5,8 @@ use std::path::PathBuf;
 
 #[async_trait::async_trait]
 pub trait ImageCache {
-    async fn get_cached_path(&self, pos: &(i32, i32)) -> Option<String>;
-    async fn save_image(&self, pos: &(i32, i32), data: &[u8]) -> Result<String>;
+    async fn get_cached_path(&self, pos: &(i32, i32, i32)) -> Option<String>;
+    async fn save_image(&self, pos: &(i32, i32, i32), data: &[u8]) -> Result<String>;
 }
 
 #[async_trait::async_trait]
@@ -24,7 +24,7 @@ impl<C: ImageCache, G: ImageGenerator> ImageManager<C, G> {
         Self { cache, generator }
     }
 
-    pub async fn get_image_for_location(&self, pos: &(i32, i32), location: &Location) -> Result<String> {
+    pub async fn get_image_for_location(&self, pos: &(i32, i32, i32), location: &Location) -> Result<String> {
         if let Some(path) = self.cache.get_cached_path(pos).await {
             return Ok(path);
         }
@@ -49,6 +49,11 @@ impl ImageGenerator for MockImageGenerator {
     }
 }
 
+/// Name a cached location image is stored under.
+pub fn image_filename(pos: &(i32, i32, i32)) -> String {
+    format!("{}_{}_{}.png", pos.0, pos.1, pos.2)
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub struct FileSystemCache {
     base_dir: PathBuf,
@@ -65,8 +70,8 @@ impl FileSystemCache {
 #[cfg(not(target_arch = "wasm32"))]
 #[async_trait::async_trait]
 impl ImageCache for FileSystemCache {
-    async fn get_cached_path(&self, pos: &(i32, i32)) -> Option<String> {
-        let path = self.base_dir.join(format!("{}_{}.png", pos.0, pos.1));
+    async fn get_cached_path(&self, pos: &(i32, i32, i32)) -> Option<String> {
+        let path = self.base_dir.join(image_filename(pos));
         if path.exists() {
             Some(path.to_string_lossy().to_string())
         } else {
@@ -74,8 +79,8 @@ impl ImageCache for FileSystemCache {
         }
     }
 
-    async fn save_image(&self, pos: &(i32, i32), data: &[u8]) -> Result<String> {
-        let path = self.base_dir.join(format!("{}_{}.png", pos.0, pos.1));
+    async fn save_image(&self, pos: &(i32, i32, i32), data: &[u8]) -> Result<String> {
+        let path = self.base_dir.join(image_filename(pos));
         tokio::fs::write(&path, data).await?;
         Ok(path.to_string_lossy().to_string())
     }
@@ -94,11 +99,11 @@ impl InMemoryCache {
 #[cfg(target_arch = "wasm32")]
 #[async_trait::async_trait]
 impl ImageCache for InMemoryCache {
-    async fn get_cached_path(&self, _pos: &(i32, i32)) -> Option<String> {
+    async fn get_cached_path(&self, _pos: &(i32, i32, i32)) -> Option<String> {
         None
     }
 
-    async fn save_image(&self, _pos: &(i32, i32), _data: &[u8]) -> Result<String> {
+    async fn save_image(&self, _pos: &(i32, i32, i32), _data: &[u8]) -> Result<String> {
         // Create Blob URL
         Ok("blob:dummy".to_string())
     }
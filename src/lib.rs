@@ -1,4 +1,10 @@
 pub mod model;
+pub mod biome;
+pub mod clock;
+pub mod weather;
+pub mod faction;
+pub mod magic;
+pub mod death;
 pub mod llm;
 pub mod llm_tests;
 pub mod game;
@@ -7,7 +13,15 @@ pub mod tui;
 pub mod cli;
 pub mod image;
 pub mod save;
+pub mod migration;
+pub mod bundle;
+pub mod chronicle;
+#[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+pub mod sqlite;
 pub mod parsing;
+pub mod worldgen;
+pub mod pregen;
+pub mod travel;
 pub mod tools;
 pub mod agent;
 pub mod commands;
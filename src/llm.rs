@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
-use crate::model::{WorldUpdate, Location};
+use crate::model::{WorldUpdate, Location, GeneratedLocation, ActorSpec, ItemSpec, RegionSpec};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
@@ -77,7 +77,7 @@ impl LlmClient {
         self.parse_content(content)
     }
 
-    pub async fn generate_location(&self, system_prompt: &str, user_input: &str) -> Result<Location> {
+    pub async fn generate_location(&self, system_prompt: &str, user_input: &str) -> Result<GeneratedLocation> {
         let request = LlmRequest {
             model: self.model_name.clone(),
             messages: vec![
@@ -109,7 +109,7 @@ impl LlmClient {
         let content = response_json["choices"][0]["message"]["content"].as_str()
             .context("No content in LLM response")?;
 
-        self.parse_location_json(content)
+        self.parse_generated_location(content)
     }
 
     pub async fn send_chat_request(&self, request: &crate::agent::LlmRequest) -> Result<serde_json::Value> {
@@ -154,6 +154,69 @@ impl LlmClient {
         Err(anyhow::anyhow!("No JSON object found in LLM response. Content: {}", cleaned_content))
     }
 
+    /// Parse a generated location whose `items` and `actors` may hold full
+    /// definitions rather than IDs, plus an optional `region`. Definitions are split out; ones that don't
+    /// fit the schema are reported in `rejected` instead of failing the room.
+    pub fn parse_generated_location(&self, content: &str) -> Result<GeneratedLocation> {
+        let cleaned_content = content.trim();
+        let mut value: Option<serde_json::Value> = None;
+        if self.is_complete_json(cleaned_content) {
+            if let (Some(start), Some(end)) = (cleaned_content.find('{'), cleaned_content.rfind('}')) {
+                value = serde_json::from_str(&cleaned_content[start..=end]).ok();
+            }
+        }
+        // Let parse_location_json produce its detailed error for anything malformed
+        let Some(mut value) = value.filter(|v| v.is_object()) else {
+            return self.parse_location_json(content).map(GeneratedLocation::from);
+        };
+
+        let mut generated = GeneratedLocation::from(Location {
+            name: String::new(),
+            description: String::new(),
+            items: Vec::new(),
+            actors: Vec::new(),
+            exits: Default::default(),
+            cached_image_path: None,
+            image_prompt: String::new(),
+            visited: false,
+        });
+
+        let mut item_ids = Vec::new();
+        for entry in take_array(&mut value, "items") {
+            match entry {
+                serde_json::Value::String(id) => item_ids.push(id),
+                other => match ItemSpec::from_value(other) {
+                    Ok(spec) => generated.items.push(spec),
+                    Err(e) => generated.rejected.push(format!("Discarded generated item: {}", e)),
+                },
+            }
+        }
+
+        let mut actor_ids = Vec::new();
+        for entry in take_array(&mut value, "actors") {
+            match entry {
+                serde_json::Value::String(id) => actor_ids.push(id),
+                other => match serde_json::from_value::<ActorSpec>(other) {
+                    Ok(spec) => generated.actors.push(spec),
+                    Err(e) => generated.rejected.push(format!("Discarded generated actor: {}", e)),
+                },
+            }
+        }
+
+        match value.get_mut("region").map(serde_json::Value::take) {
+            None | Some(serde_json::Value::Null) => {}
+            Some(region) => match serde_json::from_value::<RegionSpec>(region) {
+                Ok(spec) => generated.region = Some(spec),
+                Err(e) => generated.rejected.push(format!("Discarded generated region: {}", e)),
+            },
+        }
+
+        value["items"] = serde_json::json!(item_ids);
+        value["actors"] = serde_json::json!(actor_ids);
+        generated.location = self.parse_location_json(&value.to_string())?;
+        Ok(generated)
+    }
+
     pub fn parse_location_json(&self, content: &str) -> Result<Location> {
         let cleaned_content = content.trim();
 
@@ -172,7 +235,14 @@ impl LlmClient {
         if let (Some(start), Some(end)) = (json_start, json_end) {
             let json_str = &cleaned_content[start..=end];
 
-            match serde_json::from_str::<Location>(json_str) {
+            // The game links exits itself, so whatever the generator put there is dropped.
+            let parsed = serde_json::from_str::<serde_json::Value>(json_str).and_then(|mut value| {
+                if let Some(obj) = value.as_object_mut() {
+                    obj.remove("exits");
+                }
+                serde_json::from_value::<Location>(value)
+            });
+            match parsed {
                 Ok(mut loc) => {
                     loc.visited = false;
                     Ok(loc)
@@ -183,7 +253,6 @@ impl LlmClient {
 
                     if json_value.get("name").is_none() { missing_fields.push("name"); }
                     if json_value.get("description").is_none() { missing_fields.push("description"); }
-                    if json_value.get("exits").is_none() { missing_fields.push("exits"); }
                     if json_value.get("items").is_none() { missing_fields.push("items"); }
                     if json_value.get("actors").is_none() { missing_fields.push("actors"); }
 
@@ -260,3 +329,11 @@ impl LlmClient {
         brace_count == 0 && bracket_count == 0 && !in_string
     }
 }
+
+/// Remove `key` from a JSON object and return it as an array (empty if missing or not an array).
+fn take_array(value: &mut serde_json::Value, key: &str) -> Vec<serde_json::Value> {
+    match value.get_mut(key).map(serde_json::Value::take) {
+        Some(serde_json::Value::Array(entries)) => entries,
+        _ => Vec::new(),
+    }
+}
@@ -81,6 +81,23 @@ mod location_parsing_tests {
         let result = client.parse_location_json(json);
         assert!(result.is_ok(), "Failed to parse location with whitespace: {:?}", result);
     }
+
+    #[test]
+    fn test_parse_generated_location_with_definitions() {
+        let client = LlmClient::new("http://localhost:11434".to_string(), "test".to_string());
+        let json = r#"{"name":"Smithy","description":"A hot forge","image_prompt":"Forge","exits":{"north":null},
+            "items":[{"name":"Iron Sword","description":"Freshly forged","item_type":"weapon","properties":{"damage":6}},{"name":"Mystery"}],
+            "actors":[{"name":"Bram the Smith","description":"Hammering","money":12}],
+            "region":{"name":"Ironhold","description":"A mining town"}}"#;
+        let generated = client.parse_generated_location(json).unwrap();
+        assert_eq!(generated.location.name, "Smithy");
+        assert!(generated.location.items.is_empty());
+        assert_eq!(generated.items.len(), 1);
+        assert_eq!(generated.items[0].properties.damage, Some(6));
+        assert_eq!(generated.actors[0].money, 12);
+        assert_eq!(generated.rejected.len(), 1);
+        assert!(matches!(generated.region, Some(crate::model::RegionSpec::New { ref name, .. }) if name == "Ironhold"));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{StatusType, WorldState};
+
+/// Mana a new character starts with and can hold.
+pub const STARTING_MANA: u32 = 30;
+
+/// In-game minutes it takes to recover one point of mana.
+pub const MINUTES_PER_MANA: u64 = 15;
+
+/// Strongest effects a spell may have; keeps invented spells in line with
+/// weapons (5 damage bare-handed, 20 healing from a potion).
+pub const MAX_SPELL_DAMAGE: u32 = 30;
+pub const MAX_SPELL_HEAL: u32 = 40;
+pub const MAX_STATUS_DURATION: u32 = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Spell {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub mana_cost: u32,
+    pub effect: SpellEffect,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpellEffect {
+    /// Hurts one enemy in combat, ignoring armour.
+    Damage { amount: u32 },
+    /// Restores HP in combat; outside combat it cures lasting effects.
+    Heal { amount: u32 },
+    /// Puts a status effect on one enemy in combat.
+    ApplyStatus { status: StatusType, duration: u32, severity: u32 },
+    Utility { utility: Utility },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Utility {
+    /// Light to see by; purely descriptive.
+    Light,
+    /// Unlocks a locked door in a given direction.
+    Unlock,
+    /// Turns hidden passages here into open ones.
+    Reveal,
+}
+
+impl SpellEffect {
+    /// Whether the spell needs an enemy to aim at.
+    pub fn is_offensive(&self) -> bool {
+        matches!(self, SpellEffect::Damage { .. } | SpellEffect::ApplyStatus { .. })
+    }
+
+    /// The least a spell with this effect may cost.
+    pub fn minimum_cost(&self) -> u32 {
+        match self {
+            SpellEffect::Damage { amount } => amount.div_ceil(3),
+            SpellEffect::Heal { amount } => amount.div_ceil(4),
+            SpellEffect::ApplyStatus { duration, severity, .. } => 2 * duration + severity,
+            SpellEffect::Utility { utility: Utility::Light } => 1,
+            SpellEffect::Utility { .. } => 4,
+        }
+    }
+}
+
+impl Spell {
+    /// Check an invented spell against the engine's limits.
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.effect {
+            SpellEffect::Damage { amount } if *amount == 0 || *amount > MAX_SPELL_DAMAGE => {
+                return Err(format!("Damage must be 1-{}", MAX_SPELL_DAMAGE));
+            }
+            SpellEffect::Heal { amount } if *amount == 0 || *amount > MAX_SPELL_HEAL => {
+                return Err(format!("Healing must be 1-{}", MAX_SPELL_HEAL));
+            }
+            SpellEffect::ApplyStatus { duration, .. } if *duration == 0 || *duration > MAX_STATUS_DURATION => {
+                return Err(format!("Status duration must be 1-{}", MAX_STATUS_DURATION));
+            }
+            _ => {}
+        }
+        let minimum = self.effect.minimum_cost();
+        if self.mana_cost < minimum {
+            return Err(format!("{} must cost at least {} mana", self.name, minimum));
+        }
+        Ok(())
+    }
+}
+
+/// Spells that exist in every world.
+pub fn builtin_spells() -> Vec<Spell> {
+    let spell = |id: &str, name: &str, description: &str, mana_cost, effect| Spell {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        mana_cost,
+        effect,
+    };
+    vec![
+        spell("firebolt", "Firebolt", "A dart of flame that scorches one foe.", 5, SpellEffect::Damage { amount: 12 }),
+        spell("mend", "Mend", "Knits wounds closed.", 6, SpellEffect::Heal { amount: 20 }),
+        spell("frost_bind", "Frost Bind", "Rime creeps over a foe, slowing and numbing them.", 8,
+            SpellEffect::ApplyStatus { status: StatusType::Frozen, duration: 2, severity: 3 }),
+        spell("light", "Light", "A steady glow gathers in your palm.", 1, SpellEffect::Utility { utility: Utility::Light }),
+        spell("knock", "Knock", "Locks spring open at a word.", 6, SpellEffect::Utility { utility: Utility::Unlock }),
+        spell("reveal", "Reveal", "Hidden ways shimmer into view.", 4, SpellEffect::Utility { utility: Utility::Reveal }),
+    ]
+}
+
+/// Look up a spell in the world's own spells first, then the built-in ones.
+pub fn find_spell(world: &WorldState, spell_id: &str) -> Option<Spell> {
+    world.spells.get(spell_id).cloned()
+        .or_else(|| builtin_spells().into_iter().find(|s| s.id == spell_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_spells_are_valid() {
+        for spell in builtin_spells() {
+            assert_eq!(spell.validate(), Ok(()), "{}", spell.id);
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_cheap_or_strong_spells() {
+        let mut spell = Spell {
+            id: "doom".to_string(),
+            name: "Doom".to_string(),
+            description: String::new(),
+            mana_cost: 1,
+            effect: SpellEffect::Damage { amount: 30 },
+        };
+        assert!(spell.validate().is_err());
+        spell.mana_cost = 10;
+        assert!(spell.validate().is_ok());
+        spell.effect = SpellEffect::Damage { amount: 99 };
+        assert!(spell.validate().is_err());
+    }
+
+    #[test]
+    fn test_effect_json() {
+        let effect: SpellEffect = serde_json::from_str(r#"{"type":"apply_status","status":"Burning","duration":2,"severity":2}"#).unwrap();
+        assert!(effect.is_offensive());
+        let effect: SpellEffect = serde_json::from_str(r#"{"type":"utility","utility":"unlock"}"#).unwrap();
+        assert_eq!(effect, SpellEffect::Utility { utility: Utility::Unlock });
+    }
+}
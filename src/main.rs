@@ -1,7 +1,11 @@
 use llm_text_adventure::{Game, LlmClient, Tui, Cli};
+use llm_text_adventure::death::DeathPolicy;
+use llm_text_adventure::model::SaveFormat;
+use llm_text_adventure::save::{self, SaveManager, StorageBackend};
 use anyhow::Result;
 use clap::Parser;
 use std::env;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "llm-text-adventure")]
@@ -9,6 +13,14 @@ use std::env;
 struct CliArgs {
     #[arg(long, help = "Run in debug CLI mode with stdin/stdout")]
     llm_mode: bool,
+    #[arg(long, help = "What happens in new worlds when the player dies: permadeath, respawn or drop-inventory")]
+    death_policy: Option<DeathPolicy>,
+    #[arg(long, help = "How new worlds are saved: json or gzip")]
+    save_format: Option<SaveFormat>,
+    #[arg(long, help = "Where saves are kept: files (one JSON file per world) or sqlite")]
+    storage: Option<StorageBackend>,
+    #[arg(long, help = "Directory saves are kept in (default: the platform data directory)")]
+    save_dir: Option<PathBuf>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -30,7 +42,43 @@ async fn main() -> Result<()> {
     let model_name = env::var("LLM_MODEL_NAME").unwrap_or_else(|_| "qwen3-coder-30b-a3b-instruct".to_string());
 
     let llm_client = LlmClient::new(base_url, model_name);
+    let death_policy = match args.death_policy {
+        Some(policy) => policy,
+        None => env::var("DEATH_POLICY").ok()
+            .map(|v| v.parse().map_err(anyhow::Error::msg))
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    let save_format = match args.save_format {
+        Some(format) => format,
+        None => env::var("SAVE_FORMAT").ok()
+            .map(|v| v.parse().map_err(anyhow::Error::msg))
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
+    let storage = match args.storage {
+        Some(storage) => storage,
+        None => env::var("SAVE_STORAGE").ok()
+            .map(|v| v.parse().map_err(anyhow::Error::msg))
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
+    let save_dir = args.save_dir
+        .or_else(|| env::var_os("LLM_SAVE_DIR").map(PathBuf::from))
+        .unwrap_or_else(save::default_save_dir);
+
     let mut game = Game::new(llm_client);
+    game.death_policy = death_policy;
+    game.save_format = save_format;
+    let legacy_saves = save::legacy_save_count(Path::new(save::LEGACY_SAVE_DIR), &save_dir);
+    if legacy_saves > 0 {
+        game.status_message = format!(
+            "Found {} save(s) from an older version in ./{}. Run with --save-dir {} to play them, or move them to {}.",
+            legacy_saves, save::LEGACY_SAVE_DIR, save::LEGACY_SAVE_DIR, save_dir.display());
+    }
+    game.set_save_manager(SaveManager::open(storage, save_dir)?);
 
     if args.llm_mode {
         let mut cli = Cli::new();
@@ -0,0 +1,345 @@
+//! Save format versions and the migrations between them.
+//!
+//! Every save carries a `format_version`. Loading runs the save through each
+//! migration from its version up to `CURRENT_FORMAT_VERSION`, working on raw
+//! JSON so old shapes never have to be expressible as Rust types.
+//!
+//! Versions:
+//! 1. Locations keyed by string ID, linked by ID exits.
+//! 2. Locations keyed by `"x,y"` coordinates.
+//! 3. Locations keyed by `"x,y,z"` with z-levels and typed exits.
+//! 4. Every world carries the `seed` for its terrain.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+
+use crate::biome::splitmix64;
+
+pub const CURRENT_FORMAT_VERSION: u32 = 4;
+
+struct Migration {
+    /// Version this migration upgrades from; it produces `from + 1`.
+    from: u32,
+    description: &'static str,
+    apply: fn(Value) -> Result<Value>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration { from: 1, description: "converted location IDs to coordinates", apply: v1_to_v2 },
+    Migration { from: 2, description: "added z-levels and typed exits", apply: v2_to_v3 },
+    Migration { from: 3, description: "fixed the terrain seed", apply: v3_to_v4 },
+];
+
+/// Work out which format a save is in. Saves written before the header
+/// existed are recognised by their shape.
+pub fn detect_version(save: &Value) -> Result<u32> {
+    if let Some(version) = save.get("format_version") {
+        return version.as_u64()
+            .map(|v| v as u32)
+            .context("format_version is not a number");
+    }
+    if save.get("current_location_id").is_some() {
+        return Ok(1);
+    }
+    match save.get("current_pos").and_then(|v| v.as_array()) {
+        Some(pos) if pos.len() == 2 => Ok(2),
+        Some(_) => Ok(3),
+        None => bail!("Save has neither a format_version nor a recognisable layout"),
+    }
+}
+
+/// Bring a save up to the current format, returning it along with a
+/// description of each migration applied (empty if it was already current).
+pub fn migrate(mut save: Value) -> Result<(Value, Vec<String>)> {
+    let version = detect_version(&save)?;
+    if version == 0 || version > CURRENT_FORMAT_VERSION {
+        bail!("Save format {} is not supported (this build reads up to {})", version, CURRENT_FORMAT_VERSION);
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        save = (migration.apply)(save)
+            .with_context(|| format!("Migration from format {} failed", migration.from))?;
+        applied.push(format!("v{} → v{}: {}", migration.from, migration.from + 1, migration.description));
+    }
+
+    if let Some(obj) = save.as_object_mut() {
+        obj.insert("format_version".to_string(), json!(CURRENT_FORMAT_VERSION));
+    }
+    Ok((save, applied))
+}
+
+fn str_list(value: Option<&Value>) -> Vec<Value> {
+    value.and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter(|v| v.is_string()).cloned().collect())
+        .unwrap_or_default()
+}
+
+fn str_or<'a>(obj: &'a Value, key: &str, default: &'a str) -> &'a str {
+    obj.get(key).and_then(|v| v.as_str()).unwrap_or(default)
+}
+
+/// Version 1 kept `x`/`y` on each location and linked everything by ID.
+fn v1_to_v2(old: Value) -> Result<Value> {
+    let empty = Map::new();
+    let old_locations = old.get("locations").and_then(|v| v.as_object()).unwrap_or(&empty);
+    let coords_of = |loc_id: &str| {
+        old_locations.get(loc_id).map(|loc| {
+            let coord = |key| loc.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+            (coord("x"), coord("y"))
+        })
+    };
+
+    let current_id = str_or(&old, "current_location_id", "start");
+    let (cx, cy) = coords_of(current_id).unwrap_or((0, 0));
+
+    let mut locations = Map::new();
+    for (loc_id, loc) in old_locations {
+        if !loc.is_object() {
+            continue;
+        }
+        let (x, y) = coords_of(loc_id).unwrap_or((0, 0));
+
+        // Null and dangling exits were never enforced, so they are left unexplored.
+        let exits: Map<String, Value> = loc.get("exits").and_then(|v| v.as_object())
+            .map(|exits| exits.iter()
+                .filter_map(|(dir, target)| {
+                    let (tx, ty) = coords_of(target.as_str()?)?;
+                    Some((dir.clone(), json!([tx, ty])))
+                })
+                .collect())
+            .unwrap_or_default();
+
+        locations.insert(format!("{},{}", x, y), json!({
+            "name": str_or(loc, "name", "Unknown"),
+            "description": str_or(loc, "description", ""),
+            "items": str_list(loc.get("items")),
+            "actors": str_list(loc.get("actors")),
+            "exits": exits,
+            "cached_image_path": loc.get("cached_image_path").filter(|v| v.is_string()),
+            "image_prompt": str_or(loc, "image_prompt", ""),
+            // Everything in an old save had been visited.
+            "visited": true,
+        }));
+    }
+
+    let mut actors = Map::new();
+    if let Some(old_actors) = old.get("actors").and_then(|v| v.as_object()) {
+        for (actor_id, actor) in old_actors.iter().filter(|(_, a)| a.is_object()) {
+            let (x, y) = actor.get("current_location_id").and_then(|v| v.as_str())
+                .and_then(&coords_of)
+                .unwrap_or((0, 0));
+            actors.insert(actor_id.clone(), json!({
+                "id": actor_id,
+                "name": str_or(actor, "name", ""),
+                "description": str_or(actor, "description", ""),
+                "current_pos": [x, y],
+                "inventory": str_list(actor.get("inventory")),
+                "money": actor.get("money").and_then(|v| v.as_u64()).unwrap_or(0),
+            }));
+        }
+    }
+
+    // Version 1 items had no type or properties; they become plain materials.
+    let mut items = Map::new();
+    if let Some(old_items) = old.get("items").and_then(|v| v.as_object()) {
+        for (item_id, item) in old_items.iter().filter(|(_, i)| i.is_object()) {
+            items.insert(item_id.clone(), json!({
+                "id": item_id,
+                "name": str_or(item, "name", ""),
+                "description": str_or(item, "description", ""),
+                "item_type": "Material",
+                "state": "Normal",
+                "properties": {
+                    "damage": null, "defense": null, "value": null, "weight": null,
+                    "carryable": true, "usable": false, "equip_slot": null, "status_effects": [],
+                },
+            }));
+        }
+    }
+
+    let player = old.get("player");
+    Ok(json!({
+        "current_pos": [cx, cy],
+        "locations": locations,
+        "actors": actors,
+        "items": items,
+        "player": {
+            "inventory": str_list(player.and_then(|p| p.get("inventory"))),
+            "money": player.and_then(|p| p.get("money")).and_then(|v| v.as_u64()).unwrap_or(0),
+        },
+        "combat": { "active": false, "combatants": [], "current_turn_index": 0, "round_number": 0 },
+        "max_items": 20,
+        "max_combatants": 4,
+    }))
+}
+
+/// Extend an `[x, y]` position onto level 0; anything else is left alone.
+fn add_level(pos: &mut Value) {
+    if let Some(arr) = pos.as_array_mut() {
+        if arr.len() == 2 {
+            arr.push(json!(0));
+        }
+    }
+}
+
+/// Version 2 positions were `[x, y]` and exits were a bare `[x, y]` or `null`.
+/// The game wrote `null` for every direction it hadn't linked, so a `null`
+/// is dropped and the direction is left unexplored rather than walled off.
+fn v2_to_v3(mut save: Value) -> Result<Value> {
+    let obj = save.as_object_mut().context("Save is not a JSON object")?;
+
+    if let Some(pos) = obj.get_mut("current_pos") {
+        add_level(pos);
+    }
+
+    if let Some(Value::Object(old_locations)) = obj.remove("locations") {
+        let mut locations = Map::new();
+        for (key, mut loc) in old_locations {
+            let key = if key.split(',').count() == 2 { format!("{},0", key) } else { key };
+            if let Some(exits) = loc.get_mut("exits").and_then(|v| v.as_object_mut()) {
+                exits.retain(|_, exit| !exit.is_null());
+                for exit in exits.values_mut() {
+                    if exit.is_array() {
+                        *exit = json!({ "kind": "Open", "to": exit });
+                    }
+                    if let Some(to) = exit.get_mut("to") {
+                        add_level(to);
+                    }
+                }
+            }
+            locations.insert(key, loc);
+        }
+        obj.insert("locations".to_string(), Value::Object(locations));
+    }
+
+    if let Some(actors) = obj.get_mut("actors").and_then(|v| v.as_object_mut()) {
+        for actor in actors.values_mut() {
+            if let Some(pos) = actor.get_mut("current_pos") {
+                add_level(pos);
+            }
+        }
+    }
+
+    Ok(save)
+}
+
+/// Saves from before seeded terrain have no `seed`. Derive one from the
+/// places already in the save, so the terrain around them is the same every
+/// time the save is opened until it is written back with the seed.
+fn v3_to_v4(mut save: Value) -> Result<Value> {
+    let obj = save.as_object_mut().context("Save is not a JSON object")?;
+    if obj.get("seed").is_some_and(|v| v.is_u64()) {
+        return Ok(save);
+    }
+
+    let mut places: Vec<(&String, &str)> = obj.get("locations").and_then(|v| v.as_object())
+        .map(|locations| locations.iter().map(|(key, loc)| (key, str_or(loc, "name", ""))).collect())
+        .unwrap_or_default();
+    places.sort();
+    let seed = places.iter()
+        .flat_map(|(key, name)| key.bytes().chain(name.bytes()))
+        .fold(0, |hash, byte| splitmix64(hash ^ byte as u64));
+    obj.insert("seed".to_string(), json!(seed));
+    Ok(save)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Exit, WorldState};
+
+    fn fixture(version: u32) -> Value {
+        let text = match version {
+            1 => include_str!("../tests/fixtures/saves/v1.json"),
+            2 => include_str!("../tests/fixtures/saves/v2.json"),
+            3 => include_str!("../tests/fixtures/saves/v3.json"),
+            4 => include_str!("../tests/fixtures/saves/v4.json"),
+            _ => unreachable!(),
+        };
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_chain_covers_every_version() {
+        let froms: Vec<u32> = MIGRATIONS.iter().map(|m| m.from).collect();
+        assert_eq!(froms, (1..CURRENT_FORMAT_VERSION).collect::<Vec<_>>());
+        for version in 1..=CURRENT_FORMAT_VERSION {
+            assert_eq!(detect_version(&fixture(version)).unwrap(), version);
+        }
+    }
+
+    #[test]
+    fn test_v1_to_v2() {
+        let save = v1_to_v2(fixture(1)).unwrap();
+        assert_eq!(save["current_pos"], json!([1, 0]));
+        assert_eq!(save["locations"]["0,0"]["name"], "Village Square");
+        assert_eq!(save["locations"]["0,0"]["exits"], json!({ "east": [1, 0] }));
+        assert_eq!(save["locations"]["1,0"]["visited"], true);
+        assert_eq!(save["actors"]["smith"]["current_pos"], json!([1, 0]));
+        assert_eq!(save["items"]["hammer"]["item_type"], "Material");
+        assert_eq!(save["player"]["money"], 12);
+        assert_eq!(detect_version(&save).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_v2_to_v3() {
+        let save = v2_to_v3(fixture(2)).unwrap();
+        assert_eq!(save["current_pos"], json!([0, 1, 0]));
+        let square = &save["locations"]["0,0,0"];
+        assert_eq!(square["exits"]["north"], json!({ "kind": "Open", "to": [0, 1, 0] }));
+        assert!(square["exits"].get("west").is_none());
+        assert!(save["locations"].get("0,1").is_none());
+        assert_eq!(save["actors"]["guard"]["current_pos"], json!([0, 1, 0]));
+        assert_eq!(detect_version(&save).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_baseline_save_with_null_exits_can_be_walked() {
+        let save = serde_json::from_str(include_str!("../tests/fixtures/saves/baseline.json")).unwrap();
+        let (save, _) = migrate(save).unwrap();
+        let mut world: WorldState = serde_json::from_value(save).unwrap();
+        assert!(world.locations[&(0, 0, 0)].exits.is_empty());
+        for direction in ["north", "south", "west"] {
+            assert!(world.resolve_move(direction).is_ok(), "{}", direction);
+        }
+
+        assert_eq!(world.resolve_move("east"), Ok((1, 0, 0)));
+        world.current_pos = (1, 0, 0);
+        world.link_exits((0, 0, 0), (1, 0, 0), "east");
+        assert_eq!(world.resolve_move("west"), Ok((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_v3_to_v4_seed_is_stable() {
+        let first = v3_to_v4(fixture(3)).unwrap();
+        let second = v3_to_v4(fixture(3)).unwrap();
+        assert!(first["seed"].is_u64());
+        assert_eq!(first["seed"], second["seed"]);
+        // A seed the save already has is kept.
+        assert_eq!(v3_to_v4(fixture(4)).unwrap()["seed"], fixture(4)["seed"]);
+    }
+
+    #[test]
+    fn test_every_fixture_loads() {
+        for version in 1..=CURRENT_FORMAT_VERSION {
+            let (save, applied) = migrate(fixture(version)).unwrap();
+            assert_eq!(applied.len() as u32, CURRENT_FORMAT_VERSION - version);
+            assert_eq!(save["format_version"], json!(CURRENT_FORMAT_VERSION));
+            let world: WorldState = serde_json::from_value(save).unwrap();
+            assert!(!world.locations.is_empty(), "v{}", version);
+        }
+
+        let (save, _) = migrate(fixture(1)).unwrap();
+        let world: WorldState = serde_json::from_value(save).unwrap();
+        assert_eq!(world.current_pos, (1, 0, 0));
+        assert_eq!(world.locations[&(0, 0, 0)].exits["east"], Exit::Open { to: (1, 0, 0) });
+    }
+
+    #[test]
+    fn test_newer_format_is_rejected() {
+        let mut save = fixture(CURRENT_FORMAT_VERSION);
+        save["format_version"] = json!(CURRENT_FORMAT_VERSION + 1);
+        assert!(migrate(save).is_err());
+    }
+}
@@ -1,23 +1,101 @@
 use serde::{Deserialize, Serialize, Deserializer, Serializer};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Local};
+
+use crate::biome::{self, Terrain};
+use crate::clock::{ScheduledEvent, WorldClock, MINUTES_PER_STEP};
+use crate::weather::{self, Weather};
+use crate::faction::{self, Disposition, Faction};
+use crate::magic::{self, Spell};
+use crate::death::DeathPolicy;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WorldState {
-    pub current_pos: (i32, i32),  // Replaces current_location_id: String
+    pub current_pos: (i32, i32, i32),  // (x, y, level); replaces current_location_id: String
     #[serde(serialize_with = "serialize_coords", deserialize_with = "deserialize_coords")]
-    pub locations: HashMap<(i32, i32), Location>,  // Coord -> Location (primary key)
+    pub locations: HashMap<(i32, i32, i32), Location>,  // Coord -> Location (primary key)
     pub actors: HashMap<String, Actor>, // Changed to HashMap for easier lookup
     pub items: HashMap<String, Item>,   // Global registry of all items
     pub player: Player,
     pub combat: CombatState,
     pub max_items: u32,
     pub max_combatants: u32,
+    /// Seed for the terrain layer; see `crate::biome`. Saves from before it
+    /// existed are given a fixed one by `crate::migration`.
+    #[serde(default)]
+    pub seed: u64,
+    /// Named areas spanning many tiles, keyed by region ID.
+    #[serde(default)]
+    pub regions: HashMap<String, Region>,
+    #[serde(default)]
+    pub clock: WorldClock,
+    /// Pending scheduled events, soonest first.
+    #[serde(default)]
+    pub events: Vec<ScheduledEvent>,
+    /// Conditions where the player is, refreshed as time passes.
+    #[serde(default)]
+    pub weather: Weather,
+    #[serde(default)]
+    pub factions: HashMap<String, Faction>,
+    /// Player standing with each faction, keyed by faction ID. Missing means 0.
+    #[serde(default)]
+    pub reputation: HashMap<String, i32>,
+    /// Spells invented for this world, on top of `magic::builtin_spells`.
+    #[serde(default)]
+    pub spells: HashMap<String, Spell>,
+    #[serde(default)]
+    pub death_policy: DeathPolicy,
+    #[serde(default)]
+    pub save_format: SaveFormat,
+    /// Where the player last rested; they come round here after a defeat.
+    #[serde(default)]
+    pub rest_point: (i32, i32, i32),
+    /// Set when the player drops to 0 HP, until the defeat has been handled.
+    #[serde(default)]
+    pub player_defeated: bool,
+    #[serde(default)]
+    pub deaths: u32,
+    /// When the world was created; unknown for worlds older than this field.
+    #[serde(default)]
+    pub created: Option<DateTime<Local>>,
+    /// Actions the player has taken in this world.
+    #[serde(default)]
+    pub turns: u32,
+    /// Seconds spent playing, not counting long idle stretches.
+    #[serde(default)]
+    pub playtime_secs: u64,
+    #[serde(default)]
+    pub quests: HashMap<String, Quest>,
+    /// Actors beaten in combat, for quest objectives and later consequences.
+    #[serde(default)]
+    pub defeated_actors: Vec<String>,
+    /// The last `MAX_JOURNAL_ENTRIES` finished turns, oldest first; see `crate::chronicle`.
+    #[serde(default)]
+    pub journal: Vec<JournalEntry>,
+    /// Turns pushed out of `journal` since the last save, waiting to be
+    /// archived beside it by `SaveManager::archive_journal`.
+    #[serde(skip)]
+    pub faded_journal: Vec<JournalEntry>,
+}
+
+/// Steps' worth of time a weather hazard lingers after the player gets out of it.
+const HAZARD_DURATION: u32 = 3;
+const HAZARD_SEVERITY: u32 = 2;
+
+/// Turns kept in the journal, which is what the save carries for scrollback
+/// and the DM's prompt. Older ones move to `faded_journal` as new ones come in.
+pub const MAX_JOURNAL_ENTRIES: usize = 500;
+
+fn new_world_seed() -> u64 {
+    rand::random()
 }
 
 impl Default for WorldState {
     fn default() -> Self {
         Self {
-            current_pos: (0, 0),
+            current_pos: (0, 0, 0),
             locations: HashMap::new(),
             actors: HashMap::new(),
             items: HashMap::new(),
@@ -25,26 +103,51 @@ impl Default for WorldState {
             combat: CombatState::default(),
             max_items: 20,
             max_combatants: 4,
+            seed: new_world_seed(),
+            regions: HashMap::new(),
+            clock: WorldClock::default(),
+            events: Vec::new(),
+            weather: Weather::default(),
+            factions: HashMap::new(),
+            reputation: HashMap::new(),
+            spells: HashMap::new(),
+            death_policy: DeathPolicy::default(),
+            save_format: SaveFormat::default(),
+            rest_point: (0, 0, 0),
+            player_defeated: false,
+            deaths: 0,
+            created: None,
+            turns: 0,
+            playtime_secs: 0,
+            quests: HashMap::new(),
+            defeated_actors: Vec::new(),
+            journal: Vec::new(),
+            faded_journal: Vec::new(),
         }
     }
 }
 
+type CoordMap<T> = HashMap<(i32, i32, i32), T>;
+
+/// Steps of a journey: the direction taken and the tile it leads to.
+pub type Route = Vec<(String, (i32, i32, i32))>;
+
 // Helper functions for serializing coordinate HashMaps
-fn serialize_coords<S, T>(map: &HashMap<(i32, i32), T>, serializer: S) -> Result<S::Ok, S::Error>
+fn serialize_coords<S, T>(map: &CoordMap<T>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
     T: Serialize,
 {
     use serde::ser::SerializeMap;
     let mut seq = serializer.serialize_map(Some(map.len()))?;
-    for ((x, y), value) in map {
-        let key = format!("{},{}", x, y);
+    for ((x, y, z), value) in map {
+        let key = format!("{},{},{}", x, y, z);
         seq.serialize_entry(&key, value)?;
     }
     seq.end()
 }
 
-fn deserialize_coords<'de, D, T>(deserializer: D) -> Result<HashMap<(i32, i32), T>, D::Error>
+fn deserialize_coords<'de, D, T>(deserializer: D) -> Result<CoordMap<T>, D::Error>
 where
     D: Deserializer<'de>,
     T: Deserialize<'de>,
@@ -55,20 +158,141 @@ where
     let mut coord_map = HashMap::new();
     
     for (key_str, value) in string_map {
-        if let Some((x_str, y_str)) = key_str.split_once(',') {
-            if let (Ok(x), Ok(y)) = (x_str.parse::<i32>(), y_str.parse::<i32>()) {
-                coord_map.insert((x, y), value);
-            }
+        let parts: Option<Vec<i32>> = key_str.split(',').map(|p| p.trim().parse().ok()).collect();
+        if let Some([x, y, z]) = parts.as_deref() {
+            coord_map.insert((*x, *y, *z), value);
         }
     }
     
     Ok(coord_map)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// How a world is encoded on disk. Chosen when the world is created and kept
+/// with it; loading tells the formats apart by their first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveFormat {
+    /// Pretty-printed JSON, easy to read and diff.
+    #[default]
+    Json,
+    /// Compact JSON compressed with gzip, for large worlds. Stored as
+    /// `.json.gz`, or as plain JSON in the browser.
+    Gzip,
+}
+
+impl fmt::Display for SaveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveFormat::Json => write!(f, "json"),
+            SaveFormat::Gzip => write!(f, "gzip"),
+        }
+    }
+}
+
+impl FromStr for SaveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(SaveFormat::Json),
+            "gzip" | "gz" => Ok(SaveFormat::Gzip),
+            other => Err(format!("Unknown save format '{}' (expected json or gzip)", other)),
+        }
+    }
+}
+
+/// One turn as the player saw it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub turn: u32,
+    /// In-game time when the turn ended, as `WorldClock::describe` puts it.
+    pub time: String,
+    pub pos: (i32, i32, i32),
+    pub location: String,
+    /// What the player typed or picked.
+    pub input: String,
+    pub narrative: String,
+    /// Things that happened besides the narrative: discoveries, fired
+    /// events, quest progress, defeats.
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Quest {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// Actor ID of whoever handed out the quest.
+    #[serde(default)]
+    pub giver: Option<String>,
+    pub objectives: Vec<QuestObjective>,
+    #[serde(default)]
+    pub reward_money: u32,
+    /// Item IDs handed to the player on completion.
+    #[serde(default)]
+    pub reward_items: Vec<String>,
+    pub status: QuestStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum QuestStatus {
+    Active,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuestObjective {
+    pub description: String,
+    pub goal: ObjectiveGoal,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// What has to be true for an objective to count as done. `Custom` goals
+/// are only ticked off by the DM through `update_quest`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ObjectiveGoal {
+    FetchItem { item_id: String },
+    ReachLocation { pos: (i32, i32, i32) },
+    DefeatActor { actor_id: String },
+    Custom,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Player {
     pub inventory: Vec<String>, // List of Item IDs
     pub money: u32,
+    /// Lasting effects from outside combat, e.g. frostbite from a blizzard.
+    /// Durations count down one per step's worth of time.
+    #[serde(default)]
+    pub status_effects: Vec<StatusEffect>,
+    #[serde(default = "starting_mana")]
+    pub mana: u32,
+    #[serde(default = "starting_mana")]
+    pub max_mana: u32,
+    /// IDs of spells the player can cast; see `crate::magic`.
+    #[serde(default)]
+    pub known_spells: Vec<String>,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            inventory: Vec::new(),
+            money: 0,
+            status_effects: Vec::new(),
+            mana: magic::STARTING_MANA,
+            max_mana: magic::STARTING_MANA,
+            known_spells: Vec::new(),
+        }
+    }
+}
+
+fn starting_mana() -> u32 {
+    magic::STARTING_MANA
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -82,7 +306,7 @@ pub struct Location {
     #[serde(default)]
     pub actors: Vec<String>,
     #[serde(default)]
-    pub exits: HashMap<String, Option<(i32, i32)>>,
+    pub exits: HashMap<String, Exit>,
     #[serde(default)]
     pub cached_image_path: Option<String>,
     #[serde(default = "default_image_prompt")]
@@ -91,6 +315,222 @@ pub struct Location {
     pub visited: bool,
 }
 
+/// A named area such as a forest, city or mountain range, made up of many tiles.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Region {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Background the DM can draw on: history, legends, who lives here.
+    #[serde(default)]
+    pub lore: String,
+    #[serde(default)]
+    pub tiles: Vec<(i32, i32, i32)>,
+}
+
+/// A location as returned by the generator, with any new items and actors
+/// it introduced still waiting to be registered.
+#[derive(Debug, Clone)]
+pub struct GeneratedLocation {
+    pub location: Location,
+    pub items: Vec<ItemSpec>,
+    pub actors: Vec<ActorSpec>,
+    pub region: Option<RegionSpec>,
+    /// Definitions that did not match the schema, already described for the log.
+    pub rejected: Vec<String>,
+}
+
+impl From<Location> for GeneratedLocation {
+    fn from(location: Location) -> Self {
+        Self {
+            location,
+            items: Vec::new(),
+            actors: Vec::new(),
+            region: None,
+            rejected: Vec::new(),
+        }
+    }
+}
+
+/// Which region the generator put the new room in: the name of a nearby
+/// region, or a new one it is founding.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RegionSpec {
+    Existing(String),
+    New {
+        name: String,
+        #[serde(default)]
+        description: String,
+        #[serde(default)]
+        lore: String,
+    },
+}
+
+/// An item the generator wants placed in the new room.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemSpec {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub item_type: ItemType,
+    #[serde(default)]
+    pub properties: ItemProperties,
+}
+
+impl ItemSpec {
+    /// Parse an item definition, accepting loose spellings of `item_type`
+    /// such as "weapon" or "quest_item".
+    pub fn from_value(mut value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let canonical = value.get("item_type").and_then(|t| t.as_str()).and_then(|t| {
+            let wanted: String = t.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+            ["Weapon", "Armor", "Consumable", "Tool", "Key", "Container", "QuestItem", "Material"]
+                .into_iter()
+                .find(|variant| variant.to_lowercase() == wanted)
+        });
+        if let Some(canonical) = canonical {
+            value["item_type"] = serde_json::json!(canonical);
+        }
+        serde_json::from_value(value)
+    }
+}
+
+/// A character or creature the generator wants living in the new room.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActorSpec {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub money: u32,
+    /// ID of an existing faction; unknown factions are dropped.
+    #[serde(default)]
+    pub faction: Option<String>,
+}
+
+/// A passage out of a location in a given direction.
+///
+/// A direction with no entry in `Location::exits` is unexplored and can be
+/// walked freely; once an entry exists, movement has to respect it. `Blocked`
+/// is only placed by the DM, the generators and one-way links; old saves'
+/// empty exits arrive unexplored (see `crate::migration`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum Exit {
+    Open { to: (i32, i32, i32) },
+    Blocked,
+    Door {
+        to: (i32, i32, i32),
+        locked: bool,
+        #[serde(default)]
+        key_id: Option<String>,
+    },
+    Hidden { to: (i32, i32, i32) },
+    OneWay { to: (i32, i32, i32) },
+}
+
+impl Exit {
+    /// Where this exit leads, if it leads anywhere.
+    pub fn target(&self) -> Option<(i32, i32, i32)> {
+        match self {
+            Exit::Open { to } | Exit::Door { to, .. } | Exit::Hidden { to } | Exit::OneWay { to } => Some(*to),
+            Exit::Blocked => None,
+        }
+    }
+
+    /// Whether the player can currently walk through this exit.
+    pub fn is_traversable(&self) -> bool {
+        matches!(self, Exit::Open { .. } | Exit::OneWay { .. } | Exit::Door { locked: false, .. })
+    }
+}
+
+/// Levels between the outdoor grid and the interiors entered from it.
+///
+/// Level 0 is the surface; higher levels are upper floors and lower ones are
+/// underground. An interior sits `INTERIOR_LEVELS` above the tile it is
+/// entered from, so it never takes the cell of the floor above, and its own
+/// stairs line up with the floors of the building outside.
+pub const INTERIOR_LEVELS: i32 = 1000;
+
+/// Grid offset for a direction.
+pub fn direction_offset(direction: &str) -> Option<(i32, i32, i32)> {
+    match direction {
+        "north" => Some((0, 1, 0)),
+        "south" => Some((0, -1, 0)),
+        "east" => Some((1, 0, 0)),
+        "west" => Some((-1, 0, 0)),
+        "northeast" => Some((1, 1, 0)),
+        "northwest" => Some((-1, 1, 0)),
+        "southeast" => Some((1, -1, 0)),
+        "southwest" => Some((-1, -1, 0)),
+        "up" => Some((0, 0, 1)),
+        "down" => Some((0, 0, -1)),
+        "enter" => Some((0, 0, INTERIOR_LEVELS)),
+        "exit" => Some((0, 0, -INTERIOR_LEVELS)),
+        _ => None,
+    }
+}
+
+/// The eight directions that move across a level, clockwise from north.
+pub const COMPASS_DIRECTIONS: [&str; 8] = [
+    "north", "northeast", "east", "southeast", "south", "southwest", "west", "northwest",
+];
+
+/// Directions that change level. These need a stair, ladder or doorway:
+/// the player cannot walk them without an exit leading that way.
+pub fn is_vertical_direction(direction: &str) -> bool {
+    matches!(direction, "up" | "down" | "enter" | "exit")
+}
+
+/// How a direction reads in narration ("you move north", "you move inside").
+pub fn direction_phrase(direction: &str) -> &str {
+    match direction {
+        "enter" => "inside",
+        "exit" => "outside",
+        other => other,
+    }
+}
+
+/// Human-readable name for a level, for prompts and the map title.
+pub fn describe_level(z: i32) -> String {
+    let depth = (z + INTERIOR_LEVELS / 2).div_euclid(INTERIOR_LEVELS);
+    let floor = z - depth * INTERIOR_LEVELS;
+    match (depth > 0, floor) {
+        (false, 0) => "the surface".to_string(),
+        (true, 0) => "the ground floor indoors".to_string(),
+        (indoors, floor) => {
+            let level = if floor > 0 {
+                format!("level {} above the surface", floor)
+            } else {
+                format!("level {} below ground", -floor)
+            };
+            if indoors { format!("{} indoors", level) } else { level }
+        }
+    }
+}
+
+pub fn get_opposite_direction(direction: &str) -> String {
+    match direction {
+        "north" => "south".to_string(),
+        "south" => "north".to_string(),
+        "east" => "west".to_string(),
+        "west" => "east".to_string(),
+        "northeast" => "southwest".to_string(),
+        "southwest" => "northeast".to_string(),
+        "northwest" => "southeast".to_string(),
+        "southeast" => "northwest".to_string(),
+        "up" => "down".to_string(),
+        "down" => "up".to_string(),
+        "enter" => "exit".to_string(),
+        "exit" => "enter".to_string(),
+        _ => direction.to_string(),
+    }
+}
+
 fn default_location_name() -> String {
     "Unknown Location".to_string()
 }
@@ -108,9 +548,12 @@ pub struct Actor {
     pub id: String,
     pub name: String,
     pub description: String,
-    pub current_pos: (i32, i32),  // Replaces current_location_id: String
+    pub current_pos: (i32, i32, i32),  // Replaces current_location_id: String
     pub inventory: Vec<String>, // List of Item IDs
     pub money: u32,
+    /// ID of the faction this actor belongs to, if any.
+    #[serde(default)]
+    pub faction: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -151,6 +594,7 @@ pub enum ItemState {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct ItemProperties {
     pub damage: Option<u32>,
     pub defense: Option<u32>,
@@ -228,14 +672,14 @@ pub struct Item {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", content = "payload")]
 pub enum GameAction {
-    CreateLocation((i32, i32), Location),
-    UpdateLocation((i32, i32), Location),
+    CreateLocation((i32, i32, i32), Location),
+    UpdateLocation((i32, i32, i32), Location),
     CreateItem(Item),
     AddItemToInventory(String),
     RemoveItemFromInventory(String),
-    MoveTo((i32, i32)),
-    AddItemToLocation { pos: (i32, i32), item_id: String },
-    RemoveItemFromLocation { pos: (i32, i32), item_id: String },
+    MoveTo((i32, i32, i32)),
+    AddItemToLocation { pos: (i32, i32, i32), item_id: String },
+    RemoveItemFromLocation { pos: (i32, i32, i32), item_id: String },
 
     // Item Actions
     UseItem(String),
@@ -265,9 +709,419 @@ pub struct WorldUpdate {
 }
 
 impl WorldState {
+    /// Write the turn just finished into the journal, at the player's
+    /// position, moving the oldest turns past `MAX_JOURNAL_ENTRIES` out to
+    /// `faded_journal`.
+    pub fn record_turn(&mut self, input: &str, narrative: &str, events: Vec<String>) {
+        let location = self.locations.get(&self.current_pos)
+            .map(|l| l.name.clone())
+            .unwrap_or_default();
+        self.journal.push(JournalEntry {
+            turn: self.turns,
+            time: self.clock.describe(),
+            pos: self.current_pos,
+            location,
+            input: input.to_string(),
+            narrative: narrative.trim().to_string(),
+            events,
+        });
+        let excess = self.journal.len().saturating_sub(MAX_JOURNAL_ENTRIES);
+        let faded: Vec<JournalEntry> = self.journal.drain(..excess).collect();
+        self.faded_journal.extend(faded);
+    }
+
+    /// The last `count` turns of the journal, oldest first.
+    pub fn recent_turns(&self, count: usize) -> &[JournalEntry] {
+        &self.journal[self.journal.len().saturating_sub(count)..]
+    }
+
+    /// Shortest route from the current position to `to` through visited
+    /// tiles, following only exits the player can use right now (locked
+    /// doors count if they carry the key). Each step is the direction to
+    /// take and the tile it leads to; an empty route means already there.
+    pub fn find_path(&self, to: (i32, i32, i32)) -> Option<Route> {
+        let from = self.current_pos;
+        if from == to {
+            return Some(Vec::new());
+        }
+
+        let mut came_from: CoordMap<((i32, i32, i32), &str)> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([from]);
+        while let Some(pos) = queue.pop_front() {
+            let Some(loc) = self.locations.get(&pos) else { continue };
+
+            // Walk exits in a fixed order so equal-length routes are chosen consistently.
+            let mut exits: Vec<(&String, &Exit)> = loc.exits.iter().collect();
+            exits.sort_by(|a, b| a.0.cmp(b.0));
+            for (direction, exit) in exits {
+                let next = match exit {
+                    Exit::Open { to } | Exit::OneWay { to } | Exit::Door { to, locked: false, .. } => *to,
+                    Exit::Door { to, locked: true, key_id: Some(key) } if self.player.inventory.contains(key) => *to,
+                    _ => continue,
+                };
+                let visited = self.locations.get(&next).is_some_and(|l| l.visited);
+                if !visited || next == from || came_from.contains_key(&next) {
+                    continue;
+                }
+                came_from.insert(next, (pos, direction.as_str()));
+                if next == to {
+                    let mut path = Vec::new();
+                    let mut step = to;
+                    while step != from {
+                        let (prev, direction) = came_from[&step];
+                        path.push((direction.to_string(), step));
+                        step = prev;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(next);
+            }
+        }
+        None
+    }
+
+    /// Note any enemy combatant knocked down to 0 HP as defeated, and flag
+    /// the player's own defeat. Defeating a faction member costs reputation
+    /// with that faction; returns a message per change.
+    pub fn record_defeats(&mut self) -> Vec<String> {
+        if self.combat.combatants.iter().any(|c| c.is_player && c.hp == 0) {
+            self.player_defeated = true;
+        }
+
+        let fallen: Vec<String> = self.combat.combatants.iter()
+            .filter(|c| !c.is_player && c.hp == 0 && !self.defeated_actors.contains(&c.id))
+            .map(|c| c.id.clone())
+            .collect();
+
+        let mut messages = Vec::new();
+        for actor_id in fallen {
+            let faction_id = self.actors.get(&actor_id).and_then(|a| a.faction.clone());
+            self.defeated_actors.push(actor_id);
+            if let Some(faction_id) = faction_id {
+                messages.extend(self.adjust_reputation(&faction_id, -faction::KILL_PENALTY));
+            }
+        }
+        messages
+    }
+
+    pub fn reputation_with(&self, faction_id: &str) -> i32 {
+        self.reputation.get(faction_id).copied().unwrap_or(0)
+    }
+
+    /// Change the player's standing with a faction, clamped to the allowed
+    /// range. Returns `None` if there is no such faction.
+    pub fn adjust_reputation(&mut self, faction_id: &str, delta: i32) -> Option<String> {
+        let name = self.factions.get(faction_id)?.name.clone();
+        let score = (self.reputation_with(faction_id) + delta)
+            .clamp(faction::MIN_REPUTATION, faction::MAX_REPUTATION);
+        self.reputation.insert(faction_id.to_string(), score);
+        Some(format!("Reputation with {}: {:+} (now {}, {})", name, delta, score, Disposition::from_reputation(score)))
+    }
+
+    /// How an actor feels about the player, from their faction's reputation.
+    /// Actors without a faction are neutral.
+    pub fn disposition_of(&self, actor_id: &str) -> Disposition {
+        Disposition::from_reputation(self.actor_reputation(actor_id))
+    }
+
+    fn actor_reputation(&self, actor_id: &str) -> i32 {
+        self.actors.get(actor_id)
+            .and_then(|a| a.faction.as_deref())
+            .map(|f| self.reputation_with(f))
+            .unwrap_or(0)
+    }
+
+    /// Buy an item from an actor, or sell one to them, at a price set by the
+    /// item's value and the actor's faction standing. Money and the item
+    /// change hands; the error explains why the deal fell through.
+    pub fn trade(&mut self, actor_id: &str, item_id: &str, buying: bool) -> Result<String, String> {
+        let actor = self.actors.get(actor_id).ok_or(format!("Actor {} not found", actor_id))?;
+        let item = self.items.get(item_id).ok_or(format!("Item {} not found", item_id))?;
+        if actor.current_pos != self.current_pos {
+            return Err(format!("{} is not here", actor.name));
+        }
+        let disposition = self.disposition_of(actor_id);
+        if !disposition.will_trade() {
+            return Err(format!("{} refuses to deal with you", actor.name));
+        }
+
+        let value = item.properties.value.unwrap_or(1);
+        let reputation = self.actor_reputation(actor_id);
+        let (actor_name, item_name) = (actor.name.clone(), item.name.clone());
+
+        if buying {
+            let price = faction::buy_price(value, reputation);
+            if !actor.inventory.iter().any(|id| id == item_id) {
+                return Err(format!("{} does not have {}", actor_name, item_name));
+            }
+            if self.player.money < price {
+                return Err(format!("{} costs {} but you only have {}", item_name, price, self.player.money));
+            }
+            self.player.money -= price;
+            let actor = self.actors.get_mut(actor_id).unwrap();
+            actor.money += price;
+            actor.inventory.retain(|id| id != item_id);
+            self.player.inventory.push(item_id.to_string());
+            Ok(format!("Bought {} from {} for {} ({})", item_name, actor_name, price, disposition))
+        } else {
+            let price = faction::sell_price(value, reputation);
+            if !self.player.inventory.iter().any(|id| id == item_id) {
+                return Err(format!("You do not have {}", item_name));
+            }
+            if actor.money < price {
+                return Err(format!("{} cannot afford {} ({} needed)", actor_name, item_name, price));
+            }
+            let actor = self.actors.get_mut(actor_id).unwrap();
+            actor.money -= price;
+            actor.inventory.push(item_id.to_string());
+            self.player.inventory.retain(|id| id != item_id);
+            self.player.money += price;
+            Ok(format!("Sold {} to {} for {} ({})", item_name, actor_name, price, disposition))
+        }
+    }
+
+    /// Move the clock forward and fire any scheduled events that came due.
+    /// Returns a message per event; a repeating event that came due several
+    /// times while time passed fires once and is rescheduled after now.
+    pub fn advance_time(&mut self, minutes: u64) -> Vec<String> {
+        self.clock.advance(minutes);
+        let now = self.clock.minutes;
+
+        let mut messages = Vec::new();
+        let mut pending = Vec::new();
+        for mut event in std::mem::take(&mut self.events) {
+            if event.at > now {
+                pending.push(event);
+                continue;
+            }
+            let place = event.location
+                .and_then(|pos| self.locations.get(&pos))
+                .map(|l| format!(" ({})", l.name))
+                .unwrap_or_default();
+            messages.push(format!("Event{}: {}", place, event.description));
+
+            if let Some(every) = event.repeat_every.filter(|every| *every > 0) {
+                event.at += (now - event.at) / every * every + every;
+                pending.push(event);
+            }
+        }
+        pending.sort_by_key(|e| e.at);
+        self.events = pending;
+
+        let recovered = (minutes / magic::MINUTES_PER_MANA).min(u32::MAX as u64) as u32;
+        self.player.mana = self.player.mana.saturating_add(recovered).min(self.player.max_mana);
+
+        messages.extend(self.refresh_weather());
+        if !self.combat.active {
+            messages.extend(self.apply_weather_hazards(minutes));
+        }
+        messages
+    }
+
+    /// Recompute the weather where the player stands. Returns a message if it changed.
+    pub fn refresh_weather(&mut self) -> Option<String> {
+        let weather = weather::weather_at(self.seed, self.current_pos, &self.clock);
+        let changed = weather.condition != self.weather.condition;
+        self.weather = weather;
+        changed.then(|| format!("Weather: {}", weather.summary()))
+    }
+
+    /// Wear down the player's lasting effects over `minutes`, then expose them
+    /// to the current weather. Returns a message for each new effect.
+    pub fn apply_weather_hazards(&mut self, minutes: u64) -> Vec<String> {
+        let ticks = (minutes / MINUTES_PER_STEP).max(1) as u32;
+        self.player.status_effects.retain_mut(|effect| {
+            effect.duration = effect.duration.saturating_sub(ticks);
+            effect.duration > 0
+        });
+
+        if weather::is_sheltered(self.current_pos) {
+            return Vec::new();
+        }
+        let Some((effect_type, reason)) = self.weather.hazard() else {
+            return Vec::new();
+        };
+        match self.player.status_effects.iter_mut().find(|e| e.effect_type == effect_type) {
+            Some(effect) => {
+                effect.duration = effect.duration.max(HAZARD_DURATION);
+                Vec::new()
+            }
+            None => {
+                self.player.status_effects.push(StatusEffect {
+                    effect_type,
+                    duration: HAZARD_DURATION,
+                    severity: HAZARD_SEVERITY,
+                });
+                vec![reason.to_string()]
+            }
+        }
+    }
+
+    /// Add an event, keeping the list ordered by firing time.
+    pub fn schedule_event(&mut self, event: ScheduledEvent) {
+        self.events.retain(|e| e.id != event.id);
+        let index = self.events.partition_point(|e| e.at <= event.at);
+        self.events.insert(index, event);
+    }
+
+    /// Tick off quest objectives the world state now satisfies and complete
+    /// quests whose objectives are all done. Returns a message per change.
+    pub fn check_quests(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        let mut finished = Vec::new();
+
+        for quest in self.quests.values_mut().filter(|q| q.status == QuestStatus::Active) {
+            for objective in quest.objectives.iter_mut().filter(|o| !o.done) {
+                objective.done = match &objective.goal {
+                    ObjectiveGoal::FetchItem { item_id } => self.player.inventory.contains(item_id),
+                    ObjectiveGoal::ReachLocation { pos } => self.current_pos == *pos,
+                    ObjectiveGoal::DefeatActor { actor_id } => self.defeated_actors.contains(actor_id),
+                    ObjectiveGoal::Custom => false,
+                };
+                if objective.done {
+                    messages.push(format!("Objective complete ({}): {}", quest.title, objective.description));
+                }
+            }
+            if !quest.objectives.is_empty() && quest.objectives.iter().all(|o| o.done) {
+                finished.push(quest.id.clone());
+            }
+        }
+
+        for id in finished {
+            if let Some(message) = self.complete_quest(&id) {
+                messages.push(message);
+            }
+        }
+        messages
+    }
+
+    /// All quests for display: active ones first, then finished ones, each by title.
+    pub fn quest_log(&self) -> Vec<&Quest> {
+        let mut quests: Vec<&Quest> = self.quests.values().collect();
+        quests.sort_by(|a, b| {
+            (a.status != QuestStatus::Active, &a.title).cmp(&(b.status != QuestStatus::Active, &b.title))
+        });
+        quests
+    }
+
+    /// Mark a quest completed and hand over its rewards. Returns `None` if
+    /// there is no such active quest.
+    pub fn complete_quest(&mut self, quest_id: &str) -> Option<String> {
+        let quest = self.quests.get_mut(quest_id).filter(|q| q.status == QuestStatus::Active)?;
+        quest.status = QuestStatus::Completed;
+        let (title, money, items) = (quest.title.clone(), quest.reward_money, quest.reward_items.clone());
+
+        self.player.money += money;
+        let mut rewards = Vec::new();
+        if money > 0 {
+            rewards.push(format!("{} coins", money));
+        }
+        for item_id in items {
+            let Some(item) = self.items.get(&item_id) else { continue };
+            rewards.push(item.name.clone());
+            // The reward may be lying somewhere or held by the giver; it moves to the player.
+            for loc in self.locations.values_mut() {
+                loc.items.retain(|id| id != &item_id);
+            }
+            for actor in self.actors.values_mut() {
+                actor.inventory.retain(|id| id != &item_id);
+            }
+            if !self.player.inventory.contains(&item_id) {
+                self.player.inventory.push(item_id);
+            }
+        }
+
+        Some(if rewards.is_empty() {
+            format!("Quest complete: {}", title)
+        } else {
+            format!("Quest complete: {} (reward: {})", title, rewards.join(", "))
+        })
+    }
+
+    /// The region the tile at `pos` belongs to, if any.
+    pub fn region_at(&self, pos: (i32, i32, i32)) -> Option<&Region> {
+        self.regions.values().find(|r| r.tiles.contains(&pos))
+    }
+
+    /// Biome, elevation and danger of the cell at `pos` in this world.
+    pub fn terrain_at(&self, pos: (i32, i32, i32)) -> Terrain {
+        biome::terrain_at(self.seed, pos)
+    }
+
+    /// Work out where moving `direction` from the current position leads,
+    /// honouring any exit recorded on the current location.
+    ///
+    /// Locked doors open automatically when the player carries the key.
+    /// Returns a player-facing reason when the way is barred.
+    pub fn resolve_move(&mut self, direction: &str) -> Result<(i32, i32, i32), String> {
+        let (x, y, z) = self.current_pos;
+        let (dx, dy, dz) = direction_offset(direction)
+            .ok_or_else(|| format!("You can't go {}.", direction))?;
+        let default_target = (x + dx, y + dy, z + dz);
+
+        let exit = match self.locations.get(&self.current_pos).and_then(|l| l.exits.get(direction)) {
+            Some(exit) => exit.clone(),
+            None if is_vertical_direction(direction) => {
+                return Err(format!("There is no way {} from here.", direction));
+            }
+            None => return Ok(default_target),
+        };
+
+        match exit {
+            Exit::Open { to } | Exit::OneWay { to } | Exit::Door { to, locked: false, .. } => Ok(to),
+            Exit::Door { to, locked: true, key_id } => {
+                let has_key = key_id.as_ref().is_some_and(|k| self.player.inventory.contains(k));
+                if !has_key {
+                    return Err(format!("The way {} is locked.", direction));
+                }
+                self.unlock_door(direction);
+                Ok(to)
+            }
+            Exit::Blocked | Exit::Hidden { .. } => Err(format!("You can't go {} from here.", direction)),
+        }
+    }
+
+    /// Unlock the locked door `direction` from the current position along
+    /// with the door on its far side that leads back, so the way back opens
+    /// too. Returns where the door leads, or `None` if there is no such door.
+    pub fn unlock_door(&mut self, direction: &str) -> Option<(i32, i32, i32)> {
+        let here = self.current_pos;
+        let to = match self.locations.get(&here).and_then(|l| l.exits.get(direction)) {
+            Some(Exit::Door { to, locked: true, .. }) => *to,
+            _ => return None,
+        };
+        let sides = [(here, direction.to_string(), to), (to, get_opposite_direction(direction), here)];
+        for (pos, dir, target) in sides {
+            if let Some(Exit::Door { to, locked, .. }) = self.locations.get_mut(&pos).and_then(|l| l.exits.get_mut(&dir)) {
+                if *to == target {
+                    *locked = false;
+                }
+            }
+        }
+        Some(to)
+    }
+
+    /// Record a two-way passage between `from` and `to`, leaving any exit
+    /// the DM already placed on either side untouched. A one-way exit gets a
+    /// blocked return side instead.
+    pub fn link_exits(&mut self, from: (i32, i32, i32), to: (i32, i32, i32), direction: &str) {
+        let one_way = match self.locations.get_mut(&from) {
+            Some(loc) => matches!(
+                loc.exits.entry(direction.to_string()).or_insert(Exit::Open { to }),
+                Exit::OneWay { .. }
+            ),
+            None => false,
+        };
+        let back = if one_way { Exit::Blocked } else { Exit::Open { to: from } };
+        if let Some(loc) = self.locations.get_mut(&to) {
+            loc.exits.entry(get_opposite_direction(direction)).or_insert(back);
+        }
+    }
+
     pub fn new() -> Self {
         Self {
-            current_pos: (0, 0),  // Starting at origin
+            current_pos: (0, 0, 0),  // Starting at origin
             locations: HashMap::new(),
             actors: HashMap::new(),
             items: HashMap::new(),
@@ -275,6 +1129,335 @@ impl WorldState {
             combat: CombatState::default(),
             max_items: 20,
             max_combatants: 4,
+            seed: new_world_seed(),
+            regions: HashMap::new(),
+            clock: WorldClock::default(),
+            events: Vec::new(),
+            weather: Weather::default(),
+            factions: HashMap::new(),
+            reputation: HashMap::new(),
+            spells: HashMap::new(),
+            death_policy: DeathPolicy::default(),
+            save_format: SaveFormat::default(),
+            rest_point: (0, 0, 0),
+            player_defeated: false,
+            deaths: 0,
+            created: Some(Local::now()),
+            turns: 0,
+            playtime_secs: 0,
+            quests: HashMap::new(),
+            defeated_actors: Vec::new(),
+            journal: Vec::new(),
+            faded_journal: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_location(name: &str) -> Location {
+        Location {
+            name: name.to_string(),
+            description: String::new(),
+            items: vec![],
+            actors: vec![],
+            exits: HashMap::new(),
+            cached_image_path: None,
+            image_prompt: String::new(),
+            visited: true,
+        }
+    }
+
+    #[test]
+    fn test_journal_keeps_only_recent_turns() {
+        let mut world = WorldState::new();
+        for turn in 1..=(MAX_JOURNAL_ENTRIES as u32 + 10) {
+            world.turns = turn;
+            world.record_turn("wait", "Time passes.", vec![]);
         }
+        assert_eq!(world.journal.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(world.journal[0].turn, 11);
+        assert_eq!(world.faded_journal.iter().map(|e| e.turn).collect::<Vec<_>>(), (1..=10).collect::<Vec<_>>());
+        assert_eq!(world.recent_turns(3).iter().map(|e| e.turn).collect::<Vec<_>>(), vec![508, 509, 510]);
+    }
+
+    #[test]
+    fn test_exit_round_trip() {
+        let mut loc = test_location("Vault");
+        loc.exits.insert("west".to_string(), Exit::Door { to: (-1, 0, 0), locked: true, key_id: Some("vault_key".to_string()) });
+        loc.exits.insert("south".to_string(), Exit::Blocked);
+
+        let json = serde_json::to_string(&loc).unwrap();
+        let back: Location = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.exits, loc.exits);
+    }
+
+    #[test]
+    fn test_resolve_move_respects_exits() {
+        let mut world = WorldState::new();
+        let mut start = test_location("Start");
+        start.exits.insert("north".to_string(), Exit::Blocked);
+        start.exits.insert("east".to_string(), Exit::Door { to: (1, 0, 0), locked: true, key_id: Some("key".to_string()) });
+        world.locations.insert((0, 0, 0), start);
+
+        assert!(world.resolve_move("north").is_err());
+        assert!(world.resolve_move("east").is_err());
+        assert_eq!(world.resolve_move("south"), Ok((0, -1, 0)));
+
+        let mut vault = test_location("Vault");
+        vault.exits.insert("west".to_string(), Exit::Door { to: (0, 0, 0), locked: true, key_id: Some("key".to_string()) });
+        world.locations.insert((1, 0, 0), vault);
+        world.player.inventory.push("key".to_string());
+        assert_eq!(world.resolve_move("east"), Ok((1, 0, 0)));
+        assert!(world.locations[&(0, 0, 0)].exits["east"].is_traversable());
+        assert!(world.locations[&(1, 0, 0)].exits["west"].is_traversable());
+    }
+
+    #[test]
+    fn test_flat_save_migrates_to_level_zero() {
+        let json = r#"{
+            "current_pos": [2, -1],
+            "locations": {"2,-1": {"name": "Gate", "exits": {"north": {"kind": "Open", "to": [2, 0]}, "east": null}}},
+            "actors": {"guard": {"id": "guard", "name": "Guard", "description": "", "current_pos": [2, -1], "inventory": [], "money": 0}},
+            "items": {},
+            "player": {"inventory": [], "money": 0},
+            "combat": {"active": false, "combatants": [], "current_turn_index": 0, "round_number": 0},
+            "max_items": 20,
+            "max_combatants": 4
+        }"#;
+        let (save, _) = crate::migration::migrate(serde_json::from_str(json).unwrap()).unwrap();
+        let world: WorldState = serde_json::from_value(save).unwrap();
+        assert_eq!(world.current_pos, (2, -1, 0));
+        assert_eq!(world.actors["guard"].current_pos, (2, -1, 0));
+        assert_eq!(world.locations[&(2, -1, 0)].exits["north"], Exit::Open { to: (2, 0, 0) });
+
+        // A legacy null exit is unexplored, so the next step that way links it.
+        let mut world = world;
+        assert_eq!(world.resolve_move("east"), Ok((3, -1, 0)));
+        world.locations.insert((3, -1, 0), test_location("Field"));
+        world.link_exits((2, -1, 0), (3, -1, 0), "east");
+        assert_eq!(world.locations[&(2, -1, 0)].exits["east"], Exit::Open { to: (3, -1, 0) });
+        assert_eq!(world.locations[&(3, -1, 0)].exits["west"], Exit::Open { to: (2, -1, 0) });
+
+        let round_trip: WorldState = serde_json::from_str(&serde_json::to_string(&world).unwrap()).unwrap();
+        assert!(round_trip.locations.contains_key(&(2, -1, 0)));
+    }
+
+    #[test]
+    fn test_vertical_movement_needs_exit() {
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), test_location("Street"));
+        assert!(world.resolve_move("up").is_err());
+        assert!(world.resolve_move("enter").is_err());
+
+        world.locations.get_mut(&(0, 0, 0)).unwrap()
+            .exits.insert("down".to_string(), Exit::Open { to: (0, 0, -1) });
+        assert_eq!(world.resolve_move("down"), Ok((0, 0, -1)));
+        assert_eq!(get_opposite_direction("enter"), "exit");
+        assert_eq!(get_opposite_direction("up"), "down");
+    }
+
+    #[test]
+    fn test_interiors_do_not_share_cells_with_floors() {
+        let mut world = WorldState::new();
+        let mut street = test_location("Street");
+        street.exits.insert("up".to_string(), Exit::Open { to: (0, 0, 1) });
+        street.exits.insert("enter".to_string(), Exit::Open { to: (0, 0, INTERIOR_LEVELS) });
+        world.locations.insert((0, 0, 0), street);
+
+        let upstairs = world.resolve_move("up").unwrap();
+        let inside = world.resolve_move("enter").unwrap();
+        assert_ne!(upstairs, inside);
+        assert_eq!(describe_level(inside.2), "the ground floor indoors");
+        assert_eq!(describe_level(inside.2 + 1), "level 1 above the surface indoors");
+        assert_eq!(describe_level(-2), "level 2 below ground");
+    }
+
+    #[test]
+    fn test_link_exits_one_way() {
+        let mut world = WorldState::new();
+        let mut top = test_location("Ledge");
+        top.exits.insert("south".to_string(), Exit::OneWay { to: (0, -1, 0) });
+        world.locations.insert((0, 0, 0), top);
+        world.locations.insert((0, -1, 0), test_location("Pit"));
+
+        world.link_exits((0, 0, 0), (0, -1, 0), "south");
+        assert_eq!(world.locations[&(0, -1, 0)].exits.get("north"), Some(&Exit::Blocked));
+    }
+
+    #[test]
+    fn test_diagonal_movement() {
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), test_location("Crossroads"));
+        assert_eq!(world.resolve_move("northeast"), Ok((1, 1, 0)));
+        assert_eq!(world.resolve_move("southwest"), Ok((-1, -1, 0)));
+
+        world.locations.insert((1, 1, 0), test_location("Hill"));
+        world.link_exits((0, 0, 0), (1, 1, 0), "northeast");
+        assert_eq!(world.locations[&(1, 1, 0)].exits.get("southwest"), Some(&Exit::Open { to: (0, 0, 0) }));
+        assert_eq!(get_opposite_direction("northwest"), "southeast");
+    }
+
+    #[test]
+    fn test_quest_objectives_complete_and_pay_out() {
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), test_location("Camp"));
+        let mut cave = test_location("Cave");
+        cave.items.push("amulet".to_string());
+        world.locations.insert((0, 1, 0), cave);
+        world.items.insert("amulet".to_string(), Item {
+            id: "amulet".to_string(),
+            name: "Amulet".to_string(),
+            description: String::new(),
+            item_type: ItemType::QuestItem,
+            state: ItemState::Normal,
+            properties: ItemProperties::default(),
+        });
+        world.quests.insert("find_cave".to_string(), Quest {
+            id: "find_cave".to_string(),
+            title: "The Cave".to_string(),
+            description: String::new(),
+            giver: None,
+            objectives: vec![
+                QuestObjective { description: "Reach the cave".to_string(), goal: ObjectiveGoal::ReachLocation { pos: (0, 1, 0) }, done: false },
+                QuestObjective { description: "Defeat the bear".to_string(), goal: ObjectiveGoal::DefeatActor { actor_id: "bear".to_string() }, done: false },
+            ],
+            reward_money: 25,
+            reward_items: vec!["amulet".to_string()],
+            status: QuestStatus::Active,
+        });
+
+        assert!(world.check_quests().is_empty());
+        world.current_pos = (0, 1, 0);
+        assert_eq!(world.check_quests(), vec!["Objective complete (The Cave): Reach the cave".to_string()]);
+
+        world.defeated_actors.push("bear".to_string());
+        let messages = world.check_quests();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(world.quests["find_cave"].status, QuestStatus::Completed);
+        assert_eq!(world.player.money, 25);
+        assert!(world.player.inventory.contains(&"amulet".to_string()));
+        assert!(world.locations[&(0, 1, 0)].items.is_empty());
+        assert!(world.complete_quest("find_cave").is_none());
+    }
+
+    #[test]
+    fn test_scheduled_events_fire_and_repeat() {
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), test_location("Market"));
+        world.schedule_event(ScheduledEvent {
+            id: "caravan".to_string(),
+            description: "A caravan arrives.".to_string(),
+            at: 120,
+            repeat_every: None,
+            location: None,
+        });
+        world.schedule_event(ScheduledEvent {
+            id: "market_close".to_string(),
+            description: "The stalls close.".to_string(),
+            at: 60,
+            repeat_every: Some(crate::clock::MINUTES_PER_DAY),
+            location: Some((0, 0, 0)),
+        });
+        assert_eq!(world.events[0].id, "market_close");
+
+        // Weather changes are reported too; only look at the events.
+        let events = |world: &mut WorldState, minutes| -> Vec<String> {
+            world.advance_time(minutes).into_iter().filter(|m| m.starts_with("Event")).collect()
+        };
+        assert!(events(&mut world, 59).is_empty());
+        assert_eq!(events(&mut world, 1), vec!["Event (Market): The stalls close.".to_string()]);
+        assert_eq!(world.events[0].id, "caravan");
+
+        // Three days pass: the caravan comes once, the daily event fires once and is rescheduled.
+        assert_eq!(events(&mut world, 3 * crate::clock::MINUTES_PER_DAY).len(), 2);
+        assert_eq!(world.events.len(), 1);
+        assert_eq!(world.events[0].at, 60 + 4 * crate::clock::MINUTES_PER_DAY);
+    }
+
+    #[test]
+    fn test_weather_hazards_apply_and_wear_off() {
+        let mut world = WorldState::new();
+        world.weather = Weather { condition: crate::weather::Condition::Blizzard, temperature: -10 };
+
+        assert_eq!(world.apply_weather_hazards(MINUTES_PER_STEP).len(), 1);
+        assert_eq!(world.player.status_effects[0].effect_type, StatusType::Frozen);
+        // Staying out in it refreshes the effect rather than stacking it.
+        assert!(world.apply_weather_hazards(MINUTES_PER_STEP).is_empty());
+        assert_eq!(world.player.status_effects.len(), 1);
+
+        world.weather = Weather::default();
+        world.apply_weather_hazards(2 * MINUTES_PER_STEP);
+        assert_eq!(world.player.status_effects[0].duration, HAZARD_DURATION - 2);
+        world.apply_weather_hazards(crate::clock::MINUTES_PER_HOUR);
+        assert!(world.player.status_effects.is_empty());
+    }
+
+    #[test]
+    fn test_interiors_never_apply_weather_hazards() {
+        let mut world = WorldState::new();
+        world.current_pos = (0, 0, INTERIOR_LEVELS);
+        world.weather = Weather { condition: crate::weather::Condition::Blizzard, temperature: -10 };
+        assert!(world.apply_weather_hazards(MINUTES_PER_STEP).is_empty());
+        assert!(world.player.status_effects.is_empty());
+
+        world.refresh_weather();
+        assert!(world.weather.hazard().is_none());
+    }
+
+    #[test]
+    fn test_reputation_sets_prices_and_defeats_cost_standing() {
+        let mut world = WorldState::new();
+        world.factions.insert("guild".to_string(), Faction {
+            id: "guild".to_string(),
+            name: "Merchants' Guild".to_string(),
+            description: String::new(),
+        });
+        world.actors.insert("trader".to_string(), Actor {
+            id: "trader".to_string(),
+            name: "Trader".to_string(),
+            description: String::new(),
+            current_pos: (0, 0, 0),
+            inventory: vec!["rope".to_string()],
+            money: 0,
+            faction: Some("guild".to_string()),
+        });
+        world.items.insert("rope".to_string(), Item {
+            id: "rope".to_string(),
+            name: "Rope".to_string(),
+            description: String::new(),
+            item_type: ItemType::Tool,
+            state: ItemState::Normal,
+            properties: ItemProperties { value: Some(20), ..ItemProperties::default() },
+        });
+        world.player.money = 20;
+
+        world.adjust_reputation("guild", 50);
+        assert_eq!(world.disposition_of("trader"), Disposition::Allied);
+        assert_eq!(world.trade("trader", "rope", true), Ok("Bought Rope from Trader for 17 (allied)".to_string()));
+        assert_eq!(world.player.money, 3);
+        assert_eq!(world.trade("trader", "rope", false), Ok("Sold Rope to Trader for 12 (allied)".to_string()));
+
+        world.combat.combatants.push(Combatant {
+            id: "trader".to_string(),
+            is_player: false,
+            hp: 0,
+            max_hp: 50,
+            weapon_id: None,
+            armor_id: None,
+            initiative: 1,
+            status_effects: Vec::new(),
+            temp_defense: 0,
+        });
+        assert_eq!(world.record_defeats().len(), 1);
+        assert!(world.record_defeats().is_empty());
+        assert_eq!(world.reputation_with("guild"), 50 - faction::KILL_PENALTY);
+
+        world.adjust_reputation("guild", -200);
+        assert_eq!(world.reputation_with("guild"), faction::MIN_REPUTATION);
+        assert!(world.trade("trader", "rope", true).is_err());
+        assert!(world.adjust_reputation("nobody", 5).is_none());
     }
 }
@@ -3,14 +3,14 @@ use crate::model::{Item, Location};
 
 #[derive(Debug)]
 pub enum ParsedAction {
-    MoveTo(i32, i32),
-    CreateLocation((i32, i32), Location),
-    UpdateLocation((i32, i32), Location),
+    MoveTo(i32, i32, i32),
+    CreateLocation((i32, i32, i32), Location),
+    UpdateLocation((i32, i32, i32), Location),
     CreateItem(Item),
     AddItemToInventory(String),
     RemoveItemFromInventory(String),
-    AddItemToLocation { pos: (i32, i32), item_id: String },
-    RemoveItemFromLocation { pos: (i32, i32), item_id: String },
+    AddItemToLocation { pos: (i32, i32, i32), item_id: String },
+    RemoveItemFromLocation { pos: (i32, i32, i32), item_id: String },
     UseItem(String),
     EquipItem(String),
     UnequipItem(String),
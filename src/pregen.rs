@@ -0,0 +1,325 @@
+use crate::llm::LlmClient;
+use crate::model::{direction_offset, is_vertical_direction, Exit, GeneratedLocation, WorldState, COMPASS_DIRECTIONS};
+use crate::worldgen;
+use chrono::{DateTime, Duration, Local};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+/// Most background generation requests allowed at once, so a local LLM
+/// server still has room for the player's own turns.
+pub const MAX_IN_FLIGHT: usize = 2;
+
+/// Wait before retrying a cell whose generation failed; doubles with each
+/// further failure up to `MAX_RETRY_SECS`, so an LLM outage isn't hammered.
+const RETRY_SECS: i64 = 5;
+const MAX_RETRY_SECS: i64 = 300;
+
+/// A finished background request.
+struct PregenResult {
+    /// Seed of the world the request was made for; results for another world are dropped.
+    seed: u64,
+    target: (i32, i32, i32),
+    result: Result<GeneratedLocation, String>,
+}
+
+struct Job {
+    seed: u64,
+    cancel: Arc<AtomicBool>,
+    #[cfg(not(target_arch = "wasm32"))]
+    handle: tokio::task::JoinHandle<()>,
+}
+
+/// Failed attempts at a cell and when it may be tried again.
+struct Backoff {
+    seed: u64,
+    failures: u32,
+    retry_at: DateTime<Local>,
+}
+
+impl Job {
+    fn cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+        // Browser futures can't be aborted; the flag stops them before the request is sent.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.handle.abort();
+    }
+}
+
+/// Speculatively generates the unexplored neighbours of the player's position
+/// while they are idle, so stepping into them doesn't wait on the LLM.
+///
+/// Generated locations are stored unvisited and without exits; exits are
+/// linked when the player actually walks in.
+pub struct Pregenerator {
+    sender: Sender<PregenResult>,
+    receiver: Receiver<PregenResult>,
+    jobs: HashMap<(i32, i32, i32), Job>,
+    backoff: HashMap<(i32, i32, i32), Backoff>,
+}
+
+impl Default for Pregenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pregenerator {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver,
+            jobs: HashMap::new(),
+            backoff: HashMap::new(),
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_pending(&self, target: (i32, i32, i32)) -> bool {
+        self.jobs.contains_key(&target)
+    }
+
+    /// Whether a cell is waiting out a failure before it is tried again.
+    pub fn is_backing_off(&self, seed: u64, target: (i32, i32, i32)) -> bool {
+        self.backoff.get(&target).is_some_and(|b| b.seed == seed && b.retry_at > Local::now())
+    }
+
+    /// Cancel jobs that are no longer next to the player (or belong to
+    /// another world) and start new ones for empty frontier cells, up to
+    /// `MAX_IN_FLIGHT`. Cells that failed recently are left alone.
+    pub fn fill(&mut self, world: &WorldState, llm_client: &LlmClient) {
+        let frontier = frontier(world);
+
+        let stale: Vec<(i32, i32, i32)> = self.jobs.iter()
+            .filter(|(pos, job)| job.seed != world.seed || !frontier.iter().any(|(_, target)| target == *pos))
+            .map(|(pos, _)| *pos)
+            .collect();
+        for pos in stale {
+            if let Some(job) = self.jobs.remove(&pos) {
+                job.cancel();
+            }
+        }
+
+        for (direction, target) in frontier {
+            if self.jobs.len() >= MAX_IN_FLIGHT {
+                break;
+            }
+            if self.jobs.contains_key(&target) || self.is_backing_off(world.seed, target) {
+                continue;
+            }
+            let prompt = worldgen::build_location_prompt(world, target, direction);
+            self.spawn(world.seed, target, prompt, llm_client.clone());
+        }
+    }
+
+    /// Stop all background work, e.g. before a foreground LLM request.
+    pub fn cancel_all(&mut self) {
+        for (_, job) in self.jobs.drain() {
+            job.cancel();
+        }
+    }
+
+    /// Store finished locations in the world as unvisited cells.
+    /// Returns a log line for each finished job.
+    pub fn collect(&mut self, world: &mut WorldState) -> Vec<String> {
+        let mut notes = Vec::new();
+
+        while let Ok(done) = self.receiver.try_recv() {
+            if self.jobs.get(&done.target).is_some_and(|job| job.seed == done.seed) {
+                self.jobs.remove(&done.target);
+            }
+            if done.seed != world.seed {
+                continue;
+            }
+            let (x, y, z) = done.target;
+
+            match done.result {
+                Ok(generated) => {
+                    self.backoff.remove(&done.target);
+                    if world.locations.contains_key(&done.target) {
+                        continue;
+                    }
+                    notes.extend(worldgen::place_generated(world, generated, done.target));
+                    notes.push(format!("Pre-generated '{}' at ({}, {}, {})", world.locations[&done.target].name, x, y, z));
+                }
+                Err(e) => {
+                    let failures = match self.backoff.get(&done.target) {
+                        Some(b) if b.seed == done.seed => b.failures + 1,
+                        _ => 1,
+                    };
+                    let wait = (RETRY_SECS << (failures - 1).min(16)).min(MAX_RETRY_SECS);
+                    self.backoff.insert(done.target, Backoff {
+                        seed: done.seed,
+                        failures,
+                        retry_at: Local::now() + Duration::seconds(wait),
+                    });
+                    notes.push(format!("Pre-generation failed at ({}, {}, {}), retrying in {}s: {}", x, y, z, wait, e));
+                }
+            }
+        }
+        notes
+    }
+
+    fn spawn(&mut self, seed: u64, target: (i32, i32, i32), prompt: String, llm_client: LlmClient) {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let task_cancel = cancel.clone();
+        let sender = self.sender.clone();
+
+        let task = async move {
+            if task_cancel.load(Ordering::SeqCst) {
+                return;
+            }
+            let result = llm_client.generate_location(worldgen::SYSTEM_PROMPT, &prompt).await
+                .map_err(|e| e.to_string());
+            if !task_cancel.load(Ordering::SeqCst) {
+                let _ = sender.send(PregenResult { seed, target, result });
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let job = Job { seed, cancel, handle: tokio::spawn(task) };
+
+        #[cfg(target_arch = "wasm32")]
+        let job = {
+            wasm_bindgen_futures::spawn_local(task);
+            Job { seed, cancel }
+        };
+
+        self.jobs.insert(target, job);
+    }
+}
+
+/// Empty cells the player could step into next, cardinal directions first.
+///
+/// Walls, locked doors and hidden passages are skipped, as are level changes
+/// without a stair or doorway.
+pub fn frontier(world: &WorldState) -> Vec<(&'static str, (i32, i32, i32))> {
+    let (x, y, z) = world.current_pos;
+    let exits = world.locations.get(&world.current_pos).map(|l| &l.exits);
+
+    let mut directions: Vec<&'static str> = COMPASS_DIRECTIONS.iter().step_by(2)
+        .chain(COMPASS_DIRECTIONS.iter().skip(1).step_by(2))
+        .copied()
+        .collect();
+    directions.extend(["up", "down", "enter", "exit"]);
+
+    directions.into_iter()
+        .filter_map(|dir| {
+            let target = match exits.and_then(|e| e.get(dir)) {
+                Some(Exit::Open { to }) | Some(Exit::OneWay { to }) | Some(Exit::Door { to, locked: false, .. }) => *to,
+                Some(_) => return None,
+                None if is_vertical_direction(dir) => return None,
+                None => {
+                    let (dx, dy, dz) = direction_offset(dir)?;
+                    (x + dx, y + dy, z + dz)
+                }
+            };
+            (!world.locations.contains_key(&target)).then_some((dir, target))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lone_world() -> WorldState {
+        let mut world = WorldState::new();
+        world.locations.insert((0, 0, 0), worldgen::fallback_location((0, 0, 0)));
+        world
+    }
+
+    #[test]
+    fn test_frontier_skips_walls_and_known_cells() {
+        let mut world = lone_world();
+        world.locations.insert((1, 0, 0), worldgen::fallback_location((1, 0, 0)));
+        let start = world.locations.get_mut(&(0, 0, 0)).unwrap();
+        start.exits.insert("north".to_string(), Exit::Blocked);
+        start.exits.insert("down".to_string(), Exit::Open { to: (0, 0, -1) });
+
+        let frontier = frontier(&world);
+        let directions: Vec<&str> = frontier.iter().map(|(dir, _)| *dir).collect();
+        assert_eq!(&directions[..3], &["south", "west", "northeast"]);
+        assert!(!directions.contains(&"north"));
+        assert!(!directions.contains(&"east"));
+        assert!(frontier.contains(&("down", (0, 0, -1))));
+    }
+
+    #[tokio::test]
+    async fn test_failed_jobs_are_collected_without_changing_world() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut world = lone_world();
+        let mut pregen = Pregenerator::new();
+
+        pregen.fill(&world, &llm_client);
+        assert_eq!(pregen.in_flight(), MAX_IN_FLIGHT);
+        assert!(pregen.is_pending((0, 1, 0)));
+
+        let mut notes = Vec::new();
+        for _ in 0..200 {
+            notes.extend(pregen.collect(&mut world));
+            if pregen.in_flight() == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(pregen.in_flight(), 0);
+        assert_eq!(notes.len(), MAX_IN_FLIGHT);
+        assert_eq!(world.locations.len(), 1);
+
+        // The failed cells wait before they are tried again.
+        assert!(pregen.is_backing_off(world.seed, (0, 1, 0)));
+        pregen.fill(&world, &llm_client);
+        assert!(!pregen.is_pending((0, 1, 0)));
+        assert_eq!(pregen.in_flight(), MAX_IN_FLIGHT);
+        pregen.cancel_all();
+    }
+
+    #[tokio::test]
+    async fn test_results_for_another_world_clear_their_job() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut world = lone_world();
+        let mut pregen = Pregenerator::new();
+        pregen.fill(&world, &llm_client);
+        let old_seed = world.seed;
+        world.seed = old_seed.wrapping_add(1);
+
+        pregen.sender.send(PregenResult { seed: old_seed, target: (0, 1, 0), result: Err("gone".to_string()) }).unwrap();
+        assert!(pregen.collect(&mut world).is_empty());
+        assert!(!pregen.is_pending((0, 1, 0)));
+        assert!(!pregen.is_backing_off(world.seed, (0, 1, 0)));
+        pregen.cancel_all();
+    }
+
+    #[tokio::test]
+    async fn test_results_fill_empty_cells_as_unvisited() {
+        let mut world = lone_world();
+        let mut pregen = Pregenerator::new();
+        let mut location = worldgen::fallback_location((0, 1, 0));
+        location.name = "Orchard".to_string();
+        location.visited = true;
+
+        pregen.sender.send(PregenResult { seed: world.seed, target: (0, 1, 0), result: Ok(location.clone().into()) }).unwrap();
+        pregen.sender.send(PregenResult { seed: world.seed.wrapping_add(1), target: (0, -1, 0), result: Ok(location.into()) }).unwrap();
+        pregen.collect(&mut world);
+
+        assert!(!world.locations[&(0, 1, 0)].visited);
+        assert_eq!(world.locations[&(0, 1, 0)].name, "Orchard");
+        assert!(!world.locations.contains_key(&(0, -1, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_clears_jobs() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let world = lone_world();
+        let mut pregen = Pregenerator::new();
+        pregen.fill(&world, &llm_client);
+        pregen.cancel_all();
+        assert_eq!(pregen.in_flight(), 0);
+    }
+}
@@ -1,17 +1,128 @@
 use anyhow::{Context, Result};
-use crate::model::{WorldState, Location, Actor, CombatState};
+use crate::migration::{self, CURRENT_FORMAT_VERSION};
+use crate::model::{JournalEntry, SaveFormat, WorldState};
 use std::path::PathBuf;
 #[cfg(not(target_arch = "wasm32"))]
-use std::fs;
-use std::collections::HashMap;
+use std::{fs, path::Path};
 use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
 
 pub trait Storage {
     fn list_saves(&self) -> Result<Vec<SaveInfo>>;
-    fn save_game(&self, filename: &str, content: &str) -> Result<()>;
-    fn load_game(&self, filename: &str) -> Result<String>;
+    fn save_game(&self, filename: &str, content: &[u8]) -> Result<()>;
+    fn load_game(&self, filename: &str) -> Result<Vec<u8>>;
     fn delete_save(&self, filename: &str) -> Result<()>;
+
+    /// Save a world. Backends that keep worlds in pieces override this to
+    /// store it without encoding the whole thing into one blob first.
+    fn save_world(&self, filename: &str, save: &VersionedSave, format: SaveFormat) -> Result<()> {
+        let content = encode(save, format).context("Failed to serialize world state")?;
+        self.save_game(filename, &content)
+    }
+
+    /// At most the first `len` bytes of a save, enough to read the header
+    /// that is written ahead of the world.
+    fn load_prefix(&self, filename: &str, len: usize) -> Result<Vec<u8>> {
+        let mut content = self.load_game(filename)?;
+        content.truncate(len);
+        Ok(content)
+    }
+
+    /// Keep journal entries that no longer fit in a world's save, adding to
+    /// whatever is already archived for it.
+    fn archive_journal(&self, _filename: &str, _entries: &[JournalEntry]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Everything `archive_journal` kept for a world, in the order it came in.
+    fn load_journal_archive(&self, _filename: &str) -> Result<Vec<JournalEntry>> {
+        Ok(Vec::new())
+    }
+
+    /// Names of the backups kept for a save, newest first. Backups can be
+    /// read with `load_game`.
+    fn list_backups(&self, _filename: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Put a backup back in place of its save.
+    fn restore_backup(&self, _filename: &str, _backup: &str) -> Result<()> {
+        anyhow::bail!("This storage does not keep backups")
+    }
+}
+
+/// How many previous versions of each save are kept.
+pub const MAX_BACKUPS: usize = 3;
+
+/// Where native builds keep their saves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageBackend {
+    /// One JSON file per world in the save directory.
+    #[default]
+    Files,
+    /// A single SQLite database in the save directory; see `crate::sqlite`.
+    Sqlite,
+}
+
+impl fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageBackend::Files => write!(f, "files"),
+            StorageBackend::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "files" | "file" | "json" => Ok(StorageBackend::Files),
+            "sqlite" | "db" => Ok(StorageBackend::Sqlite),
+            other => Err(format!("Unknown storage '{}' (expected files or sqlite)", other)),
+        }
+    }
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Added to the filename of gzipped saves on disk.
+#[cfg(not(target_arch = "wasm32"))]
+const GZIP_SUFFIX: &str = ".gz";
+
+/// Serialize a save in the given format.
+pub fn encode<T: Serialize>(save: &T, format: SaveFormat) -> Result<Vec<u8>> {
+    match format {
+        SaveFormat::Json => Ok(serde_json::to_vec_pretty(save)?),
+        SaveFormat::Gzip => {
+            // Saves happen on every move, so favour speed over the last few percent of size.
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(&serde_json::to_vec(save)?)?;
+            Ok(encoder.finish()?)
+        }
+    }
+}
+
+/// Turn stored bytes back into JSON text, whichever format they were written in.
+pub fn decode(bytes: &[u8]) -> Result<String> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut json = String::new();
+        GzDecoder::new(bytes).read_to_string(&mut json)
+            .context("Save file is not valid gzip")?;
+        Ok(json)
+    } else {
+        String::from_utf8(bytes.to_vec()).context("Save file is not valid UTF-8")
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -21,12 +132,81 @@ pub struct FileSystemStorage {
 
 #[cfg(not(target_arch = "wasm32"))]
 impl FileSystemStorage {
+    /// The directory is only created once something is saved into it.
     pub fn new(save_dir: PathBuf) -> Self {
-        if !save_dir.exists() {
-            fs::create_dir_all(&save_dir).unwrap_or_default();
-        }
         Self { save_dir }
     }
+
+    fn backup_name(filename: &str, n: usize) -> String {
+        format!("{}.bak{}", filename, n)
+    }
+
+    /// The append-only file of a world's archived journal entries, one JSON
+    /// object per line: `castle.journal.jsonl` beside `castle.json`.
+    fn journal_path(&self, filename: &str) -> PathBuf {
+        self.save_dir.join(format!("{}.journal.jsonl", filename.strip_suffix(".json").unwrap_or(filename)))
+    }
+
+    /// Where a save is on disk: gzipped saves are `name.json.gz`, but callers
+    /// always ask for `name.json`.
+    fn path_of(&self, filename: &str) -> PathBuf {
+        let gzipped = self.save_dir.join(format!("{}{}", filename, GZIP_SUFFIX));
+        if gzipped.exists() { gzipped } else { self.save_dir.join(filename) }
+    }
+
+    /// Write a save under the name matching its format, removing the copy in
+    /// the other format if the world switched.
+    fn write_save(&self, filename: &str, content: &[u8]) -> Result<()> {
+        let gzipped = format!("{}{}", filename, GZIP_SUFFIX);
+        let (name, other) = if content.starts_with(&GZIP_MAGIC) {
+            (gzipped.as_str(), filename)
+        } else {
+            (filename, gzipped.as_str())
+        };
+        self.write_atomic(name, content)?;
+        let other = self.save_dir.join(other);
+        if other.exists() {
+            fs::remove_file(&other)
+                .context(format!("Failed to remove old save file: {:?}", other))?;
+        }
+        Ok(())
+    }
+
+    /// Write to a temporary file and rename it over the target, so a crash
+    /// or full disk leaves either the old file or the new one, never half of each.
+    fn write_atomic(&self, filename: &str, content: &[u8]) -> Result<()> {
+        let path = self.save_dir.join(filename);
+        let tmp = self.save_dir.join(format!("{}.tmp", filename));
+        fs::create_dir_all(&self.save_dir)
+            .context(format!("Failed to create save directory: {:?}", self.save_dir))?;
+        {
+            let mut file = fs::File::create(&tmp)
+                .context(format!("Failed to create temp file: {:?}", tmp))?;
+            file.write_all(content)
+                .and_then(|_| file.sync_all())
+                .context(format!("Failed to write temp file: {:?}", tmp))?;
+        }
+        fs::rename(&tmp, &path)
+            .context(format!("Failed to replace save file: {:?}", path))?;
+        Ok(())
+    }
+
+    /// Shift `.bak1`..`.bakN` along by one and copy the current save into `.bak1`.
+    fn rotate_backups(&self, filename: &str) -> Result<()> {
+        let path = self.path_of(filename);
+        if !path.exists() {
+            return Ok(());
+        }
+        for n in (1..MAX_BACKUPS).rev() {
+            let from = self.save_dir.join(Self::backup_name(filename, n));
+            if from.exists() {
+                fs::rename(&from, self.save_dir.join(Self::backup_name(filename, n + 1)))?;
+            }
+        }
+        fs::copy(&path, self.save_dir.join(Self::backup_name(filename, 1)))
+            .context(format!("Failed to back up save file: {:?}", path))?;
+        Ok(())
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -40,45 +220,99 @@ impl Storage for FileSystemStorage {
         for entry in fs::read_dir(&self.save_dir)? {
             let entry = entry?;
             let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("unknown");
+            let name = name.strip_suffix(GZIP_SUFFIX).unwrap_or(name);
+            if name.ends_with(".json") {
                 let metadata = fs::metadata(&path)?;
                 let modified: DateTime<Local> = metadata.modified()?.into();
-                let filename = path.file_name()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
+                let filename = name.to_string();
 
                 saves.push(SaveInfo {
                     filename,
                     path,
                     modified,
+                    header: None,
                 });
             }
         }
         Ok(saves)
     }
 
-    fn save_game(&self, filename: &str, content: &str) -> Result<()> {
-        let path = self.save_dir.join(filename);
-        fs::write(&path, content)
-            .context(format!("Failed to write save file: {:?}", path))?;
-        Ok(())
+    fn save_game(&self, filename: &str, content: &[u8]) -> Result<()> {
+        self.rotate_backups(filename)?;
+        self.write_save(filename, content)
     }
 
-    fn load_game(&self, filename: &str) -> Result<String> {
-        let path = self.save_dir.join(filename);
-        fs::read_to_string(&path)
+    fn load_game(&self, filename: &str) -> Result<Vec<u8>> {
+        let path = self.path_of(filename);
+        fs::read(&path)
             .context(format!("Failed to read save file: {:?}", path))
     }
 
+    fn load_prefix(&self, filename: &str, len: usize) -> Result<Vec<u8>> {
+        let path = self.path_of(filename);
+        let file = fs::File::open(&path)
+            .context(format!("Failed to read save file: {:?}", path))?;
+        let mut content = Vec::new();
+        file.take(len as u64).read_to_end(&mut content)?;
+        Ok(content)
+    }
+
     fn delete_save(&self, filename: &str) -> Result<()> {
-        let path = self.save_dir.join(filename);
-        if path.exists() {
-            fs::remove_file(&path)
-                .context(format!("Failed to delete save file: {:?}", path))?;
+        for path in [self.save_dir.join(filename), self.save_dir.join(format!("{}{}", filename, GZIP_SUFFIX))] {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .context(format!("Failed to delete save file: {:?}", path))?;
+            }
+        }
+        for backup in self.list_backups(filename)? {
+            fs::remove_file(self.save_dir.join(&backup))
+                .context(format!("Failed to delete backup: {}", backup))?;
+        }
+        let journal = self.journal_path(filename);
+        if journal.exists() {
+            fs::remove_file(&journal)
+                .context(format!("Failed to delete journal archive: {:?}", journal))?;
         }
         Ok(())
     }
+
+    fn archive_journal(&self, filename: &str, entries: &[JournalEntry]) -> Result<()> {
+        let mut lines = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut lines, entry)?;
+            lines.push(b'\n');
+        }
+        let path = self.journal_path(filename);
+        fs::create_dir_all(&self.save_dir)
+            .context(format!("Failed to create save directory: {:?}", self.save_dir))?;
+        fs::OpenOptions::new().create(true).append(true).open(&path)
+            .and_then(|mut file| file.write_all(&lines))
+            .context(format!("Failed to archive journal: {:?}", path))
+    }
+
+    fn load_journal_archive(&self, filename: &str) -> Result<Vec<JournalEntry>> {
+        let path = self.journal_path(filename);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let text = fs::read_to_string(&path)
+            .context(format!("Failed to read journal archive: {:?}", path))?;
+        // A line cut short by a crash mid-append is skipped rather than losing the rest.
+        Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+
+    fn list_backups(&self, filename: &str) -> Result<Vec<String>> {
+        Ok((1..=MAX_BACKUPS)
+            .map(|n| Self::backup_name(filename, n))
+            .filter(|backup| self.save_dir.join(backup).exists())
+            .collect())
+    }
+
+    fn restore_backup(&self, filename: &str, backup: &str) -> Result<()> {
+        let content = self.load_game(backup)?;
+        self.write_save(filename, &content)
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -99,6 +333,10 @@ impl BrowserStorage {
     fn get_meta_key(&self) -> String {
         format!("{}metadata", self.prefix)
     }
+
+    fn journal_key(&self, filename: &str) -> String {
+        format!("{}{}.journal", self.prefix, filename)
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -121,12 +359,15 @@ impl Storage for BrowserStorage {
             filename: m.filename.clone(),
             path: PathBuf::from(&m.filename),
             modified: m.modified,
+            header: None,
         }).collect())
     }
 
-    fn save_game(&self, filename: &str, content: &str) -> Result<()> {
+    fn save_game(&self, filename: &str, content: &[u8]) -> Result<()> {
         let storage = self.get_storage();
-        storage.set_item(&format!("{}{}", self.prefix, filename), content)
+        // localStorage only holds text, so gzipped saves are kept as plain JSON.
+        let content = decode(content)?;
+        storage.set_item(&format!("{}{}", self.prefix, filename), &content)
             .map_err(|e| anyhow::anyhow!("Failed to save to localStorage: {:?}", e))?;
 
         // Update metadata
@@ -138,6 +379,7 @@ impl Storage for BrowserStorage {
                 filename: filename.to_string(),
                 path: PathBuf::from(filename),
                 modified: Local::now(),
+                header: None,
             });
         }
 
@@ -153,10 +395,11 @@ impl Storage for BrowserStorage {
         Ok(())
     }
 
-    fn load_game(&self, filename: &str) -> Result<String> {
+    fn load_game(&self, filename: &str) -> Result<Vec<u8>> {
         let storage = self.get_storage();
         storage.get_item(&format!("{}{}", self.prefix, filename))
             .map_err(|e| anyhow::anyhow!("Failed to read from localStorage: {:?}", e))?
+            .map(String::into_bytes)
             .ok_or_else(|| anyhow::anyhow!("Save file not found"))
     }
 
@@ -164,6 +407,8 @@ impl Storage for BrowserStorage {
         let storage = self.get_storage();
         storage.remove_item(&format!("{}{}", self.prefix, filename))
             .map_err(|e| anyhow::anyhow!("Failed to remove from localStorage: {:?}", e))?;
+        storage.remove_item(&self.journal_key(filename))
+            .map_err(|e| anyhow::anyhow!("Failed to remove from localStorage: {:?}", e))?;
 
         // Update metadata
         let mut saves = self.list_saves().unwrap_or_default();
@@ -180,10 +425,125 @@ impl Storage for BrowserStorage {
 
         Ok(())
     }
+
+    fn archive_journal(&self, filename: &str, entries: &[JournalEntry]) -> Result<()> {
+        let mut archived = self.load_journal_archive(filename)?;
+        archived.extend_from_slice(entries);
+        self.get_storage().set_item(&self.journal_key(filename), &serde_json::to_string(&archived)?)
+            .map_err(|e| anyhow::anyhow!("Failed to archive journal: {:?}", e))
+    }
+
+    fn load_journal_archive(&self, filename: &str) -> Result<Vec<JournalEntry>> {
+        let archived = self.get_storage().get_item(&self.journal_key(filename))
+            .map_err(|e| anyhow::anyhow!("Failed to read from localStorage: {:?}", e))?;
+        Ok(archived.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+    }
+}
+
+/// What goes on disk: the world with the format it was written in.
+#[derive(Serialize)]
+pub struct VersionedSave<'a> {
+    pub format_version: u32,
+    pub header: SaveHeader,
+    #[serde(flatten)]
+    pub world: &'a WorldState,
+}
+
+/// A summary written at the top of each save so the save list can describe
+/// a world without building it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub world_name: String,
+    pub created: Option<DateTime<Local>>,
+    pub turns: u32,
+    pub playtime_secs: u64,
+    pub location: String,
+    pub money: u32,
+    pub deaths: u32,
+    /// LLM the world was last played with.
+    pub model: String,
+}
+
+impl SaveHeader {
+    fn new(filename: &str, world: &WorldState, model: &str) -> Self {
+        let world_file = split_checkpoint(filename).0;
+        Self {
+            world_name: world_file.strip_suffix(".json").unwrap_or(&world_file).to_string(),
+            created: world.created,
+            turns: world.turns,
+            playtime_secs: world.playtime_secs,
+            location: world.locations.get(&world.current_pos).map(|l| l.name.clone()).unwrap_or_default(),
+            money: world.player.money,
+            deaths: world.deaths,
+            model: model.to_string(),
+        }
+    }
+
+    /// One line for the save list: "Harbour · 12 turns · 1h 05m played · 7 coins".
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.location.is_empty() {
+            parts.push(self.location.clone());
+        }
+        parts.push(format!("{} turns", self.turns));
+        parts.push(format!("{} played", self.playtime()));
+        parts.push(format!("{} coins", self.money));
+        if self.deaths > 0 {
+            parts.push(format!("{} deaths", self.deaths));
+        }
+        if let Some(created) = self.created {
+            parts.push(format!("started {}", created.format("%Y-%m-%d")));
+        }
+        if !self.model.is_empty() {
+            parts.push(self.model.clone());
+        }
+        parts.join(" · ")
+    }
+
+    /// Playtime as "2h 05m" or "12m".
+    pub fn playtime(&self) -> String {
+        let minutes = self.playtime_secs / 60;
+        if minutes >= 60 {
+            format!("{}h {:02}m", minutes / 60, minutes % 60)
+        } else {
+            format!("{}m", minutes)
+        }
+    }
+}
+
+/// How much of a save is read to find its header. The header follows
+/// `format_version` at the top of the file, so this is plenty even gzipped.
+const HEADER_PREFIX_BYTES: usize = 16 * 1024;
+
+/// Walks the top-level keys of a save until it reaches `header`, then stops
+/// with an error so the world after it is never tokenized.
+struct HeaderVisitor<'a>(&'a mut Option<SaveHeader>);
+
+impl<'de> Visitor<'de> for HeaderVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a save object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "header" {
+                *self.0 = map.next_value()?;
+                return Err(de::Error::custom("stopped after header"));
+            }
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
 }
 
 pub struct SaveManager {
     storage: Box<dyn Storage>,
+    /// Recorded in the header of each save.
+    pub model_name: String,
+    /// Where images from imported bundles are unpacked; `None` drops them.
+    pub image_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -191,6 +551,83 @@ pub struct SaveInfo {
     pub filename: String,
     pub path: PathBuf,
     pub modified: DateTime<Local>,
+    /// Filled in by `SaveManager::list_saves`; `None` for saves written
+    /// before headers existed or that cannot be read.
+    pub header: Option<SaveHeader>,
+}
+
+/// Separates a world's file stem from a checkpoint name: `castle@before-boss.json`.
+const CHECKPOINT_SEPARATOR: char = '@';
+
+impl SaveInfo {
+    /// The checkpoint name, if this is a named snapshot rather than a world's main save.
+    pub fn checkpoint(&self) -> Option<&str> {
+        split_checkpoint(&self.filename).1
+    }
+
+    /// The main save this entry belongs to; itself for a world's main save.
+    pub fn world_file(&self) -> String {
+        split_checkpoint(&self.filename).0
+    }
+}
+
+pub(crate) fn split_checkpoint(filename: &str) -> (String, Option<&str>) {
+    let stem = filename.strip_suffix(".json").unwrap_or(filename);
+    match stem.split_once(CHECKPOINT_SEPARATOR) {
+        Some((world, name)) => (format!("{}.json", world), Some(name)),
+        None => (filename.to_string(), None),
+    }
+}
+
+/// File a named checkpoint of `world_file` is stored in.
+pub fn checkpoint_filename(world_file: &str, name: &str) -> String {
+    let stem = world_file.strip_suffix(".json").unwrap_or(world_file);
+    format!("{}{}{}.json", stem, CHECKPOINT_SEPARATOR, name)
+}
+
+/// Longest world or checkpoint name kept in a filename.
+const MAX_NAME_LEN: usize = 64;
+
+/// Turn a player-typed world or checkpoint name into something safe to put
+/// in a filename: anything but letters, digits, `-` and `_` becomes `-`, so
+/// path separators, `..` and the checkpoint separator can never get through.
+pub fn sanitize_name(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let raw = raw.strip_suffix(".json").unwrap_or(raw);
+    let name: String = raw.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .take(MAX_NAME_LEN)
+        .collect();
+    let name = name.trim_matches('-').to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Where saves go when no directory is configured: the platform data
+/// directory (`~/.local/share/llm-text-adventure/saves` on Linux), or
+/// `saves/` beside the game if there is none.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_save_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("llm-text-adventure").join("saves"))
+        .unwrap_or_else(|| PathBuf::from("saves"))
+}
+
+/// Where saves went before they moved to the platform data directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub const LEGACY_SAVE_DIR: &str = "saves";
+
+/// How many saves an old save directory holds, unless it is the one in use.
+/// Used to point players at worlds left behind in `./saves`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn legacy_save_count(legacy: &Path, in_use: &Path) -> usize {
+    let same = match (legacy.canonicalize(), in_use.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => legacy == in_use,
+    };
+    if same {
+        return 0;
+    }
+    FileSystemStorage::new(legacy.to_path_buf()).list_saves().map(|saves| saves.len()).unwrap_or(0)
 }
 
 impl SaveManager {
@@ -198,292 +635,472 @@ impl SaveManager {
         #[cfg(not(target_arch = "wasm32"))]
         {
             Self {
-                storage: Box::new(FileSystemStorage::new(PathBuf::from("saves"))),
+                storage: Box::new(FileSystemStorage::new(default_save_dir())),
+                model_name: String::new(),
+                image_dir: Some(default_save_dir().join("images")),
             }
         }
         #[cfg(target_arch = "wasm32")]
         {
             Self {
                 storage: Box::new(BrowserStorage::new()),
+                model_name: String::new(),
+                image_dir: None,
             }
         }
     }
 
+    /// Open the chosen storage backend in `save_dir`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(backend: StorageBackend, save_dir: PathBuf) -> Result<Self> {
+        let image_dir = save_dir.join("images");
+        let storage: Box<dyn Storage> = match backend {
+            StorageBackend::Files => Box::new(FileSystemStorage::new(save_dir)),
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite => Box::new(crate::sqlite::SqliteStorage::open(save_dir.join("worlds.db"))?),
+            #[cfg(not(feature = "sqlite"))]
+            StorageBackend::Sqlite => anyhow::bail!("This build was compiled without the sqlite feature"),
+        };
+        Ok(Self { image_dir: Some(image_dir), ..Self::with_storage(storage) })
+    }
+
+    pub fn with_storage(storage: Box<dyn Storage>) -> Self {
+        Self { storage, model_name: String::new(), image_dir: None }
+    }
+
+    /// Worlds newest first, each followed by its checkpoints (also newest first).
     pub fn list_saves(&self) -> Result<Vec<SaveInfo>> {
         let mut saves = self.storage.list_saves()?;
-        // Sort by newest first
         saves.sort_by(|a, b| b.modified.cmp(&a.modified));
-        Ok(saves)
+        for save in &mut saves {
+            save.header = self.read_header(&save.filename);
+        }
+        let (worlds, mut checkpoints): (Vec<_>, Vec<_>) = saves.into_iter().partition(|s| s.checkpoint().is_none());
+
+        let mut grouped = Vec::new();
+        for world in worlds {
+            let file = world.filename.clone();
+            grouped.push(world);
+            let (own, rest): (Vec<_>, Vec<_>) = checkpoints.into_iter().partition(|c| c.world_file() == file);
+            grouped.extend(own);
+            checkpoints = rest;
+        }
+        // Checkpoints whose world was deleted stay loadable.
+        grouped.extend(checkpoints);
+        Ok(grouped)
+    }
+
+    /// Checkpoints of one world, newest first.
+    pub fn list_checkpoints(&self, world_file: &str) -> Result<Vec<SaveInfo>> {
+        Ok(self.list_saves()?.into_iter()
+            .filter(|s| s.checkpoint().is_some() && s.world_file() == world_file)
+            .collect())
+    }
+
+    /// Snapshot a world under a name, replacing any checkpoint of the same name.
+    pub fn save_checkpoint(&self, world_file: &str, name: &str, world: &WorldState) -> Result<String> {
+        let filename = checkpoint_filename(world_file, name);
+        self.save_game(&filename, world)?;
+        Ok(filename)
     }
 
-    pub fn load_save(&self, filename: &str) -> Result<WorldState> {
+    /// Load a save, upgrading it from older formats. Also returns a line per
+    /// migration applied so the caller can tell the player.
+    pub fn load_save(&self, filename: &str) -> Result<(WorldState, Vec<String>)> {
         let content = self.storage.load_game(filename)?;
-        
-        // Try to load as new format first
-        match serde_json::from_str::<WorldState>(&content) {
-            Ok(world) => Ok(world),
-            Err(_) => {
-                // Try to migrate from old format
-                self.migrate_old_save(&content)
-                    .context("Failed to migrate old save format")
+        Self::parse_save(&content)
+    }
+
+    /// Read a save's header without deserializing the world.
+    pub fn read_header(&self, filename: &str) -> Option<SaveHeader> {
+        let prefix = self.storage.load_prefix(filename, HEADER_PREFIX_BYTES).ok()?;
+        let reader: Box<dyn Read + '_> = if prefix.starts_with(&GZIP_MAGIC) {
+            Box::new(GzDecoder::new(prefix.as_slice()))
+        } else {
+            Box::new(prefix.as_slice())
+        };
+        let mut header = None;
+        // The visitor always errors once it has the header, and a prefix cut
+        // short mid-world errors too; either way `header` holds what was found.
+        let _ = serde_json::Deserializer::from_reader(reader)
+            .deserialize_map(HeaderVisitor(&mut header));
+        header
+    }
+
+    /// A save's JSON as stored, before any migration.
+    pub(crate) fn load_json(&self, filename: &str) -> Result<String> {
+        decode(&self.storage.load_game(filename)?)
+    }
+
+    pub(crate) fn parse_save(content: &[u8]) -> Result<(WorldState, Vec<String>)> {
+        let save: Value = serde_json::from_str(&decode(content)?)
+            .context("Save file is not valid JSON")?;
+        let (save, applied) = migration::migrate(save)?;
+        let world = serde_json::from_value(save)
+            .context("Save does not match the current world format")?;
+        Ok((world, applied))
+    }
+
+    pub fn list_backups(&self, filename: &str) -> Result<Vec<String>> {
+        self.storage.list_backups(filename)
+    }
+
+    /// Replace a save with its newest backup that loads, returning the world
+    /// as `load_save` would along with the name of the backup used.
+    pub fn restore_backup(&self, filename: &str) -> Result<(WorldState, Vec<String>, String)> {
+        for backup in self.storage.list_backups(filename)? {
+            let Ok(content) = self.storage.load_game(&backup) else { continue };
+            if let Ok((world, applied)) = Self::parse_save(&content) {
+                self.storage.restore_backup(filename, &backup)?;
+                return Ok((world, applied, backup));
             }
         }
+        anyhow::bail!("No usable backup of {}", filename)
     }
 
-    fn migrate_old_save(&self, content: &str) -> Result<WorldState> {
-        let old_data: Value = serde_json::from_str(content)
-            .context("Failed to parse old save format")?;
-        
-        // Extract old data
-        let old_current_id = old_data.get("current_location_id")
-            .and_then(|v| v.as_str())
-            .unwrap_or("start");
-        
-        let empty_map = serde_json::Map::new();
-        let old_locations = old_data.get("locations")
-            .and_then(|v| v.as_object())
-            .unwrap_or(&empty_map);
-        
-        // Convert to new format
-        let mut new_locations = HashMap::new();
-        let mut current_pos = (0, 0);
-        
-        for (loc_id, loc_data) in old_locations {
-            if let Some(loc_obj) = loc_data.as_object() {
-                let name = loc_obj.get("name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
-                
-                let description = loc_obj.get("description")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                
-                let x = loc_obj.get("x")
-                    .and_then(|v| v.as_i64())
-                    .unwrap_or(0) as i32;
-                
-                let y = loc_obj.get("y")
-                    .and_then(|v| v.as_i64())
-                    .unwrap_or(0) as i32;
-                
-                let pos = (x, y);
-                
-                // Convert exits from string IDs to coordinates
-                let mut new_exits = HashMap::new();
-                if let Some(exits) = loc_obj.get("exits").and_then(|v| v.as_object()) {
-                    for (dir, target) in exits {
-                        if let Some(target_id) = target.as_str() {
-                            // Try to find target location's coordinates
-                            if let Some(target_loc) = old_locations.get(target_id) {
-                                if let Some(target_obj) = target_loc.as_object() {
-                                    let target_x = target_obj.get("x")
-                                        .and_then(|v| v.as_i64())
-                                        .unwrap_or(0) as i32;
-                                    let target_y = target_obj.get("y")
-                                        .and_then(|v| v.as_i64())
-                                        .unwrap_or(0) as i32;
-                                    new_exits.insert(dir.clone(), Some((target_x, target_y)));
-                                }
-                            } else {
-                                new_exits.insert(dir.clone(), None); // Blocked exit
-                            }
-                        } else {
-                            new_exits.insert(dir.clone(), None); // Null/blocked exit
-                        }
-                    }
-                }
-                
-                let items = loc_obj.get("items")
-                    .and_then(|v| v.as_array())
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .collect()
-                    })
-                    .unwrap_or_default();
-                
-                let actors = loc_obj.get("actors")
-                    .and_then(|v| v.as_array())
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .collect()
-                    })
-                    .unwrap_or_default();
-                
-                let cached_image_path = loc_obj.get("cached_image_path")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-                
-                let image_prompt = loc_obj.get("image_prompt")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                
-                let location = Location {
-                    name,
-                    description,
-                    items,
-                    actors,
-                    exits: new_exits,
-                    cached_image_path,
-                    image_prompt,
-                    visited: true, // Assume old locations were visited
-                };
-                
-                new_locations.insert(pos, location);
-                
-                if loc_id == old_current_id {
-                    current_pos = pos;
-                }
+    pub fn save_game(&self, filename: &str, world: &WorldState) -> Result<()> {
+        let save = VersionedSave {
+            format_version: CURRENT_FORMAT_VERSION,
+            header: SaveHeader::new(filename, world, &self.model_name),
+            world,
+        };
+        self.storage.save_world(filename, &save, world.save_format)
+    }
+
+    /// Whether a save with this filename exists. Case is ignored, since
+    /// `Castle.json` and `castle.json` are the same file on some systems.
+    pub fn save_exists(&self, filename: &str) -> bool {
+        self.existing_save(filename).is_some()
+    }
+
+    /// The stored name of the save matching `filename` regardless of case.
+    fn existing_save(&self, filename: &str) -> Option<String> {
+        let wanted = filename.to_lowercase();
+        self.storage.list_saves().ok()?
+            .into_iter()
+            .map(|s| s.filename)
+            .find(|name| name.to_lowercase() == wanted)
+    }
+
+    /// Filename a new world called `name` would be saved under.
+    pub fn world_filename(name: &str) -> Option<String> {
+        sanitize_name(name).map(|name| format!("{}.json", name))
+    }
+
+    /// The first of `name`, `name-2`, `name-3`, ... not already taken.
+    pub fn unique_name(&self, name: &str) -> String {
+        let Some(name) = sanitize_name(name) else {
+            return "world".to_string();
+        };
+        (1..)
+            .map(|n| if n == 1 { name.clone() } else { format!("{}-{}", name, n) })
+            .find(|candidate| !self.save_exists(&format!("{}.json", candidate)))
+            .unwrap_or(name)
+    }
+
+    /// Save a new world, returning its filename. Fails if the name has nothing
+    /// usable in it, or if a world by that name exists and `overwrite` is false.
+    pub fn create_new_save(&self, name: &str, world: &WorldState, overwrite: bool) -> Result<String> {
+        let filename = Self::world_filename(name)
+            .context("World names need at least one letter or digit")?;
+        if let Some(existing) = self.existing_save(&filename) {
+            if !overwrite {
+                anyhow::bail!("A world called '{}' already exists", existing.trim_end_matches(".json"));
             }
+            // Replacing a world takes its checkpoints and backups with it.
+            self.delete_save(&existing)?;
         }
-        
-        // Migrate actors
-        let mut new_actors = HashMap::new();
-        if let Some(actors_data) = old_data.get("actors").and_then(|v| v.as_object()) {
-            for (actor_id, actor_obj) in actors_data {
-                if let Some(obj) = actor_obj.as_object() {
-                    let name = obj.get("name")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    let description = obj.get("description")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    let inventory = obj.get("inventory")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| {
-                            arr.iter()
-                                .filter_map(|v| v.as_str())
-                                .map(|s| s.to_string())
-                                .collect()
-                        })
-                        .unwrap_or_default();
-                    
-                    let money = obj.get("money")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0) as u32;
-                    
-                    // Find actor's current location
-                    let mut actor_pos = (0, 0);
-                    if let Some(loc_id) = obj.get("current_location_id").and_then(|v| v.as_str()) {
-                        if let Some(loc_data) = old_locations.get(loc_id) {
-                            if let Some(loc_obj) = loc_data.as_object() {
-                                let x = loc_obj.get("x")
-                                    .and_then(|v| v.as_i64())
-                                    .unwrap_or(0) as i32;
-                                let y = loc_obj.get("y")
-                                    .and_then(|v| v.as_i64())
-                                    .unwrap_or(0) as i32;
-                                actor_pos = (x, y);
-                            }
-                        }
-                    }
-                    
-                    let actor = Actor {
-                        id: actor_id.clone(),
-                        name,
-                        description,
-                        current_pos: actor_pos,
-                        inventory,
-                        money,
-                    };
-                    
-                    new_actors.insert(actor_id.clone(), actor);
-                }
+        self.save_game(&filename, world)?;
+        Ok(filename)
+    }
+
+    /// Keep journal entries that no longer fit in a world's save, so its
+    /// chronicle can still tell the whole story.
+    pub fn archive_journal(&self, world_file: &str, entries: &[JournalEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.storage.archive_journal(world_file, entries)
+    }
+
+    /// A world's archived journal entries for turns before `before`, oldest
+    /// first. A turn played again after going back to a checkpoint keeps its
+    /// latest version.
+    pub fn journal_archive(&self, world_file: &str, before: u32) -> Result<Vec<JournalEntry>> {
+        let mut by_turn = BTreeMap::new();
+        for entry in self.storage.load_journal_archive(world_file)? {
+            if entry.turn < before {
+                by_turn.insert(entry.turn, entry);
             }
         }
-        
-        // Migrate items
-        let mut new_items = HashMap::new();
-        if let Some(items_data) = old_data.get("items").and_then(|v| v.as_object()) {
-            for (item_id, item_obj) in items_data {
-                if let Some(obj) = item_obj.as_object() {
-                    let name = obj.get("name")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    let description = obj.get("description")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    let item = crate::model::Item {
-                        id: item_id.clone(),
-                        name,
-                        description,
-                        item_type: crate::model::ItemType::Material,
-                        state: crate::model::ItemState::Normal,
-                        properties: crate::model::ItemProperties::default(),
-                    };
-                    
-                    new_items.insert(item_id.clone(), item);
+        Ok(by_turn.into_values().collect())
+    }
+
+    /// Where images unpacked for a world go, if this manager keeps images.
+    pub fn world_image_dir(&self, world_name: &str) -> Option<PathBuf> {
+        self.image_dir.as_ref().map(|dir| dir.join(world_name))
+    }
+
+    /// Delete a save. Deleting a world's main save takes its checkpoints and
+    /// unpacked images with it.
+    pub fn delete_save(&self, filename: &str) -> Result<()> {
+        if split_checkpoint(filename).1.is_none() {
+            for checkpoint in self.list_checkpoints(filename)? {
+                self.storage.delete_save(&checkpoint.filename)?;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(dir) = self.world_image_dir(filename.strip_suffix(".json").unwrap_or(filename)) {
+                if dir.is_dir() {
+                    fs::remove_dir_all(&dir)
+                        .context(format!("Failed to delete images: {:?}", dir))?;
                 }
             }
         }
-        
-        // Migrate player
-        let player_inventory = old_data.get("player")
-            .and_then(|v| v.get("inventory"))
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .collect()
-            })
-            .unwrap_or_default();
-        
-        let player_money = old_data.get("player")
-            .and_then(|v| v.get("money"))
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0) as u32;
-        
-        let player = crate::model::Player {
-            inventory: player_inventory,
-            money: player_money,
-        };
-        
-        Ok(WorldState {
-            current_pos,
-            locations: new_locations,
-            actors: new_actors,
-            items: new_items,
-            player,
-            combat: CombatState::default(),
-            max_items: 20,
-            max_combatants: 4,
-        })
+        self.storage.delete_save(filename)
     }
+}
 
-    pub fn save_game(&self, filename: &str, world: &WorldState) -> Result<()> {
-        let content = serde_json::to_string_pretty(world)
-            .context("Failed to serialize world state")?;
-        self.storage.save_game(filename, &content)?;
-        Ok(())
+/// A file-backed manager in a fresh directory under the system temp dir,
+/// for tests. The directory is only created by the first save; the caller
+/// removes it.
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub(crate) fn temp_manager() -> (SaveManager, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("llm-adventure-{}", uuid::Uuid::new_v4()));
+    let manager = SaveManager::open(StorageBackend::Files, dir.clone()).expect("file storage always opens");
+    (manager, dir)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saves_rotate_backups() {
+        let (manager, dir) = temp_manager();
+        let mut world = WorldState::new();
+        for money in 0..5 {
+            world.player.money = money;
+            manager.save_game("w.json", &world).unwrap();
+        }
+        assert_eq!(manager.list_backups("w.json").unwrap(), vec!["w.json.bak1", "w.json.bak2", "w.json.bak3"]);
+        assert!(!dir.join("w.json.tmp").exists());
+        assert_eq!(manager.load_save("w.json").unwrap().0.player.money, 4);
+        // Backups never show up as saves of their own.
+        assert_eq!(manager.list_saves().unwrap().len(), 1);
+
+        manager.delete_save("w.json").unwrap();
+        assert!(manager.list_backups("w.json").unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    pub fn create_new_save(&self, name: &str, world: &WorldState) -> Result<String> {
-        // Sanitize name or just use it. 
-        // If name doesn't end in .json, add it.
-        let mut filename = name.to_string();
-        if !filename.ends_with(".json") {
-            filename.push_str(".json");
+    #[test]
+    fn test_gzip_saves_are_detected_on_load() {
+        let (manager, dir) = temp_manager();
+        let mut world = WorldState::new();
+        for x in 0..50 {
+            world.locations.insert((x, 0, 0), crate::worldgen::fallback_location((x, 0, 0)));
         }
-        self.save_game(&filename, world)?;
-        Ok(filename)
+        manager.save_game("plain.json", &world).unwrap();
+        world.save_format = SaveFormat::Gzip;
+        manager.save_game("packed.json", &world).unwrap();
+
+        let packed = fs::read(dir.join("packed.json.gz")).unwrap();
+        assert!(packed.starts_with(&GZIP_MAGIC));
+        assert!(packed.len() < fs::metadata(dir.join("plain.json")).unwrap().len() as usize / 4);
+        assert!(!dir.join("packed.json").exists());
+
+        // The game keeps using the `.json` name; the suffix is the storage's business.
+        let mut files: Vec<String> = manager.list_saves().unwrap().into_iter().map(|s| s.filename).collect();
+        files.sort();
+        assert_eq!(files, vec!["packed.json", "plain.json"]);
+        let (loaded, _) = manager.load_save("packed.json").unwrap();
+        assert_eq!(loaded.locations.len(), 50);
+        assert_eq!(loaded.save_format, SaveFormat::Gzip);
+        assert!(manager.read_header("packed.json").is_some());
+        assert_eq!("GZ".parse(), Ok(SaveFormat::Gzip));
+
+        // Switching back renames the file, and the gzipped backup still restores.
+        world.save_format = SaveFormat::Json;
+        manager.save_game("packed.json", &world).unwrap();
+        assert!(dir.join("packed.json").exists() && !dir.join("packed.json.gz").exists());
+        let (restored, _, _) = manager.restore_backup("packed.json").unwrap();
+        assert_eq!(restored.save_format, SaveFormat::Gzip);
+        assert!(dir.join("packed.json.gz").exists() && !dir.join("packed.json").exists());
+        manager.delete_save("packed.json").unwrap();
+        assert_eq!(manager.list_saves().unwrap().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    pub fn delete_save(&self, filename: &str) -> Result<()> {
-        self.storage.delete_save(filename)
+    fn entry(turn: u32, narrative: &str) -> JournalEntry {
+        JournalEntry {
+            turn,
+            time: String::new(),
+            pos: (0, 0, 0),
+            location: String::new(),
+            input: String::new(),
+            narrative: narrative.to_string(),
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_journal_archive_keeps_the_latest_of_each_turn() {
+        let (manager, dir) = temp_manager();
+        manager.archive_journal("w.json", &[entry(1, "First."), entry(2, "Second.")]).unwrap();
+        // A rewound world archives its turns again; the newer telling wins.
+        manager.archive_journal("w.json", &[entry(2, "Second, again."), entry(3, "Third.")]).unwrap();
+        manager.archive_journal("w.json", &[]).unwrap();
+
+        let archived = manager.journal_archive("w.json", 3).unwrap();
+        let story: Vec<(u32, &str)> = archived.iter().map(|e| (e.turn, e.narrative.as_str())).collect();
+        assert_eq!(story, vec![(1, "First."), (2, "Second, again.")]);
+        assert!(manager.list_saves().unwrap().is_empty());
+
+        manager.delete_save("w.json").unwrap();
+        assert!(manager.journal_archive("w.json", 10).unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_header_is_listed_with_the_save() {
+        let (mut manager, dir) = temp_manager();
+        manager.model_name = "test-model".to_string();
+        let mut world = WorldState::new();
+        let mut loc = crate::worldgen::fallback_location((0, 0, 0));
+        loc.name = "Harbour".to_string();
+        world.locations.insert((0, 0, 0), loc);
+        world.turns = 12;
+        world.playtime_secs = 3900;
+        world.player.money = 7;
+        manager.save_checkpoint("port.json", "dock", &world).unwrap();
+
+        let header = manager.list_saves().unwrap().remove(0).header.unwrap();
+        assert_eq!(header.world_name, "port");
+        assert_eq!(header.location, "Harbour");
+        assert_eq!((header.turns, header.money), (12, 7));
+        assert_eq!(header.playtime(), "1h 05m");
+        assert!(header.summary().starts_with("Harbour · 12 turns · 1h 05m played · 7 coins"));
+        assert_eq!(header.model, "test-model");
+        assert!(header.created.is_some());
+
+        // Older saves without a header still list and load.
+        fs::write(dir.join("old.json"), include_str!("../tests/fixtures/saves/v3.json")).unwrap();
+        let old = manager.list_saves().unwrap().into_iter().find(|s| s.filename == "old.json").unwrap();
+        assert!(old.header.is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_header_is_read_from_the_start_of_large_saves() {
+        let (manager, dir) = temp_manager();
+        let mut world = WorldState::new();
+        for x in 0..400 {
+            world.locations.insert((x, 0, 0), crate::worldgen::fallback_location((x, 0, 0)));
+        }
+        world.turns = 3;
+        manager.save_game("big.json", &world).unwrap();
+        assert!(fs::metadata(dir.join("big.json")).unwrap().len() > HEADER_PREFIX_BYTES as u64);
+        assert_eq!(manager.read_header("big.json").unwrap().turns, 3);
+
+        world.save_format = SaveFormat::Gzip;
+        manager.save_game("big.json", &world).unwrap();
+        assert_eq!(manager.read_header("big.json").unwrap().turns, 3);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_overwriting_a_world_ignores_case_and_clears_it() {
+        let (manager, dir) = temp_manager();
+        let mut world = WorldState::new();
+        world.player.money = 5;
+        manager.save_game("Castle.json", &world).unwrap();
+        manager.save_game("Castle.json", &world).unwrap();
+        manager.save_checkpoint("Castle.json", "gate", &world).unwrap();
+        assert!(manager.save_exists("castle.json"));
+        assert_eq!(manager.unique_name("CASTLE"), "CASTLE-2");
+        assert!(manager.create_new_save("castle", &world, false).is_err());
+
+        world.player.money = 0;
+        assert_eq!(manager.create_new_save("castle", &world, true).unwrap(), "castle.json");
+        let files: Vec<String> = manager.list_saves().unwrap().into_iter().map(|s| s.filename).collect();
+        assert_eq!(files, vec!["castle.json"]);
+        assert!(manager.list_backups("castle.json").unwrap().is_empty());
+        assert!(!dir.join("Castle.json.bak1").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_dir_is_created_on_first_save_and_legacy_saves_are_found() {
+        let (manager, dir) = temp_manager();
+        assert!(manager.list_saves().unwrap().is_empty());
+        assert!(!dir.exists());
+        manager.save_game("old.json", &WorldState::new()).unwrap();
+
+        let in_use = dir.join("new");
+        assert_eq!(legacy_save_count(&dir, &in_use), 1);
+        assert_eq!(legacy_save_count(&dir, &dir), 0);
+        assert_eq!(legacy_save_count(&in_use, &dir), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoints_group_under_their_world() {
+        let (manager, dir) = temp_manager();
+        let world = WorldState::new();
+        manager.save_game("a.json", &world).unwrap();
+        manager.save_game("b.json", &world).unwrap();
+        let name = sanitize_name(" Before the Boss! ").unwrap();
+        assert_eq!(name, "Before-the-Boss");
+        assert_eq!(manager.save_checkpoint("a.json", &name, &world).unwrap(), "a@Before-the-Boss.json");
+
+        let files: Vec<String> = manager.list_saves().unwrap().into_iter().map(|s| s.filename).collect();
+        let a = files.iter().position(|f| f == "a.json").unwrap();
+        assert_eq!(files[a + 1], "a@Before-the-Boss.json");
+        assert_eq!(manager.list_checkpoints("a.json").unwrap()[0].checkpoint(), Some("Before-the-Boss"));
+        assert!(manager.list_checkpoints("b.json").unwrap().is_empty());
+
+        manager.delete_save("a.json").unwrap();
+        assert_eq!(manager.list_saves().unwrap().len(), 1);
+        assert!(sanitize_name("!!").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_world_names_stay_inside_the_save_dir() {
+        assert_eq!(sanitize_name("../../etc/passwd").as_deref(), Some("etc-passwd"));
+        assert_eq!(sanitize_name("Castle.json").as_deref(), Some("Castle"));
+        assert_eq!(sanitize_name("a@b").as_deref(), Some("a-b"));
+        assert_eq!(sanitize_name(&"x".repeat(200)).unwrap().len(), MAX_NAME_LEN);
+        assert!(sanitize_name("/..").is_none());
+
+        let (manager, dir) = temp_manager();
+        let world = WorldState::new();
+        assert_eq!(manager.create_new_save("../Castle", &world, false).unwrap(), "Castle.json");
+        assert!(dir.join("Castle.json").exists());
+        assert!(manager.create_new_save("Castle", &world, false).is_err());
+        assert_eq!(manager.unique_name("Castle"), "Castle-2");
+        assert!(manager.create_new_save("Castle", &world, true).is_ok());
+        assert!(manager.create_new_save("??", &world, false).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_restore_skips_to_a_backup_that_loads() {
+        let (manager, dir) = temp_manager();
+        let mut world = WorldState::new();
+        for money in [7, 8] {
+            world.player.money = money;
+            manager.save_game("w.json", &world).unwrap();
+        }
+        manager.save_game("w.json", &world).unwrap();
+        fs::write(dir.join("w.json"), "{ truncated").unwrap();
+        fs::write(dir.join("w.json.bak1"), "").unwrap();
+        assert!(manager.load_save("w.json").is_err());
+
+        let (restored, _, backup) = manager.restore_backup("w.json").unwrap();
+        assert_eq!(backup, "w.json.bak2");
+        assert_eq!(restored.player.money, 7);
+        assert_eq!(manager.load_save("w.json").unwrap().0.player.money, 7);
+        fs::remove_dir_all(dir).unwrap();
     }
 }
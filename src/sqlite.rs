@@ -0,0 +1,453 @@
+//! SQLite save storage: one database holding every world.
+//!
+//! A world's locations get a row each and everything else is kept as one
+//! JSON document, so a save only rewrites the locations that changed.
+//! Checkpoints are stored whole in `snapshots`, and every save records its
+//! turn in `turns`, so play can be queried across worlds:
+//!
+//! ```sql
+//! SELECT world, MAX(turn), MAX(playtime_secs) FROM turns GROUP BY world;
+//! ```
+//!
+//! A save that takes a world back to an earlier turn (loading a checkpoint)
+//! first copies the world into `backups` and drops the turns it rewound past.
+//! Turn history outlives the world it belongs to. Journal entries too old
+//! to stay in the save are archived in `journal` for the chronicle.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::model::{JournalEntry, SaveFormat};
+use crate::save::{self, SaveInfo, Storage, VersionedSave, MAX_BACKUPS};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS worlds (
+    filename TEXT PRIMARY KEY,
+    modified TEXT NOT NULL,
+    state TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS locations (
+    world TEXT NOT NULL,
+    pos TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (world, pos)
+);
+CREATE TABLE IF NOT EXISTS snapshots (
+    filename TEXT PRIMARY KEY,
+    world TEXT NOT NULL,
+    modified TEXT NOT NULL,
+    content TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS backups (
+    filename TEXT PRIMARY KEY,
+    world TEXT NOT NULL,
+    modified TEXT NOT NULL,
+    content TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS journal (
+    world TEXT NOT NULL,
+    turn INTEGER NOT NULL,
+    entry TEXT NOT NULL,
+    PRIMARY KEY (world, turn)
+);
+CREATE TABLE IF NOT EXISTS turns (
+    world TEXT NOT NULL,
+    turn INTEGER NOT NULL,
+    recorded TEXT NOT NULL,
+    location TEXT NOT NULL,
+    money INTEGER NOT NULL,
+    playtime_secs INTEGER NOT NULL,
+    PRIMARY KEY (world, turn)
+);
+";
+
+/// One row of a world's turn history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnRecord {
+    pub turn: u32,
+    pub recorded: DateTime<Local>,
+    pub location: String,
+    pub money: u32,
+}
+
+pub struct SqliteStorage {
+    conn: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)
+            .context(format!("Failed to open save database: {:?}", path))?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA).context("Failed to create save tables")?;
+        Ok(Self { conn })
+    }
+
+    /// Turns recorded for a world, oldest first.
+    pub fn turn_history(&self, world_file: &str) -> Result<Vec<TurnRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT turn, recorded, location, money FROM turns WHERE world = ?1 ORDER BY turn")?;
+        let rows = stmt.query_map(params![world_file], |row| {
+            Ok(TurnRecord {
+                turn: row.get(0)?,
+                recorded: parse_time(&row.get::<_, String>(1)?),
+                location: row.get(2)?,
+                money: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn store_world(&self, filename: &str, mut save: Value) -> Result<()> {
+        let obj = save.as_object_mut().context("Save is not a JSON object")?;
+        let locations = match obj.remove("locations") {
+            Some(Value::Object(locations)) => locations,
+            _ => Map::new(),
+        };
+        let header = obj.get("header").cloned().unwrap_or(Value::Null);
+        let now = Local::now().to_rfc3339();
+        let turn = header.get("turns").and_then(|v| v.as_u64());
+
+        let last_turn: Option<u64> = self.conn.query_row(
+            "SELECT MAX(turn) FROM turns WHERE world = ?1", params![filename], |row| row.get(0))?;
+        if matches!((turn, last_turn), (Some(turn), Some(last)) if turn < last) {
+            self.back_up(filename)?;
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO worlds (filename, modified, state) VALUES (?1, ?2, ?3)
+             ON CONFLICT(filename) DO UPDATE SET modified = excluded.modified, state = excluded.state",
+            params![filename, now, serde_json::to_string(&save)?],
+        )?;
+
+        // Only rows whose JSON differs are written.
+        {
+            let mut upsert = tx.prepare(
+                "INSERT INTO locations (world, pos, data) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(world, pos) DO UPDATE SET data = excluded.data WHERE data != excluded.data")?;
+            for (pos, location) in &locations {
+                upsert.execute(params![filename, pos, serde_json::to_string(location)?])?;
+            }
+
+            let mut stored = tx.prepare("SELECT pos FROM locations WHERE world = ?1")?;
+            let gone: Vec<String> = stored.query_map(params![filename], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?
+                .into_iter()
+                .filter(|pos| !locations.contains_key(pos))
+                .collect();
+            for pos in gone {
+                tx.execute("DELETE FROM locations WHERE world = ?1 AND pos = ?2", params![filename, pos])?;
+            }
+        }
+
+        if let Some(turn) = turn {
+            tx.execute("DELETE FROM turns WHERE world = ?1 AND turn > ?2", params![filename, turn])?;
+            tx.execute(
+                "INSERT OR REPLACE INTO turns (world, turn, recorded, location, money, playtime_secs)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    filename,
+                    turn,
+                    now,
+                    header.get("location").and_then(|v| v.as_str()).unwrap_or_default(),
+                    header.get("money").and_then(|v| v.as_u64()).unwrap_or(0),
+                    header.get("playtime_secs").and_then(|v| v.as_u64()).unwrap_or(0),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn store_snapshot(&self, filename: &str, json: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO snapshots (filename, world, modified, content) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(filename) DO UPDATE SET modified = excluded.modified, content = excluded.content",
+            params![filename, save::split_checkpoint(filename).0, Local::now().to_rfc3339(), json],
+        )?;
+        Ok(())
+    }
+
+    fn backup_name(filename: &str, n: usize) -> String {
+        format!("{}.bak{}", filename, n)
+    }
+
+    /// Copy a world into `backups` as `.bak1`, shifting older copies along
+    /// and dropping the oldest past `MAX_BACKUPS`.
+    fn back_up(&self, filename: &str) -> Result<()> {
+        let Some(save) = self.load_world(filename)? else {
+            return Ok(());
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM backups WHERE filename = ?1", params![Self::backup_name(filename, MAX_BACKUPS)])?;
+        for n in (1..MAX_BACKUPS).rev() {
+            tx.execute("UPDATE backups SET filename = ?1 WHERE filename = ?2",
+                params![Self::backup_name(filename, n + 1), Self::backup_name(filename, n)])?;
+        }
+        tx.execute(
+            "INSERT INTO backups (filename, world, modified, content) VALUES (?1, ?2, ?3, ?4)",
+            params![Self::backup_name(filename, 1), filename, Local::now().to_rfc3339(), serde_json::to_string(&save)?],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_world(&self, filename: &str) -> Result<Option<Value>> {
+        let Some(state) = self.conn.query_row(
+            "SELECT state FROM worlds WHERE filename = ?1", params![filename], |row| row.get::<_, String>(0),
+        ).optional()? else {
+            return Ok(None);
+        };
+        let mut save: Value = serde_json::from_str(&state)?;
+
+        let mut stmt = self.conn.prepare("SELECT pos, data FROM locations WHERE world = ?1")?;
+        let mut locations = Map::new();
+        for row in stmt.query_map(params![filename], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+            let (pos, data) = row?;
+            locations.insert(pos, serde_json::from_str(&data)?);
+        }
+        if let Some(obj) = save.as_object_mut() {
+            obj.insert("locations".to_string(), Value::Object(locations));
+        }
+        Ok(Some(save))
+    }
+}
+
+fn parse_time(text: &str) -> DateTime<Local> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|t| t.with_timezone(&Local))
+        .unwrap_or_else(|_| Local::now())
+}
+
+impl Storage for SqliteStorage {
+    fn list_saves(&self) -> Result<Vec<SaveInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT filename, modified FROM worlds UNION ALL SELECT filename, modified FROM snapshots")?;
+        let saves = stmt.query_map([], |row| {
+            let filename: String = row.get(0)?;
+            Ok(SaveInfo {
+                path: PathBuf::from(&filename),
+                modified: parse_time(&row.get::<_, String>(1)?),
+                filename,
+                header: None,
+            })
+        })?;
+        Ok(saves.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn save_game(&self, filename: &str, content: &[u8]) -> Result<()> {
+        let json = save::decode(content)?;
+        if save::split_checkpoint(filename).1.is_some() {
+            return self.store_snapshot(filename, &json);
+        }
+        self.store_world(filename, serde_json::from_str(&json).context("Save is not valid JSON")?)
+    }
+
+    fn save_world(&self, filename: &str, save: &VersionedSave, _format: SaveFormat) -> Result<()> {
+        // Rows are plain JSON whatever the format; skipping the encode and
+        // re-parse of `save_game` is most of the cost of a save.
+        if save::split_checkpoint(filename).1.is_some() {
+            return self.store_snapshot(filename, &serde_json::to_string(save)?);
+        }
+        self.store_world(filename, serde_json::to_value(save)?)
+    }
+
+    fn load_game(&self, filename: &str) -> Result<Vec<u8>> {
+        if let Some(save) = self.load_world(filename)? {
+            return Ok(serde_json::to_vec(&save)?);
+        }
+        self.conn.query_row(
+            "SELECT content FROM snapshots WHERE filename = ?1
+             UNION ALL SELECT content FROM backups WHERE filename = ?1",
+            params![filename], |row| row.get::<_, String>(0),
+        ).optional()?
+            .map(String::into_bytes)
+            .context(format!("Save not found: {}", filename))
+    }
+
+    fn load_prefix(&self, filename: &str, len: usize) -> Result<Vec<u8>> {
+        // Worlds keep their header inside the state document, so hand back
+        // just that rather than reassembling every location.
+        let header = self.conn.query_row(
+            "SELECT json_extract(state, '$.header') FROM worlds WHERE filename = ?1",
+            params![filename], |row| row.get::<_, Option<String>>(0),
+        ).optional()?;
+        if let Some(header) = header {
+            return Ok(format!("{{\"header\":{}}}", header.as_deref().unwrap_or("null")).into_bytes());
+        }
+        self.conn.query_row(
+            "SELECT substr(content, 1, ?2) FROM snapshots WHERE filename = ?1",
+            params![filename, len as i64], |row| row.get::<_, String>(0),
+        ).optional()?
+            .map(String::into_bytes)
+            .context(format!("Save not found: {}", filename))
+    }
+
+    fn delete_save(&self, filename: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM snapshots WHERE filename = ?1", params![filename])?;
+        tx.execute("DELETE FROM worlds WHERE filename = ?1", params![filename])?;
+        tx.execute("DELETE FROM locations WHERE world = ?1", params![filename])?;
+        tx.execute("DELETE FROM backups WHERE world = ?1", params![filename])?;
+        tx.execute("DELETE FROM journal WHERE world = ?1", params![filename])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn archive_journal(&self, filename: &str, entries: &[JournalEntry]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            // A turn played again after a rewind replaces the old one.
+            let mut insert = tx.prepare("INSERT OR REPLACE INTO journal (world, turn, entry) VALUES (?1, ?2, ?3)")?;
+            for entry in entries {
+                insert.execute(params![filename, entry.turn, serde_json::to_string(entry)?])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_journal_archive(&self, filename: &str) -> Result<Vec<JournalEntry>> {
+        let mut stmt = self.conn.prepare("SELECT entry FROM journal WHERE world = ?1 ORDER BY turn")?;
+        let entries = stmt.query_map(params![filename], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        entries.iter()
+            .map(|entry| serde_json::from_str(entry).context("Archived journal entry is not valid"))
+            .collect()
+    }
+
+    fn list_backups(&self, filename: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT filename FROM backups WHERE world = ?1 ORDER BY filename")?;
+        let backups = stmt.query_map(params![filename], |row| row.get(0))?;
+        Ok(backups.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn restore_backup(&self, filename: &str, backup: &str) -> Result<()> {
+        let content = self.load_game(backup)?;
+        self.save_game(filename, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WorldState;
+    use crate::save::SaveManager;
+    use crate::worldgen::fallback_location;
+
+    fn world_with_tiles(count: i32) -> WorldState {
+        let mut world = WorldState::new();
+        for x in 0..count {
+            world.locations.insert((x, 0, 0), fallback_location((x, 0, 0)));
+        }
+        world
+    }
+
+    /// A world as `save_game` is handed it, with just enough header for `turns`.
+    fn save_of(world: &WorldState) -> Value {
+        let mut save = serde_json::to_value(world).unwrap();
+        save["header"] = serde_json::json!({ "turns": world.turns, "money": world.player.money });
+        save
+    }
+
+    #[test]
+    fn test_round_trip_and_checkpoints() {
+        let manager = SaveManager::with_storage(Box::new(SqliteStorage::open_in_memory().unwrap()));
+        let mut world = world_with_tiles(3);
+        world.player.money = 11;
+        manager.save_game("w.json", &world).unwrap();
+        manager.save_checkpoint("w.json", "start", &world).unwrap();
+
+        let (loaded, _) = manager.load_save("w.json").unwrap();
+        assert_eq!(loaded.locations.len(), 3);
+        assert_eq!(loaded.player.money, 11);
+        let files: Vec<String> = manager.list_saves().unwrap().into_iter().map(|s| s.filename).collect();
+        assert_eq!(files, vec!["w.json", "w@start.json"]);
+        assert!(manager.read_header("w.json").is_some());
+
+        world.turns = 2;
+        world.record_turn("look", "Dust.", Vec::new());
+        manager.archive_journal("w.json", &world.journal).unwrap();
+        manager.archive_journal("w.json", &world.journal).unwrap();
+        let archived = manager.journal_archive("w.json", 5).unwrap();
+        assert_eq!(archived.iter().map(|e| e.turn).collect::<Vec<_>>(), vec![2]);
+
+        manager.delete_save("w.json").unwrap();
+        assert!(manager.list_saves().unwrap().is_empty());
+        assert!(manager.load_save("w.json").is_err());
+        assert!(manager.journal_archive("w.json", 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_saves_only_write_changed_locations() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let mut world = world_with_tiles(100);
+        let encoded = |world: &WorldState| serde_json::to_vec(&save_of(world)).unwrap();
+
+        storage.save_game("w.json", &encoded(&world)).unwrap();
+        let before = storage.conn.total_changes();
+        world.locations.get_mut(&(5, 0, 0)).unwrap().name = "Renamed".to_string();
+        world.locations.remove(&(6, 0, 0));
+        world.turns = 1;
+        storage.save_game("w.json", &encoded(&world)).unwrap();
+        // The world row, one changed location, one deleted and one turn.
+        assert_eq!(storage.conn.total_changes() - before, 4);
+
+        let history = storage.turn_history("w.json").unwrap();
+        assert_eq!(history.iter().map(|t| t.turn).collect::<Vec<_>>(), vec![0, 1]);
+        let loaded: WorldState = serde_json::from_slice(&storage.load_game("w.json").unwrap()).unwrap();
+        assert_eq!(loaded.locations.len(), 99);
+        assert_eq!(loaded.locations[&(5, 0, 0)].name, "Renamed");
+    }
+
+    #[test]
+    fn test_rewinding_backs_up_and_keeps_history_consistent() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let mut world = world_with_tiles(2);
+        let mut early = world.clone();
+        for turn in 0..=3 {
+            world.turns = turn;
+            world.player.money = turn;
+            storage.save_game("w.json", &serde_json::to_vec(&save_of(&world)).unwrap()).unwrap();
+            if turn == 1 {
+                early = world.clone();
+            }
+        }
+        assert!(storage.list_backups("w.json").unwrap().is_empty());
+
+        // Back at turn 1 from a checkpoint, then playing on: the abandoned
+        // turns are replaced, not kept.
+        storage.save_game("w.json", &serde_json::to_vec(&save_of(&early)).unwrap()).unwrap();
+        early.turns = 2;
+        early.player.money = 20;
+        storage.save_game("w.json", &serde_json::to_vec(&save_of(&early)).unwrap()).unwrap();
+        let history = storage.turn_history("w.json").unwrap();
+        assert_eq!(history.iter().map(|t| (t.turn, t.money)).collect::<Vec<_>>(), vec![(0, 0), (1, 1), (2, 20)]);
+
+        // The world as it was before the rewind is kept and can be put back.
+        assert_eq!(storage.list_backups("w.json").unwrap(), vec!["w.json.bak1"]);
+        storage.restore_backup("w.json", "w.json.bak1").unwrap();
+        let restored: WorldState = serde_json::from_slice(&storage.load_game("w.json").unwrap()).unwrap();
+        assert_eq!(restored.player.money, 3);
+
+        // Deleting the world leaves its history for queries.
+        storage.delete_save("w.json").unwrap();
+        assert!(storage.list_backups("w.json").unwrap().is_empty());
+        assert_eq!(storage.turn_history("w.json").unwrap().len(), 4);
+    }
+}
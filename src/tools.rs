@@ -32,18 +32,52 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
     vec![
         ToolDefinition {
             name: "move_to",
-            description: "Move player in direction (north/south/east/west). Auto-generates new locations if needed.",
+            description: "Move player in direction (north/south/east/west, the diagonals northeast/northwest/southeast/southwest, or up/down/enter/exit to change level through stairs, ladders or doorways placed with open_exit). Auto-generates new locations if needed.",
             parameters: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "direction": {
                         "type": "string",
-                        "enum": ["north", "south", "east", "west"]
+                        "enum": ["north", "south", "east", "west", "northeast", "northwest", "southeast", "southwest", "up", "down", "enter", "exit"]
                     }
                 },
                 "required": ["direction"]
             }),
         },
+        ToolDefinition {
+            name: "open_exit",
+            description: "Open the passage from the current location in a direction: unlocks a door (the player must carry its key), reveals a hidden passage or clears a blocked way. Set one_way for drops and slides with no way back.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "direction": {
+                        "type": "string",
+                        "enum": ["north", "south", "east", "west", "northeast", "northwest", "southeast", "southwest", "up", "down", "enter", "exit"]
+                    },
+                    "one_way": {"type": "boolean", "description": "Only passable from this side"}
+                },
+                "required": ["direction"]
+            }),
+        },
+        ToolDefinition {
+            name: "block_exit",
+            description: "Close off a direction from the current location so it stays closed: a wall or collapsed tunnel (blocked), a locked door (locked, with key_id) or a secret door (hidden) that open_exit can reveal later.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "direction": {
+                        "type": "string",
+                        "enum": ["north", "south", "east", "west", "northeast", "northwest", "southeast", "southwest", "up", "down", "enter", "exit"]
+                    },
+                    "kind": {
+                        "type": "string",
+                        "enum": ["blocked", "locked", "hidden"]
+                    },
+                    "key_id": {"type": "string", "description": "Item ID of the key that opens a locked door"}
+                },
+                "required": ["direction", "kind"]
+            }),
+        },
         ToolDefinition {
             name: "update_location_description",
             description: "Update the current location's description with new text",
@@ -337,6 +371,186 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
                 "required": ["object_id"]
             }),
         },
+        ToolDefinition {
+            name: "create_quest",
+            description: "Give the player a quest. Objectives are ticked off automatically when the player holds the item, reaches the location or defeats the actor; custom objectives are ticked off with update_quest. The quest completes and pays out its rewards when every objective is done.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "string", "description": "Unique quest ID in snake_case"},
+                    "title": {"type": "string"},
+                    "description": {"type": "string"},
+                    "giver_id": {"type": "string", "description": "Actor ID of whoever gives the quest"},
+                    "objectives": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "type": {"type": "string", "enum": ["fetch_item", "reach_location", "defeat_actor", "custom"]},
+                                "description": {"type": "string"},
+                                "item_id": {"type": "string", "description": "For fetch_item: an existing item ID"},
+                                "actor_id": {"type": "string", "description": "For defeat_actor: an existing actor ID"},
+                                "x": {"type": "integer"},
+                                "y": {"type": "integer"},
+                                "z": {"type": "integer", "description": "For reach_location; defaults to 0"}
+                            },
+                            "required": ["type", "description"]
+                        }
+                    },
+                    "reward_money": {"type": "integer"},
+                    "reward_item_ids": {"type": "array", "items": {"type": "string"}, "description": "Existing items handed over on completion"}
+                },
+                "required": ["id", "title", "description", "objectives"]
+            }),
+        },
+        ToolDefinition {
+            name: "update_quest",
+            description: "Change a quest: reword it, add objectives, tick off a custom objective by index, or mark the quest failed.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "quest_id": {"type": "string"},
+                    "description": {"type": "string"},
+                    "add_objectives": {"type": "array", "items": {"type": "object"}, "description": "Objectives in the same format as create_quest"},
+                    "complete_objective": {"type": "integer", "description": "0-based index of an objective to mark done"},
+                    "status": {"type": "string", "enum": ["active", "failed"]}
+                },
+                "required": ["quest_id"]
+            }),
+        },
+        ToolDefinition {
+            name: "complete_quest",
+            description: "Complete an active quest now, e.g. when the player turns it in, and hand over its rewards.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "quest_id": {"type": "string"}
+                },
+                "required": ["quest_id"]
+            }),
+        },
+        ToolDefinition {
+            name: "pass_time",
+            description: "Let time pass while the player rests, sleeps, waits or works. Scheduled events that come due fire along the way.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "hours": {"type": "integer"},
+                    "minutes": {"type": "integer"},
+                    "activity": {"type": "string", "description": "What the player does meanwhile, e.g. 'sleeps by the fire'"}
+                }
+            }),
+        },
+        ToolDefinition {
+            name: "schedule_event",
+            description: "Schedule something to happen later whether or not the player is there, e.g. the market closing at 18:00 every day or a caravan arriving in two days.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "string", "description": "Unique event ID in snake_case"},
+                    "description": {"type": "string", "description": "What happens, e.g. 'The market stalls close for the night.'"},
+                    "in_hours": {"type": "integer", "description": "Fire this many hours from now"},
+                    "at_hour": {"type": "integer", "description": "Fire the next time the clock reaches this hour (0-23)"},
+                    "repeat_every_hours": {"type": "integer", "description": "Fire again at this interval, e.g. 24 for daily"},
+                    "here": {"type": "boolean", "description": "Whether the event happens at the current location"}
+                },
+                "required": ["id", "description"]
+            }),
+        },
+        ToolDefinition {
+            name: "cancel_event",
+            description: "Cancel a scheduled event.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "string"}
+                },
+                "required": ["id"]
+            }),
+        },
+        ToolDefinition {
+            name: "create_faction",
+            description: "Create a faction (guild, town watch, cult, bandit clan) and optionally enrol existing actors in it. The player starts neutral with it.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "string", "description": "Unique faction ID in snake_case"},
+                    "name": {"type": "string"},
+                    "description": {"type": "string"},
+                    "member_ids": {"type": "array", "items": {"type": "string"}, "description": "Existing actor IDs who belong to it"}
+                },
+                "required": ["id", "name", "description"]
+            }),
+        },
+        ToolDefinition {
+            name: "set_actor_faction",
+            description: "Make an actor a member of a faction, or remove them from their faction by omitting faction_id.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "actor_id": {"type": "string"},
+                    "faction_id": {"type": "string"}
+                },
+                "required": ["actor_id"]
+            }),
+        },
+        ToolDefinition {
+            name: "adjust_reputation",
+            description: "Raise or lower the player's reputation with a faction after something they did. Reputation runs from -100 to 100 and sets how members treat the player and what they charge.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "faction_id": {"type": "string"},
+                    "amount": {"type": "integer", "description": "Change, e.g. 10 for a favour or -25 for a betrayal"},
+                    "reason": {"type": "string"}
+                },
+                "required": ["faction_id", "amount"]
+            }),
+        },
+        ToolDefinition {
+            name: "trade_item",
+            description: "Buy an item from an actor here, or sell one to them. The engine sets the price from the item's value and the actor's faction reputation, and moves the money and item.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "actor_id": {"type": "string"},
+                    "item_id": {"type": "string"},
+                    "action": {"type": "string", "enum": ["buy", "sell"]}
+                },
+                "required": ["actor_id", "item_id", "action"]
+            }),
+        },
+        ToolDefinition {
+            name: "learn_spell",
+            description: "Teach the player a spell. Use a built-in spell ID (firebolt, mend, frost_bind, light, knock, reveal) or invent a new spell by also giving name, description, mana_cost and effect; the engine rejects spells that are too strong for their cost.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "spell_id": {"type": "string"},
+                    "name": {"type": "string"},
+                    "description": {"type": "string"},
+                    "mana_cost": {"type": "integer"},
+                    "effect": {
+                        "type": "object",
+                        "description": "One of {\"type\":\"damage\",\"amount\":N}, {\"type\":\"heal\",\"amount\":N}, {\"type\":\"apply_status\",\"status\":\"Poison|Stunned|Burning|Frozen|Bleeding\",\"duration\":N,\"severity\":N}, {\"type\":\"utility\",\"utility\":\"light|unlock|reveal\"}"
+                    }
+                },
+                "required": ["spell_id"]
+            }),
+        },
+        ToolDefinition {
+            name: "cast_spell",
+            description: "Cast a spell the player knows, spending its mana. Damage and status spells need a target_id in combat; heal targets the player unless told otherwise; unlock needs a direction. In combat this uses up the player's turn.",
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "spell_id": {"type": "string"},
+                    "target_id": {"type": "string", "description": "Combatant ID to aim at"},
+                    "direction": {"type": "string", "description": "Door to unlock"}
+                },
+                "required": ["spell_id"]
+            }),
+        },
     ]
 }
 
@@ -348,7 +562,7 @@ mod tests {
     fn test_tool_definitions_exist() {
         let tools = get_tool_definitions();
         assert!(!tools.is_empty());
-        assert_eq!(tools.len(), 22);
+        assert_eq!(tools.len(), 36);
     }
 
     #[test]
@@ -361,6 +575,14 @@ mod tests {
         assert!(params["properties"]["item_type"]["enum"].is_array());
     }
 
+    #[test]
+    fn test_exit_tool_schemas() {
+        let tools = get_tool_definitions();
+        let block_exit = tools.iter().find(|t| t.name == "block_exit").unwrap();
+        assert_eq!(block_exit.parameters["properties"]["kind"]["enum"].as_array().unwrap().len(), 3);
+        assert!(tools.iter().any(|t| t.name == "open_exit"));
+    }
+
     #[test]
     fn test_tool_call_serialization() {
         let tool_call = ToolCall {
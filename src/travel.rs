@@ -0,0 +1,161 @@
+use crate::faction::Disposition;
+use crate::model::WorldState;
+
+/// Chance per point of danger that something interrupts the journey on a tile.
+const ENCOUNTER_CHANCE_PER_DANGER: f64 = 0.03;
+
+/// Work out where `query` points: "x,y" or "x,y,z" coordinates (z defaults to
+/// the current level), or the name of a visited location. Exact names win
+/// over partial matches; ties go to the closest location.
+pub fn resolve_destination(world: &WorldState, query: &str) -> Result<(i32, i32, i32), String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Travel where? Try 'travel to <place>' or 'travel to x,y'.".to_string());
+    }
+
+    let numbers: Vec<Option<i32>> = query.trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect();
+    if let [Some(x), Some(y)] = numbers[..] {
+        return Ok((x, y, world.current_pos.2));
+    }
+    if let [Some(x), Some(y), Some(z)] = numbers[..] {
+        return Ok((x, y, z));
+    }
+
+    let wanted = query.to_lowercase();
+    let (cx, cy, cz) = world.current_pos;
+    let distance = |pos: &(i32, i32, i32)| (pos.0 - cx).abs() + (pos.1 - cy).abs() + (pos.2 - cz).abs();
+    let visited = || world.locations.iter().filter(|(_, l)| l.visited);
+
+    visited().filter(|(_, l)| l.name.to_lowercase() == wanted)
+        .min_by_key(|(pos, _)| distance(pos))
+        .or_else(|| {
+            visited().filter(|(_, l)| l.name.to_lowercase().contains(&wanted))
+                .min_by_key(|(pos, _)| distance(pos))
+        })
+        .map(|(pos, _)| *pos)
+        .ok_or_else(|| format!("You don't know of anywhere called '{}'.", query))
+}
+
+/// Whether arriving at `pos` mid-journey should stop the player, and why.
+///
+/// Anyone hostile to the player stops the journey, while townsfolk and
+/// shopkeepers are passed by; otherwise `roll` (0.0..1.0) is compared against
+/// the tile's danger.
+pub fn encounter_at(world: &WorldState, pos: (i32, i32, i32), roll: f64) -> Option<String> {
+    let loc = world.locations.get(&pos)?;
+
+    let names: Vec<&str> = loc.actors.iter()
+        .filter(|id| !world.defeated_actors.contains(id) && world.disposition_of(id) == Disposition::Hostile)
+        .filter_map(|id| world.actors.get(id).map(|a| a.name.as_str()))
+        .collect();
+    if !names.is_empty() {
+        return Some(format!("You come across {}.", names.join(" and ")));
+    }
+
+    let danger = world.terrain_at(pos).danger;
+    if roll < danger as f64 * ENCOUNTER_CHANCE_PER_DANGER {
+        return Some("Something stirs nearby and you stop, wary.".to_string());
+    }
+    None
+}
+
+/// "45 minutes", "2 hours", "1 hour 30 minutes".
+pub fn format_duration(minutes: u64) -> String {
+    let plural = |n: u64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    match (minutes / 60, minutes % 60) {
+        (0, m) => plural(m, "minute"),
+        (h, 0) => plural(h, "hour"),
+        (h, m) => format!("{} {}", plural(h, "hour"), plural(m, "minute")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::faction::Faction;
+    use crate::model::{Actor, Exit, Location};
+
+    fn visited(name: &str) -> Location {
+        let mut loc = crate::worldgen::fallback_location((0, 0, 0));
+        loc.name = name.to_string();
+        loc.visited = true;
+        loc
+    }
+
+    /// A row of tiles (0,0)..(3,0) linked east-west, with a wall between the
+    /// first two and a way round through (0,1) and (1,1).
+    fn road_world() -> WorldState {
+        let mut world = WorldState::new();
+        for (x, name) in [(0, "Gate"), (1, "Market"), (2, "Bridge"), (3, "Old Mill")] {
+            world.locations.insert((x, 0, 0), visited(name));
+        }
+        world.locations.insert((0, 1, 0), visited("Lane"));
+        world.locations.insert((1, 1, 0), visited("Alley"));
+        for pair in [((1, 0, 0), (2, 0, 0), "east"), ((2, 0, 0), (3, 0, 0), "east"),
+                     ((0, 0, 0), (0, 1, 0), "north"), ((0, 1, 0), (1, 1, 0), "east"), ((1, 1, 0), (1, 0, 0), "south")] {
+            world.link_exits(pair.0, pair.1, pair.2);
+        }
+        world.locations.get_mut(&(0, 0, 0)).unwrap().exits.insert("east".to_string(), Exit::Blocked);
+        world
+    }
+
+    #[test]
+    fn test_find_path_goes_round_walls() {
+        let world = road_world();
+        let path = world.find_path((3, 0, 0)).unwrap();
+        let directions: Vec<&str> = path.iter().map(|(d, _)| d.as_str()).collect();
+        assert_eq!(directions, vec!["north", "east", "south", "east", "east"]);
+        assert_eq!(path.last().unwrap().1, (3, 0, 0));
+    }
+
+    #[test]
+    fn test_find_path_only_uses_visited_tiles() {
+        let mut world = road_world();
+        world.locations.get_mut(&(2, 0, 0)).unwrap().visited = false;
+        assert!(world.find_path((3, 0, 0)).is_none());
+        assert_eq!(world.find_path((0, 0, 0)), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_resolve_destination() {
+        let world = road_world();
+        assert_eq!(resolve_destination(&world, "old mill"), Ok((3, 0, 0)));
+        assert_eq!(resolve_destination(&world, "mill"), Ok((3, 0, 0)));
+        assert_eq!(resolve_destination(&world, "2, 0"), Ok((2, 0, 0)));
+        assert_eq!(resolve_destination(&world, "(1,1,0)"), Ok((1, 1, 0)));
+        assert!(resolve_destination(&world, "Atlantis").is_err());
+    }
+
+    #[test]
+    fn test_encounters() {
+        let mut world = road_world();
+        assert_eq!(encounter_at(&world, (2, 0, 0), 1.0), None);
+        assert!(encounter_at(&world, (2, 0, 0), 0.0).is_some());
+
+        world.factions.insert("trolls".to_string(), Faction {
+            id: "trolls".to_string(),
+            name: "Bridge Trolls".to_string(),
+            description: String::new(),
+        });
+        for (id, name, faction) in [("baker", "a baker", None), ("troll", "a bridge troll", Some("trolls".to_string()))] {
+            world.actors.insert(id.to_string(), Actor {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: String::new(),
+                current_pos: (2, 0, 0),
+                inventory: vec![],
+                money: 0,
+                faction,
+            });
+            world.locations.get_mut(&(2, 0, 0)).unwrap().actors.push(id.to_string());
+        }
+        // Neutral folk are passed by; only the hostile troll stops the journey.
+        assert_eq!(encounter_at(&world, (2, 0, 0), 1.0), None);
+        world.adjust_reputation("trolls", -60);
+        assert_eq!(encounter_at(&world, (2, 0, 0), 1.0), Some("You come across a bridge troll.".to_string()));
+        assert_eq!(format_duration(90), "1 hour 30 minutes");
+    }
+}
@@ -1,5 +1,7 @@
 use crate::game::{Game, GameState};
 use crate::commands::Command;
+use crate::model::{Exit, Location, QuestStatus, describe_level, COMPASS_DIRECTIONS};
+use crate::death::DeathPolicy;
 use anyhow::Result;
 use ratatui::{
     prelude::*,
@@ -21,6 +23,8 @@ pub struct Tui<B: Backend, E: EventSource> {
     event_source: E,
     input_buffer: String,
     spinner_frame: usize,
+    /// Where the Narrative panel was last drawn, to page it by its height.
+    narrative_area: Rect,
 }
 
 impl<B: Backend, E: EventSource> Tui<B, E> {
@@ -30,12 +34,14 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
             event_source,
             input_buffer: String::new(),
             spinner_frame: 0,
+            narrative_area: Rect::default(),
         }
     }
 
     pub async fn run(&mut self, game: &mut Game) -> Result<()> {
         let spinner_chars = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
         loop {
+            game.tick();
             let command_buffer = self.input_buffer.clone();
 
             // Update spinner frame when processing
@@ -43,13 +49,16 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
                 self.spinner_frame = (self.spinner_frame + 1) % spinner_chars.len();
             }
 
+            let mut narrative_area = self.narrative_area;
             self.terminal.draw(|frame| {
                 match game.state {
                     GameState::SplashScreen => Self::render_splash_screen(frame, game),
                     GameState::NamingWorld => Self::render_naming_screen(frame, game, &game.new_world_name),
-                    _ => Self::render_main_game(frame, game, &command_buffer, spinner_chars[self.spinner_frame]),
+                    GameState::GameOver => Self::render_game_over_screen(frame, game),
+                    _ => narrative_area = Self::render_main_game(frame, game, &command_buffer, spinner_chars[self.spinner_frame]),
                 }
             })?;
+            self.narrative_area = narrative_area;
 
             // Wait for next event
             if let Some(event) = self.event_source.next_event().await? {
@@ -86,9 +95,15 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
                                 KeyCode::Up => Command::Up,
                                 KeyCode::Down => Command::Down,
                                 KeyCode::Delete => Command::Delete,
+                                KeyCode::Char('r') | KeyCode::Char('R') => Command::RestoreBackup,
+                                KeyCode::Char('i') | KeyCode::Char('I') => Command::Import(String::new()),
                                 _ => Command::None
                             };
                             game.process_command(command).await?;
+                        } else if game.state == GameState::GameOver {
+                            if key.code == KeyCode::Enter {
+                                game.process_command(Command::Enter).await?;
+                            }
                         } else if game.state == GameState::WaitingForInput {
                             match key.code {
                                 KeyCode::Enter => {
@@ -117,6 +132,15 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
                                 KeyCode::Right => {
                                     game.process_command(Command::MoveEast).await?;
                                 }
+                                KeyCode::PageUp => {
+                                    // Stop once the first earlier turn is at the top
+                                    let total = journal_lines(game, self.narrative_area.width.saturating_sub(2)).len();
+                                    let lines = self.narrative_page().min(total.saturating_sub(game.narrative_scroll));
+                                    game.process_command(Command::ScrollBack(lines)).await?;
+                                }
+                                KeyCode::PageDown => {
+                                    game.process_command(Command::ScrollForward(self.narrative_page())).await?;
+                                }
                                 _ => {}
                             }
                         }
@@ -126,6 +150,11 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
         }
     }
     
+    /// Lines in one page of the Narrative panel, inside its borders.
+    fn narrative_page(&self) -> usize {
+        self.narrative_area.height.saturating_sub(2).max(1) as usize
+    }
+
     // ... render methods remain identical as they use Frame which is Ratatui independent of event inputs ...
 
     fn render_splash_screen(frame: &mut Frame, game: &Game) {
@@ -138,13 +167,27 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
             ])
             .split(frame.area());
 
-        let title = Paragraph::new("INFINITE TEXT ADVENTURE\n(↑↓ to select, Enter to load, Delete to remove)")
+        let title = Paragraph::new("INFINITE TEXT ADVENTURE\n(↑↓ to select, Enter to load, Delete to remove, I to import)")
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL));
         frame.render_widget(title, chunks[0]);
 
         let mut items: Vec<ListItem> = game.save_list.iter()
-            .map(|save| ListItem::new(format!("Load: {} ({})", save.filename, save.modified.format("%Y-%m-%d %H:%M"))))
+            .map(|save| {
+                let modified = save.modified.format("%Y-%m-%d %H:%M");
+                match (save.checkpoint(), &save.header) {
+                    (Some(name), header) => {
+                        let place = header.as_ref().map(|h| format!(" - {}, turn {}", h.location, h.turns)).unwrap_or_default();
+                        ListItem::new(format!("    ↳ Checkpoint: {} ({}){}", name, modified, place))
+                            .style(Style::default().fg(Color::Gray))
+                    }
+                    (None, Some(header)) => ListItem::new(vec![
+                        Line::from(format!("Load: {} ({})", header.world_name, modified)),
+                        Line::styled(format!("      {}", header.summary()), Style::default().fg(Color::DarkGray)),
+                    ]),
+                    (None, None) => ListItem::new(format!("Load: {} ({})", save.filename, modified)),
+                }
+            })
             .collect();
         items.push(ListItem::new("Start New Game"));
 
@@ -157,6 +200,14 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
         state.select(Some(game.selected_save_index));
 
         frame.render_stateful_widget(list, chunks[1], &mut state);
+
+        if !game.status_message.is_empty() {
+            let status = Paragraph::new(game.status_message.clone())
+                .wrap(Wrap { trim: true })
+                .style(Style::default().fg(Color::Yellow))
+                .block(Block::default().borders(Borders::ALL));
+            frame.render_widget(status, chunks[2]);
+        }
     }
     
     fn render_naming_screen(frame: &mut Frame, game: &Game, _input_buffer: &str) {
@@ -184,24 +235,121 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
             .block(Block::default().title("World Name").borders(Borders::ALL));
         frame.render_widget(input_block, chunks[1]);
 
-        let help = Paragraph::new("Type a name and press Enter\nPress Backspace to delete\nPress Esc to go back")
+        let help = if game.status_message.is_empty() {
+            Paragraph::new("Type a name and press Enter\nPress Backspace to delete\nPress Esc to go back")
+        } else {
+            Paragraph::new(game.status_message.clone())
+                .wrap(Wrap { trim: true })
+                .style(Style::default().fg(Color::Yellow))
+        };
+        let help = help
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL));
         frame.render_widget(help, chunks[2]);
     }
 
+    fn render_game_over_screen(frame: &mut Frame, game: &Game) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(20),
+                Constraint::Percentage(60),
+                Constraint::Percentage(20),
+            ])
+            .split(frame.area());
+
+        let title = Paragraph::new(format!("YOU HAVE FALLEN\nDeaths in this world: {}", game.world.deaths))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(title, chunks[0]);
+
+        let story = Paragraph::new(game.last_narrative.clone())
+            .wrap(Wrap { trim: true })
+            .block(Block::default().title(format!("Death policy: {}", game.world.death_policy)).borders(Borders::ALL));
+        frame.render_widget(story, chunks[1]);
+
+        let help = if game.world.death_policy != DeathPolicy::Permadeath {
+            "Press Enter to get back up\nPress Esc to quit"
+        } else {
+            "Press Enter to return to the title screen\nPress Esc to quit"
+        };
+        let help = Paragraph::new(help)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(help, chunks[2]);
+    }
+
+    /// The current turn: its narrative, where the player is and what they might do next.
+    fn render_narrative(frame: &mut Frame, game: &Game, area: Rect) {
+        let narrative_block = Block::default().borders(Borders::ALL).title("Narrative");
+        let turn_narrative = game.last_narrative.clone();
+        let location_desc = game.world.locations.get(&game.world.current_pos)
+            .map(|l| l.description.clone())
+            .unwrap_or_else(|| "Unknown location.".to_string());
+
+        let mut narrative_text = if turn_narrative.is_empty() {
+            location_desc
+        } else {
+            format!("{}\n\n{}", turn_narrative, location_desc)
+        };
+
+        // Append options
+        if !game.current_options.is_empty() {
+            narrative_text.push_str("\n\nSuggested Actions:\n");
+            for (i, option) in game.current_options.iter().enumerate() {
+                narrative_text.push_str(&format!("{}. {}\n", i + 1, option));
+            }
+        }
+
+        frame.render_widget(
+            Paragraph::new(narrative_text)
+                .block(narrative_block)
+                .wrap(Wrap { trim: true }),
+            area,
+        );
+    }
+
+    /// Earlier turns from the journal, scrolled `narrative_scroll` lines
+    /// back from the end of the turn before the current one.
+    fn render_journal(frame: &mut Frame, game: &Game, area: Rect) {
+        let lines = journal_lines(game, area.width.saturating_sub(2));
+        let top = lines.len().saturating_sub(game.narrative_scroll);
+        let title = format!("Narrative - turn {} of {} (PgUp/PgDn to page)",
+            lines.get(top).map(|(turn, _)| *turn).unwrap_or_default(), game.world.turns);
+        let lines: Vec<Line> = lines.into_iter().map(|(_, line)| line).collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .scroll((top.min(u16::MAX as usize) as u16, 0)),
+            area,
+        );
+    }
+
+    // Secret passages stay off the map until they are revealed.
+    fn visible_exit_target(exit: &Exit) -> Option<(i32, i32, i32)> {
+        match exit {
+            Exit::Hidden { .. } => None,
+            _ => exit.target(),
+        }
+    }
+
+    fn has_visible_exit(loc: &Location, directions: &[&str]) -> bool {
+        directions.iter().any(|dir| loc.exits.get(*dir).and_then(Self::visible_exit_target).is_some())
+    }
+
     fn render_map(game: &Game) -> String {
         if game.world.locations.is_empty() {
             return "No locations".to_string();
         }
 
-        // Get visible locations (visited + adjacent to current_pos for fog-of-war)
-        let (current_x, current_y) = game.world.current_pos;
+        // Get visible locations on the current level (visited + adjacent to current_pos for fog-of-war)
+        let (current_x, current_y, level) = game.world.current_pos;
         let mut visible_coords = Vec::new();
         
-        for (&(x, y), loc) in &game.world.locations {
-            if loc.visited || 
-               (x.abs_diff(current_x) <= 1 && y.abs_diff(current_y) <= 1) {
+        for (&(x, y, z), loc) in &game.world.locations {
+            if z == level && (loc.visited ||
+               (x.abs_diff(current_x) <= 1 && y.abs_diff(current_y) <= 1)) {
                 visible_coords.push((x, y));
             }
         }
@@ -222,45 +370,61 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
             max_y = max_y.max(y);
         }
 
-        let width = (max_x - min_x + 1) as usize;
-        let height = (max_y - min_y + 1) as usize;
+        // Tiles sit on even cells so paths, including diagonals, have room between them
+        let width = ((max_x - min_x) * 2 + 1) as usize;
+        let height = ((max_y - min_y) * 2 + 1) as usize;
 
-        let mut grid = vec![vec!['.'; width]; height];
+        let mut grid = vec![vec![' '; width]; height];
+        // Unexplored cells show the lie of the land from the world's terrain layer
+        for (row_index, row) in grid.iter_mut().enumerate().step_by(2) {
+            for (col_index, cell) in row.iter_mut().enumerate().step_by(2) {
+                let pos = (min_x + col_index as i32 / 2, max_y - row_index as i32 / 2, level);
+                *cell = game.world.terrain_at(pos).biome.glyph();
+            }
+        }
 
         // Render locations and paths
         for &(x, y) in &visible_coords {
-            let gx = (x - min_x) as usize;
-            let gy = (max_y - y) as usize; // Y reversed (north at top)
-            
-            if gx < width && gy < height {
-                if (x, y) == game.world.current_pos {
-                    grid[gy][gx] = '@';
-                } else if let Some(loc) = game.world.locations.get(&(x, y)) {
-                    grid[gy][gx] = if loc.visited { '#' } else { '?' };
-                }
-                
-                // Draw paths to adjacent visible locations
-                if let Some(current_loc) = game.world.locations.get(&(x, y)) {
-                    // North path
-                    if let Some(Some((nx, ny))) = current_loc.exits.get("north") {
-                        if visible_coords.contains(&(*nx, *ny)) {
-                            let ngx = (*nx - min_x) as usize;
-                            let ngy = (max_y - *ny) as usize;
-                            if ngx < width && ngy < height && ngy < gy {
-                                grid[ngy][ngx] = '|';
-                            }
-                        }
-                    }
-                    // East path
-                    if let Some(Some((ex, ey))) = current_loc.exits.get("east") {
-                        if visible_coords.contains(&(*ex, *ey)) {
-                            let egx = (*ex - min_x) as usize;
-                            let egy = (max_y - *ey) as usize;
-                            if egx < width && egy < height && egx > gx {
-                                grid[egy][gx] = '-';
-                            }
-                        }
+            let gx = ((x - min_x) * 2) as usize;
+            let gy = ((max_y - y) * 2) as usize; // Y reversed (north at top)
+
+            if (x, y, level) == game.world.current_pos {
+                grid[gy][gx] = '@';
+            } else if let Some(loc) = game.world.locations.get(&(x, y, level)) {
+                grid[gy][gx] = if !loc.visited {
+                    '?'
+                } else if Self::has_visible_exit(loc, &["up", "enter"]) {
+                    '<'
+                } else if Self::has_visible_exit(loc, &["down", "exit"]) {
+                    '>'
+                } else {
+                    '#'
+                };
+            }
+
+            // Draw paths to adjacent visible locations, in the cell between the two tiles
+            if let Some(current_loc) = game.world.locations.get(&(x, y, level)) {
+                for dir in COMPASS_DIRECTIONS {
+                    let Some((tx, ty, _)) = current_loc.exits.get(dir).and_then(Self::visible_exit_target) else {
+                        continue;
+                    };
+                    let (dx, dy) = (tx - x, ty - y);
+                    if dx.abs() > 1 || dy.abs() > 1 || !visible_coords.contains(&(tx, ty)) {
+                        continue;
                     }
+                    let path = match (dx, dy) {
+                        (0, _) => '|',
+                        (_, 0) => '-',
+                        (1, 1) | (-1, -1) => '/',
+                        _ => '\\',
+                    };
+                    let px = (gx as i32 + dx) as usize;
+                    let py = (gy as i32 - dy) as usize;
+                    // Crossing diagonals share a cell
+                    grid[py][px] = match (grid[py][px], path) {
+                        ('/', '\\') | ('\\', '/') => 'X',
+                        _ => path,
+                    };
                 }
             }
         }
@@ -273,7 +437,46 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
         map_str.trim_end().to_string()
     }
 
-    fn render_main_game(frame: &mut Frame, game: &Game, input_buffer: &str, spinner_char: char) {
+    /// One-line legend of the regions on the current level that the player
+    /// has seen, with the one they are standing in first.
+    fn render_region_legend(game: &Game) -> String {
+        let level = game.world.current_pos.2;
+        let here = game.world.region_at(game.world.current_pos);
+        let mut names: Vec<&str> = game.world.regions.values()
+            .filter(|r| Some(r.id.as_str()) != here.map(|h| h.id.as_str()))
+            .filter(|r| r.tiles.iter().any(|pos| {
+                pos.2 == level && game.world.locations.get(pos).is_some_and(|l| l.visited)
+            }))
+            .map(|r| r.name.as_str())
+            .collect();
+        names.sort();
+
+        match (here, names.is_empty()) {
+            (Some(region), true) => format!("Here: {}", region.name),
+            (Some(region), false) => format!("Here: {} | Nearby: {}", region.name, names.join(", ")),
+            (None, false) => format!("Regions: {}", names.join(", ")),
+            (None, true) => String::new(),
+        }
+    }
+
+    /// Quest log text: active quests with their objectives, then finished ones.
+    fn render_quest_log(game: &Game) -> String {
+        let mut lines = Vec::new();
+        for quest in game.world.quest_log() {
+            if quest.status == QuestStatus::Active {
+                lines.push(quest.title.clone());
+                for objective in &quest.objectives {
+                    lines.push(format!(" [{}] {}", if objective.done { "x" } else { " " }, objective.description));
+                }
+            } else {
+                lines.push(format!("{} ({:?})", quest.title, quest.status));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Draws the game screen and returns where the Narrative panel went.
+    fn render_main_game(frame: &mut Frame, game: &Game, input_buffer: &str, spinner_char: char) -> Rect {
         let mut constraints = vec![
             Constraint::Min(1), // Main content
         ];
@@ -340,36 +543,32 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
             Some(loc) => format!("Image for: {}\nPrompt: {}", loc.name, loc.image_prompt),
             None => "No location".to_string(),
         };
-        frame.render_widget(Paragraph::new(image_text).block(image_block), top_chunks[0]);
+        let quest_log = Self::render_quest_log(game);
+        let visual_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(if quest_log.is_empty() { 0 } else { 8 }),
+            ])
+            .split(top_chunks[0]);
+        frame.render_widget(Paragraph::new(image_text).block(image_block), visual_chunks[0]);
+
+        if !quest_log.is_empty() {
+            let quest_block = Block::default().borders(Borders::ALL).title("Quests");
+            frame.render_widget(
+                Paragraph::new(quest_log).block(quest_block).wrap(Wrap { trim: false }),
+                visual_chunks[1],
+            );
+        }
 
         // Narrative Area
-        let narrative_block = Block::default().borders(Borders::ALL).title("Narrative");
-        let turn_narrative = game.last_narrative.clone();
-        let location_desc = game.world.locations.get(&game.world.current_pos)
-            .map(|l| l.description.clone())
-            .unwrap_or_else(|| "Unknown location.".to_string());
-
-        let mut narrative_text = if turn_narrative.is_empty() {
-            location_desc
+        let narrative_area = top_chunks[1];
+        if game.narrative_scroll > 0 {
+            Self::render_journal(frame, game, top_chunks[1]);
         } else {
-            format!("{}\n\n{}", turn_narrative, location_desc)
-        };
-
-        // Append options
-        if !game.current_options.is_empty() {
-            narrative_text.push_str("\n\nSuggested Actions:\n");
-            for (i, option) in game.current_options.iter().enumerate() {
-                narrative_text.push_str(&format!("{}. {}\n", i + 1, option));
-            }
+            Self::render_narrative(frame, game, top_chunks[1]);
         }
 
-        frame.render_widget(
-            Paragraph::new(narrative_text)
-                .block(narrative_block)
-                .wrap(Wrap { trim: true }),
-            top_chunks[1],
-        );
-
         // Debug and Map Area
         let debug_chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -380,9 +579,31 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
             .split(chunks[debug_chunks_start]);
 
         // Map Area
-        let map_block = Block::default().borders(Borders::ALL).title("Map");
+        let map_block = Block::default().borders(Borders::ALL)
+            .title(format!("Map - {} - {}", describe_level(game.world.current_pos.2),
+                game.world.terrain_at(game.world.current_pos).summary()));
         let map_text = Self::render_map(game);
-        frame.render_widget(Paragraph::new(map_text).block(map_block), debug_chunks[0]);
+        let legend = Self::render_region_legend(game);
+        let map_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(1),
+                Constraint::Length(if legend.is_empty() { 0 } else { 1 }),
+            ])
+            .split(map_block.inner(debug_chunks[0]));
+        frame.render_widget(map_block, debug_chunks[0]);
+
+        // Keep the player centred when the map outgrows the panel
+        let (player_row, player_col) = map_text.lines().enumerate()
+            .find_map(|(row, line)| line.chars().position(|c| c == '@').map(|col| (row, col)))
+            .unwrap_or((0, 0));
+        let map_area = map_chunks[0];
+        let scroll = (
+            (player_row as u16).saturating_sub(map_area.height / 2),
+            (player_col as u16).saturating_sub(map_area.width / 2),
+        );
+        frame.render_widget(Paragraph::new(map_text).scroll(scroll), map_area);
+        frame.render_widget(Paragraph::new(legend).style(Style::default().fg(Color::Cyan)), map_chunks[1]);
 
         // Debug Log Area
         let debug_block = Block::default().borders(Borders::ALL).title("Debug Log");
@@ -405,17 +626,79 @@ impl<B: Backend, E: EventSource> Tui<B, E> {
 
         // Status Bar
         let status_text = format!(
-            "Save: {} | Status: {:?} | Money: {}",
+            "Save: {} | Status: {:?} | Money: {} | Mana: {}/{} | Day {} {} ({}) | {}",
             game.current_save_path.as_deref().unwrap_or("Unsaved"),
             match game.state {
                 GameState::Processing => "Processing",
                 GameState::UpdatingWorld => "Updating",
                 _ => "Idle",
             },
-            game.world.player.money
+            game.world.player.money,
+            game.world.player.mana,
+            game.world.player.max_mana,
+            game.world.clock.day(),
+            game.world.clock.time_of_day(),
+            game.world.clock.phase(),
+            game.world.weather.summary()
         );
         frame.render_widget(Paragraph::new(status_text).style(Style::default().bg(Color::Blue).fg(Color::White)), chunks[status_chunk]);
+
+        narrative_area
+    }
+}
+
+/// Turns before the current one, wrapped to `width`, each line with
+/// the turn it belongs to. The current turn is the live narrative.
+fn journal_lines(game: &Game, width: u16) -> Vec<(u32, Line<'static>)> {
+    let journal = &game.world.journal;
+    let earlier = &journal[..journal.len().saturating_sub(1)];
+    let width = width as usize;
+    let mut lines = Vec::new();
+    for entry in earlier {
+        let mut push = |text: &str, style: Style| {
+            for line in wrap_text(text, width) {
+                lines.push((entry.turn, Line::from(Span::styled(line, style))));
+            }
+        };
+        push(&format!("Turn {} · {} · {}", entry.turn, entry.location, entry.time), Style::default().fg(Color::Yellow));
+        if !entry.input.is_empty() {
+            push(&format!("> {}", entry.input), Style::default().fg(Color::Cyan));
+        }
+        for line in entry.narrative.lines() {
+            push(line, Style::default());
+        }
+        for event in &entry.events {
+            push(&format!("* {}", event), Style::default().fg(Color::Gray));
+        }
+        push("", Style::default());
+    }
+    lines
+}
+
+/// Word-wraps `text` to `width` columns, splitting words that don't fit, so
+/// the journal knows how many lines it scrolls through.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        while current.chars().count() > width {
+            let rest = current.chars().skip(width).collect();
+            lines.push(current.chars().take(width).collect());
+            current = rest;
+        }
     }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -468,3 +751,67 @@ impl EventSource for CrosstermEventSource {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LlmClient;
+    use crate::model::INTERIOR_LEVELS;
+    use ratatui::backend::TestBackend;
+
+    /// Plays back a fixed list of key presses, then presses Esc to quit.
+    struct ScriptedKeys(Vec<KeyCode>);
+
+    #[async_trait::async_trait(?Send)]
+    impl EventSource for ScriptedKeys {
+        async fn next_event(&mut self) -> Result<Option<InputEvent>> {
+            let code = if self.0.is_empty() { KeyCode::Esc } else { self.0.remove(0) };
+            Ok(Some(InputEvent::Key(crate::input::KeyEvent { code, kind: KeyEventKind::Press })))
+        }
+    }
+
+    fn typed(text: &str) -> Vec<KeyCode> {
+        text.chars().map(KeyCode::Char).chain([KeyCode::Enter]).collect()
+    }
+
+    #[tokio::test]
+    async fn test_typed_moves_take_the_quick_path() {
+        let mut game = Game::new(LlmClient::new("http://localhost:11434".to_string(), "test".to_string()));
+        game.state = GameState::WaitingForInput;
+        for pos in [(0, 0, 0), (1, 1, 0), (1, 1, INTERIOR_LEVELS)] {
+            game.world.locations.insert(pos, crate::worldgen::fallback_location(pos));
+        }
+        game.world.locations.get_mut(&(1, 1, 0)).unwrap()
+            .exits.insert("enter".to_string(), Exit::Open { to: (1, 1, INTERIOR_LEVELS) });
+
+        let keys = [typed("ne"), typed("go inside")].concat();
+        let mut tui = Tui::new(Terminal::new(TestBackend::new(80, 30)).unwrap(), ScriptedKeys(keys));
+        tui.run(&mut game).await.unwrap();
+
+        assert_eq!(game.world.current_pos, (1, 1, INTERIOR_LEVELS));
+        assert_eq!(game.world.turns, 2);
+        assert!(game.last_narrative.starts_with("You move"), "{}", game.last_narrative);
+    }
+
+    #[test]
+    fn test_wrap_text_fits_words_to_the_width() {
+        assert_eq!(wrap_text("the quick brown fox", 9), vec!["the quick", "brown fox"]);
+        assert_eq!(wrap_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap_text("", 10), vec![""]);
+    }
+
+    #[test]
+    fn test_journal_lines_leave_out_the_current_turn() {
+        let mut game = Game::new(LlmClient::new("http://localhost:11434".to_string(), "test".to_string()));
+        game.world.turns = 1;
+        game.world.record_turn("open gate", "The gate creaks open.", vec![]);
+        game.world.turns = 2;
+        game.world.record_turn("enter", "You step through.", vec![]);
+
+        let lines = journal_lines(&game, 12);
+        assert!(lines.iter().all(|(turn, _)| *turn == 1));
+        let text: Vec<String> = lines.iter().map(|(_, line)| line.to_string()).collect();
+        assert!(text.contains(&"The gate".to_string()));
+        assert!(text.iter().all(|line| line.chars().count() <= 12));
+    }
+}
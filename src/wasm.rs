@@ -11,6 +11,45 @@ use ratatui::layout::Size;
 // Thread-local event queue
 thread_local! {
     static EVENT_QUEUE: RefCell<VecDeque<InputEvent>> = RefCell::new(VecDeque::new());
+    static UPLOADED_BUNDLE: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Hand over a world bundle picked with the page's Import button; the next
+/// `import` command reads it.
+#[wasm_bindgen]
+pub fn upload_bundle(data: Vec<u8>) {
+    UPLOADED_BUNDLE.with(|b| *b.borrow_mut() = Some(data));
+}
+
+pub(crate) fn take_upload() -> Option<Vec<u8>> {
+    UPLOADED_BUNDLE.with(|b| b.borrow_mut().take())
+}
+
+/// How long a download's object URL is kept before it is revoked.
+const DOWNLOAD_URL_LIFETIME_MS: i32 = 60_000;
+
+/// Offer `data` to the browser as a file download.
+pub(crate) fn download(file_name: &str, data: &[u8]) -> anyhow::Result<()> {
+    let js_err = |e: JsValue| anyhow::anyhow!("{:?}", e);
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(js_err)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_err)?;
+
+    let document = web_sys::window().and_then(|w| w.document()).ok_or_else(|| anyhow::anyhow!("no document"))?;
+    let link: web_sys::HtmlAnchorElement = document.create_element("a").map_err(js_err)?
+        .dyn_into()
+        .map_err(|_| anyhow::anyhow!("could not create a download link"))?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    // The download starts after `click` returns, so the URL has to outlive it.
+    let revoke = Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    web_sys::window().ok_or_else(|| anyhow::anyhow!("no window"))?
+        .set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), DOWNLOAD_URL_LIFETIME_MS)
+        .map_err(js_err)?;
+    Ok(())
 }
 
 #[wasm_bindgen]
@@ -23,6 +62,8 @@ pub fn send_input(key: String) {
         "ArrowDown" => KeyCode::Down,
         "ArrowLeft" => KeyCode::Left,
         "ArrowRight" => KeyCode::Right,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
         c if c.len() == 1 => KeyCode::Char(c.chars().next().unwrap()),
         _ => return,
     };
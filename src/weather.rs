@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::biome::{self, Biome};
+use crate::clock::{DayPhase, WorldClock, MINUTES_PER_HOUR};
+use crate::model::StatusType;
+
+/// Width, in tiles, of the area that shares the same weather.
+const WEATHER_SCALE: i32 = 12;
+
+/// How long one spell of weather lasts before it can change.
+const HOURS_PER_SPELL: u64 = 6;
+
+/// Dry, clear weather at least this hot can set forests and grassland alight.
+const WILDFIRE_TEMPERATURE: i32 = 22;
+const WILDFIRE_CHANCE: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Condition {
+    Clear,
+    Overcast,
+    Fog,
+    Rain,
+    Storm,
+    Snow,
+    Blizzard,
+    Heatwave,
+    Sandstorm,
+    Wildfire,
+    /// Underground, out of the weather.
+    Still,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Condition::Clear => "clear skies",
+            Condition::Overcast => "overcast",
+            Condition::Fog => "thick fog",
+            Condition::Rain => "rain",
+            Condition::Storm => "thunderstorm",
+            Condition::Snow => "snow",
+            Condition::Blizzard => "blizzard",
+            Condition::Heatwave => "scorching heat",
+            Condition::Sandstorm => "sandstorm",
+            Condition::Wildfire => "wildfire smoke and flames",
+            Condition::Still => "still underground air",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Conditions where the player is standing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Weather {
+    pub condition: Condition,
+    /// Degrees Celsius.
+    pub temperature: i32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            condition: Condition::Clear,
+            temperature: 15,
+        }
+    }
+}
+
+impl Weather {
+    /// Short summary, e.g. "snow, -4°C".
+    pub fn summary(&self) -> String {
+        format!("{}, {}°C", self.condition, self.temperature)
+    }
+
+    /// The status effect this weather inflicts on anyone caught out in it,
+    /// with a line explaining why.
+    pub fn hazard(&self) -> Option<(StatusType, &'static str)> {
+        match self.condition {
+            Condition::Blizzard => Some((StatusType::Frozen, "The blizzard chills you to the bone.")),
+            Condition::Wildfire => Some((StatusType::Burning, "Embers from the wildfire catch your clothes.")),
+            _ if self.temperature <= -20 => Some((StatusType::Frozen, "The bitter cold numbs your hands and feet.")),
+            _ => None,
+        }
+    }
+}
+
+/// Anywhere off the surface level, underground or indoors, is out of the
+/// weather whatever the terrain above it.
+pub fn is_sheltered(pos: (i32, i32, i32)) -> bool {
+    pos.2 != 0
+}
+
+/// Work out the weather at `pos` at the time on `clock`.
+///
+/// Like terrain, weather is never stored ahead of time: the seed, the area
+/// around `pos` and the current six-hour spell always give the same answer.
+/// Biome sets the likely conditions, and season and time of day the temperature.
+pub fn weather_at(seed: u64, pos: (i32, i32, i32), clock: &WorldClock) -> Weather {
+    let biome = biome::terrain_at(seed, pos).biome;
+    if is_sheltered(pos) || biome == Biome::Cavern {
+        return Weather { condition: Condition::Still, temperature: 12 };
+    }
+
+    let spell = clock.minutes / (HOURS_PER_SPELL * MINUTES_PER_HOUR);
+    let roll = spell_roll(seed, pos, spell, 0);
+    let temperature = base_temperature(biome) + season_offset(clock.month()) + phase_offset(clock.phase());
+
+    let table = conditions(biome);
+    let total: u32 = table.iter().map(|(_, weight)| weight).sum();
+    let mut pick = (roll * total as f64) as u32;
+    let mut condition = table[0].0;
+    for (candidate, weight) in table {
+        if pick < *weight {
+            condition = *candidate;
+            break;
+        }
+        pick -= weight;
+    }
+
+    let condition = match condition {
+        Condition::Rain if temperature <= 0 => Condition::Snow,
+        Condition::Storm if temperature <= 0 => Condition::Blizzard,
+        Condition::Clear if matches!(biome, Biome::Forest | Biome::Plains)
+            && temperature >= WILDFIRE_TEMPERATURE
+            && spell_roll(seed, pos, spell, 1) < WILDFIRE_CHANCE => Condition::Wildfire,
+        Condition::Clear if temperature >= 32 => Condition::Heatwave,
+        other => other,
+    };
+
+    Weather { condition, temperature }
+}
+
+/// Likely conditions for a biome, with relative weights.
+fn conditions(biome: Biome) -> &'static [(Condition, u32)] {
+    match biome {
+        Biome::Desert => &[(Condition::Clear, 6), (Condition::Heatwave, 3), (Condition::Sandstorm, 1)],
+        Biome::Tundra => &[(Condition::Clear, 1), (Condition::Overcast, 3), (Condition::Snow, 4), (Condition::Blizzard, 2)],
+        Biome::Mountains => &[(Condition::Clear, 3), (Condition::Overcast, 2), (Condition::Snow, 3), (Condition::Storm, 1), (Condition::Blizzard, 1)],
+        Biome::Swamp => &[(Condition::Overcast, 2), (Condition::Fog, 4), (Condition::Rain, 3), (Condition::Storm, 1)],
+        Biome::Lake => &[(Condition::Clear, 3), (Condition::Fog, 2), (Condition::Rain, 3), (Condition::Storm, 2)],
+        Biome::Forest => &[(Condition::Clear, 4), (Condition::Overcast, 2), (Condition::Rain, 3), (Condition::Storm, 1)],
+        Biome::Plains | Biome::Hills | Biome::Cavern => &[(Condition::Clear, 4), (Condition::Overcast, 3), (Condition::Rain, 2), (Condition::Storm, 1)],
+    }
+}
+
+fn base_temperature(biome: Biome) -> i32 {
+    match biome {
+        Biome::Tundra => -12,
+        Biome::Mountains => -2,
+        Biome::Hills => 10,
+        Biome::Lake | Biome::Forest | Biome::Cavern => 12,
+        Biome::Plains => 14,
+        Biome::Swamp => 18,
+        Biome::Desert => 28,
+    }
+}
+
+fn season_offset(month: u64) -> i32 {
+    match month {
+        0 | 1 | 11 => -10,
+        5..=7 => 8,
+        _ => 0,
+    }
+}
+
+fn phase_offset(phase: DayPhase) -> i32 {
+    match phase {
+        DayPhase::Night => -6,
+        DayPhase::Dawn => -3,
+        DayPhase::Evening => -2,
+        DayPhase::Morning | DayPhase::Dusk => 0,
+        DayPhase::Afternoon => 4,
+    }
+}
+
+/// Uniform 0.0..1.0 roll for an area and spell of weather.
+fn spell_roll(seed: u64, pos: (i32, i32, i32), spell: u64, channel: u64) -> f64 {
+    let area_x = pos.0.div_euclid(WEATHER_SCALE) as u64;
+    let area_y = pos.1.div_euclid(WEATHER_SCALE) as u64;
+    let mut h = seed ^ (channel + 11).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= area_x.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^= area_y.wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= spell.wrapping_mul(0xD6E8_FEB8_6659_FD93);
+    (biome::splitmix64(h) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_is_deterministic_and_shared_by_area() {
+        let clock = WorldClock { minutes: 500 };
+        for seed in 0..20 {
+            assert_eq!(weather_at(seed, (3, 4, 0), &clock), weather_at(seed, (3, 4, 0), &clock));
+            // Tiles in the same area share a roll, so only biome can make them differ.
+            assert_eq!(spell_roll(seed, (0, 0, 0), 1, 0), spell_roll(seed, (5, 5, 0), 1, 0));
+        }
+    }
+
+    #[test]
+    fn test_underground_and_indoors_are_sheltered() {
+        for pos in [(0, 0, -1), (0, 0, crate::model::INTERIOR_LEVELS), (4, 2, crate::model::INTERIOR_LEVELS + 2)] {
+            let weather = weather_at(7, pos, &WorldClock::default());
+            assert_eq!(weather.condition, Condition::Still);
+            assert!(weather.hazard().is_none());
+        }
+    }
+
+    #[test]
+    fn test_wildfires_break_out_in_hot_forests_and_plains() {
+        // Midsummer afternoon: months 5-7 are the warmest, 12:00-16:59 the warmest hours.
+        let mut clock = WorldClock { minutes: 6 * crate::clock::DAYS_PER_MONTH * crate::clock::MINUTES_PER_DAY };
+        while clock.phase() != DayPhase::Afternoon {
+            clock.advance(MINUTES_PER_HOUR);
+        }
+        let fire = (0..50u64)
+            .flat_map(|seed| (-20..20).map(move |x| (seed, (x * WEATHER_SCALE, 0, 0))))
+            .find(|(seed, pos)| weather_at(*seed, *pos, &clock).condition == Condition::Wildfire);
+        let (seed, pos) = fire.expect("no wildfire anywhere in midsummer");
+        assert!(matches!(biome::terrain_at(seed, pos).biome, Biome::Forest | Biome::Plains));
+        assert_eq!(weather_at(seed, pos, &clock).hazard().map(|(s, _)| s), Some(StatusType::Burning));
+    }
+
+    #[test]
+    fn test_hazards() {
+        let blizzard = Weather { condition: Condition::Blizzard, temperature: -8 };
+        assert_eq!(blizzard.hazard().map(|(s, _)| s), Some(StatusType::Frozen));
+        let fire = Weather { condition: Condition::Wildfire, temperature: 35 };
+        assert_eq!(fire.hazard().map(|(s, _)| s), Some(StatusType::Burning));
+        assert!(Weather { condition: Condition::Overcast, temperature: -25 }.hazard().is_some());
+        assert!(Weather::default().hazard().is_none());
+        assert_eq!(blizzard.summary(), "blizzard, -8°C");
+    }
+}
@@ -0,0 +1,552 @@
+use crate::biome::terrain_constraints;
+use crate::llm::LlmClient;
+use crate::model::{describe_level, direction_offset, Actor, ActorSpec, GeneratedLocation, Item, ItemSpec, ItemState, Location, Region, RegionSpec, WorldState, COMPASS_DIRECTIONS};
+use std::collections::{HashMap, HashSet};
+
+/// System prompt for every location generation request.
+pub const SYSTEM_PROMPT: &str = "You are a world generator for a text adventure game. Create interesting, thematically consistent locations. You MUST output valid JSON only.";
+
+/// Longest location name kept from the LLM; anything longer is cut at a word boundary.
+const MAX_NAME_LEN: usize = 60;
+
+/// Most items a freshly generated room may bring into the world.
+pub const MAX_ITEMS_PER_ROOM: usize = 3;
+
+/// Most actors a freshly generated room may bring into the world.
+pub const MAX_ACTORS_PER_ROOM: usize = 2;
+
+/// What happened when a location was generated.
+#[derive(Debug, Clone)]
+pub struct GenerationReport {
+    pub name: String,
+    /// The LLM failed and the placeholder location was used instead.
+    pub used_fallback: bool,
+    /// Problems found and fixed while validating the LLM's output, plus any LLM error.
+    pub notes: Vec<String>,
+}
+
+/// Generate the empty cell at `target`, reached by going `direction` from the
+/// current position, store it in the world and link exits between the two.
+///
+/// The player is not moved. Falls back to a placeholder location when the LLM
+/// is unavailable, so this never leaves the cell empty.
+pub async fn generate_location(
+    llm_client: &LlmClient,
+    world: &mut WorldState,
+    target: (i32, i32, i32),
+    direction: &str,
+) -> GenerationReport {
+    let prompt = build_location_prompt(world, target, direction);
+
+    let (generated, used_fallback, mut notes) = match llm_client.generate_location(SYSTEM_PROMPT, &prompt).await {
+        Ok(generated) => (generated, false, Vec::new()),
+        Err(e) => (fallback_location(target).into(), true, vec![format!("Failed to generate location: {}", e)]),
+    };
+    notes.extend(place_generated(world, generated, target));
+
+    let report = GenerationReport {
+        name: world.locations[&target].name.clone(),
+        used_fallback,
+        notes,
+    };
+
+    let from = world.current_pos;
+    world.link_exits(from, target, direction);
+    report
+}
+
+/// Validate a generated location, register the items and actors it brought
+/// with it and store it at `target`. Exits are left for the caller to link.
+/// Returns a note for each fix or rejection.
+pub fn place_generated(world: &mut WorldState, generated: GeneratedLocation, target: (i32, i32, i32)) -> Vec<String> {
+    let GeneratedLocation { mut location, items, actors, region, mut rejected } = generated;
+    let mut notes = validate_location(world, &mut location, target);
+    notes.append(&mut rejected);
+    notes.extend(populate(world, &mut location, target, items, actors));
+    world.locations.insert(target, location);
+    notes.extend(assign_region(world, target, region));
+    notes
+}
+
+/// Regions with a tile next to `target`, with the directions they lie in.
+pub fn adjacent_regions(world: &WorldState, target: (i32, i32, i32)) -> Vec<(&Region, Vec<String>)> {
+    let mut found: Vec<(&Region, Vec<String>)> = Vec::new();
+    for (label, pos) in neighbours(target) {
+        let Some(region) = world.region_at(pos) else { continue };
+        match found.iter_mut().find(|(r, _)| r.id == region.id) {
+            Some((_, labels)) => labels.push(label),
+            None => found.push((region, vec![label])),
+        }
+    }
+    found
+}
+
+/// Put the tile at `target` into a region.
+///
+/// A named region is only joined if it borders the tile, so regions stay in
+/// one piece. Without a usable choice the tile joins a bordering region whose
+/// neighbouring tile has the same biome, or stays outside any region.
+pub fn assign_region(world: &mut WorldState, target: (i32, i32, i32), spec: Option<RegionSpec>) -> Vec<String> {
+    let mut notes = Vec::new();
+    let adjacent: Vec<String> = adjacent_regions(world, target).into_iter().map(|(r, _)| r.id.clone()).collect();
+    let find_named = |world: &WorldState, name: &str| -> Option<String> {
+        let wanted = name.trim().to_lowercase();
+        world.regions.values()
+            .find(|r| r.id == wanted || r.name.to_lowercase() == wanted)
+            .map(|r| r.id.clone())
+    };
+
+    let chosen = match spec {
+        Some(RegionSpec::Existing(name)) | Some(RegionSpec::New { name, .. }) if find_named(world, &name).is_some() => {
+            let id = find_named(world, &name).unwrap();
+            if adjacent.contains(&id) {
+                Some(id)
+            } else {
+                notes.push(format!("Ignored region '{}': it does not border this tile", name));
+                None
+            }
+        }
+        Some(RegionSpec::New { name, description, lore }) if !name.trim().is_empty() => {
+            let id = unique_id(&name, |id| world.regions.contains_key(id));
+            notes.push(format!("Founded region '{}'", name.trim()));
+            world.regions.insert(id.clone(), Region {
+                id: id.clone(),
+                name: name.trim().to_string(),
+                description: description.trim().to_string(),
+                lore: lore.trim().to_string(),
+                tiles: Vec::new(),
+            });
+            Some(id)
+        }
+        Some(RegionSpec::Existing(name)) if !name.trim().is_empty() => {
+            notes.push(format!("Ignored unknown region '{}'", name));
+            None
+        }
+        _ => None,
+    };
+
+    let chosen = chosen.or_else(|| {
+        let biome = world.terrain_at(target).biome;
+        neighbours(target).into_iter()
+            .filter(|(_, pos)| world.terrain_at(*pos).biome == biome)
+            .find_map(|(_, pos)| world.region_at(pos).map(|r| r.id.clone()))
+    });
+
+    if let Some(region) = chosen.and_then(|id| world.regions.get_mut(&id)) {
+        if !region.tiles.contains(&target) {
+            region.tiles.push(target);
+        }
+    }
+    notes
+}
+
+/// Every cell touching `target`: the eight on its level, then above and below.
+fn neighbours(target: (i32, i32, i32)) -> Vec<(String, (i32, i32, i32))> {
+    let (x, y, z) = target;
+    let mut neighbors: Vec<(String, (i32, i32, i32))> = COMPASS_DIRECTIONS.iter()
+        .filter_map(|dir| direction_offset(dir).map(|(dx, dy, _)| {
+            (format!("To the {}", dir), (x + dx, y + dy, z))
+        }))
+        .collect();
+    neighbors.push(("Above".to_string(), (x, y, z + 1)));
+    neighbors.push(("Below".to_string(), (x, y, z - 1)));
+    neighbors
+}
+
+/// How many more items the world can take before hitting `max_items`.
+/// Items waiting in pre-generated rooms the player hasn't reached yet don't
+/// count, so speculative rooms can't use up the budget.
+pub fn item_budget(world: &WorldState) -> usize {
+    let waiting: HashSet<&String> = world.locations.values()
+        .filter(|l| !l.visited)
+        .flat_map(|l| &l.items)
+        .collect();
+    let placed = world.items.keys().filter(|id| !waiting.contains(id)).count();
+    (world.max_items as usize).saturating_sub(placed)
+}
+
+fn populate(
+    world: &mut WorldState,
+    location: &mut Location,
+    target: (i32, i32, i32),
+    items: Vec<ItemSpec>,
+    actors: Vec<ActorSpec>,
+) -> Vec<String> {
+    let mut notes = Vec::new();
+
+    let (items, nameless): (Vec<ItemSpec>, Vec<ItemSpec>) = items.into_iter().partition(|spec| !spec.name.trim().is_empty());
+    if !nameless.is_empty() {
+        notes.push(format!("Discarded {} generated item(s) with no name", nameless.len()));
+    }
+    let allowed = item_budget(world).min(MAX_ITEMS_PER_ROOM);
+    if items.len() > allowed {
+        notes.push(format!("Item budget reached; dropped {} generated item(s)", items.len() - allowed));
+    }
+    for spec in items.into_iter().take(allowed) {
+        let name = spec.name.trim().to_string();
+        let id = unique_id(spec.id.as_deref().unwrap_or(&name), |id| is_taken(world, id));
+        let description = if spec.description.trim().is_empty() { name.clone() } else { spec.description.trim().to_string() };
+        world.items.insert(id.clone(), Item {
+            id: id.clone(),
+            name,
+            description,
+            item_type: spec.item_type,
+            state: ItemState::Normal,
+            properties: spec.properties,
+        });
+        location.items.push(id);
+    }
+
+    if actors.len() > MAX_ACTORS_PER_ROOM {
+        notes.push(format!("Too many actors; dropped {}", actors.len() - MAX_ACTORS_PER_ROOM));
+    }
+    for spec in actors.into_iter().take(MAX_ACTORS_PER_ROOM) {
+        let name = spec.name.trim().to_string();
+        if name.is_empty() {
+            notes.push("Discarded generated actor with no name".to_string());
+            continue;
+        }
+        let id = unique_id(spec.id.as_deref().unwrap_or(&name), |id| is_taken(world, id));
+        let description = if spec.description.trim().is_empty() { name.clone() } else { spec.description.trim().to_string() };
+        world.actors.insert(id.clone(), Actor {
+            id: id.clone(),
+            name,
+            description,
+            current_pos: target,
+            inventory: Vec::new(),
+            money: spec.money,
+            faction: spec.faction.filter(|f| world.factions.contains_key(f)),
+        });
+        location.actors.push(id);
+    }
+
+    notes
+}
+
+/// Items and actors share one ID namespace so tools can refer to either.
+fn is_taken(world: &WorldState, id: &str) -> bool {
+    world.items.contains_key(id) || world.actors.contains_key(id)
+}
+
+/// Turn a name into a snake_case ID for which `taken` is false.
+fn unique_id(wanted: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut base = String::new();
+    for c in wanted.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            base.push(c.to_ascii_lowercase());
+        } else if !base.ends_with('_') && !base.is_empty() {
+            base.push('_');
+        }
+    }
+    let base = match base.trim_end_matches('_') {
+        "" => "thing".to_string(),
+        trimmed => trimmed.to_string(),
+    };
+
+    if !taken(&base) {
+        return base;
+    }
+    (2..).map(|n| format!("{}_{}", base, n)).find(|id| !taken(id)).unwrap()
+}
+
+/// Prompt asking for the location at `target`, with the current location,
+/// every known neighbour of the target, the terrain it must match and how
+/// many items and actors it may introduce.
+pub fn build_location_prompt(world: &WorldState, target: (i32, i32, i32), direction: &str) -> String {
+    let (target_x, target_y, target_z) = target;
+    let (current_x, current_y, current_z) = world.current_pos;
+    let (current_name, current_description) = world.locations.get(&world.current_pos)
+        .map(|l| (l.name.as_str(), l.description.as_str()))
+        .unwrap_or(("Unknown", ""));
+
+    let mut neighbor_info = String::new();
+    for (dir, pos) in neighbours(target).iter() {
+        if let Some(loc) = world.locations.get(pos) {
+            neighbor_info.push_str(&format!("- {} is '{}': {}\n", dir, loc.name, loc.description));
+        }
+    }
+    if neighbor_info.is_empty() {
+        neighbor_info = "None known.".to_string();
+    }
+
+    let mut region_info = String::new();
+    for (region, labels) in adjacent_regions(world, target) {
+        region_info.push_str(&format!("- '{}' ({}): {}\n", region.name, labels.join(", ").to_lowercase(), region.description));
+    }
+    if region_info.is_empty() {
+        region_info = "None.".to_string();
+    }
+
+    let items_rule = match item_budget(world).min(MAX_ITEMS_PER_ROOM) {
+        0 => "items MUST be an empty array [] (the world has no room for more items)".to_string(),
+        n => format!("items: at most {} objects worth finding here, or an empty array []", n),
+    };
+
+    let mut faction_ids: Vec<&String> = world.factions.keys().collect();
+    faction_ids.sort();
+    let faction_rule = if faction_ids.is_empty() {
+        "always null (no factions exist yet)".to_string()
+    } else {
+        let listed: Vec<String> = faction_ids.iter()
+            .map(|id| format!("'{}' ({})", id, world.factions[*id].name))
+            .collect();
+        format!("the ID of the faction an actor belongs to, one of {}, or null", listed.join(", "))
+    };
+
+    format!(
+        r#"Current Location: {} at ({}, {}, {})
+Description: {}
+
+Known Adjacent Locations (for context):
+{}
+Bordering Regions:
+{}
+
+The player is heading {} toward coordinates ({}, {}, {}) on {}.
+This grid cell is currently EMPTY and needs to be generated.
+It is {}. The description may mention the light, but describe the place itself: the player will come back at other times of day.
+
+{}
+
+Create a new location at ({}, {}, {}) that fits thematically with the current location and connects logically to any adjacent locations listed above.
+
+Return ONLY a valid JSON object:
+{{
+  "name": "Location name",
+  "description": "Description of what the player sees",
+  "image_prompt": "Visual description for generating an image",
+  "items": [{{"name": "Item name", "description": "What it looks like", "item_type": "Weapon|Armor|Consumable|Tool|Key|Container|QuestItem|Material", "properties": {{"value": 5, "damage": null, "defense": null, "carryable": true, "usable": false}}}}],
+  "actors": [{{"name": "Character name", "description": "Who they are and what they are doing", "money": 0, "faction": null}}],
+  "region": "Name of a bordering region this location is part of"
+}}
+
+CRITICAL:
+- {}
+- actors: at most {} people or creatures who plausibly belong here, or an empty array []
+- faction: {}
+- Mention every item and actor in the description
+- region: the name of a bordering region if this location continues it, or {{"name": "...", "description": "...", "lore": "..."}} to start a new named region (a town, forest, ruin, mountain range) here, or null
+- NO narrative text, NO extra commentary
+
+Just the JSON. Nothing else."#,
+        current_name, current_x, current_y, current_z,
+        current_description,
+        neighbor_info,
+        region_info,
+        direction, target_x, target_y, target_z, describe_level(target_z),
+        world.clock.phase(),
+        terrain_constraints(world.seed, target),
+        target_x, target_y, target_z,
+        items_rule,
+        MAX_ACTORS_PER_ROOM,
+        faction_rule
+    )
+}
+
+/// Placeholder used when the LLM cannot produce a location.
+pub fn fallback_location(target: (i32, i32, i32)) -> Location {
+    Location {
+        name: format!("Mysterious area ({}, {}, {})", target.0, target.1, target.2),
+        description: "A mysterious place that appeared suddenly.".to_string(),
+        items: vec![],
+        actors: vec![],
+        exits: HashMap::new(),
+        cached_image_path: None,
+        image_prompt: "A mysterious location with undefined characteristics.".to_string(),
+        visited: false,
+    }
+}
+
+/// Repair a freshly generated location so it can be dropped into the world.
+///
+/// Exits are always cleared (the game links them), references to unknown items
+/// and actors are dropped, and blank text is filled in. Returns a note for each fix.
+pub fn validate_location(world: &WorldState, location: &mut Location, target: (i32, i32, i32)) -> Vec<String> {
+    let mut notes = Vec::new();
+
+    location.name = location.name.trim().to_string();
+    if location.name.is_empty() {
+        location.name = fallback_location(target).name;
+        notes.push("Generated location had no name".to_string());
+    } else if location.name.chars().count() > MAX_NAME_LEN {
+        let cut: String = location.name.chars().take(MAX_NAME_LEN).collect();
+        location.name = cut.rsplit_once(' ').map(|(head, _)| head.to_string()).unwrap_or(cut);
+        notes.push("Generated location name was too long".to_string());
+    }
+
+    location.description = location.description.trim().to_string();
+    if location.description.is_empty() {
+        location.description = fallback_location(target).description;
+        notes.push("Generated location had no description".to_string());
+    }
+    if location.image_prompt.trim().is_empty() {
+        location.image_prompt = location.description.clone();
+    }
+
+    if !location.exits.is_empty() {
+        location.exits.clear();
+        notes.push("Discarded exits supplied by the generator".to_string());
+    }
+
+    let before = location.items.len() + location.actors.len();
+    location.items.retain(|id| world.items.contains_key(id));
+    location.actors.retain(|id| world.actors.contains_key(id));
+    let dropped = before - location.items.len() - location.actors.len();
+    if dropped > 0 {
+        notes.push(format!("Dropped {} unknown item/actor reference(s)", dropped));
+    }
+
+    location.cached_image_path = None;
+    location.visited = false;
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Exit, ItemProperties, ItemType};
+
+    fn start_world() -> WorldState {
+        let mut world = WorldState::new();
+        let mut start = fallback_location((0, 0, 0));
+        start.name = "Village Square".to_string();
+        world.locations.insert((0, 0, 0), start);
+        world
+    }
+
+    #[test]
+    fn test_prompt_includes_neighbours_and_terrain() {
+        let mut world = start_world();
+        let mut mill = fallback_location((1, 2, 0));
+        mill.name = "Old Mill".to_string();
+        world.locations.insert((1, 2, 0), mill);
+
+        let prompt = build_location_prompt(&world, (0, 1, 0), "north");
+        assert!(prompt.contains("Village Square"));
+        assert!(prompt.contains("To the northeast is 'Old Mill'"));
+        assert!(prompt.contains("HARD CONSTRAINTS"));
+        assert!(!prompt.contains("exits"));
+    }
+
+    #[test]
+    fn test_validate_location_repairs_output() {
+        let world = start_world();
+        let mut location = fallback_location((0, 1, 0));
+        location.name = "  ".to_string();
+        location.image_prompt = String::new();
+        location.items.push("ghost_item".to_string());
+        location.exits.insert("north".to_string(), Exit::Open { to: (5, 5, 0) });
+
+        let notes = validate_location(&world, &mut location, (0, 1, 0));
+        assert_eq!(notes.len(), 3);
+        assert_eq!(location.name, "Mysterious area (0, 1, 0)");
+        assert!(location.items.is_empty());
+        assert!(location.exits.is_empty());
+        assert_eq!(location.image_prompt, location.description);
+    }
+
+    #[test]
+    fn test_place_generated_registers_items_and_actors() {
+        let mut world = start_world();
+        world.max_items = 2;
+        world.items.insert("lantern".to_string(), Item {
+            id: "lantern".to_string(),
+            name: "Lantern".to_string(),
+            description: String::new(),
+            item_type: ItemType::Tool,
+            state: ItemState::Normal,
+            properties: ItemProperties::default(),
+        });
+
+        let spec = |name: &str| ItemSpec {
+            id: None,
+            name: name.to_string(),
+            description: String::new(),
+            item_type: ItemType::Tool,
+            properties: ItemProperties::default(),
+        };
+        let mut generated = GeneratedLocation::from(fallback_location((0, 1, 0)));
+        generated.items = vec![spec("Lantern"), spec("Rope")];
+        generated.actors = vec![ActorSpec { id: None, name: "Old Hermit".to_string(), description: String::new(), money: 3, faction: None }];
+
+        let notes = place_generated(&mut world, generated, (0, 1, 0));
+        let room = &world.locations[&(0, 1, 0)];
+        assert_eq!(room.items, vec!["lantern_2".to_string()]);
+        assert_eq!(world.items.len(), 2);
+        assert!(notes.iter().any(|n| n.contains("budget")));
+        assert_eq!(room.actors, vec!["old_hermit".to_string()]);
+        assert_eq!(world.actors["old_hermit"].current_pos, (0, 1, 0));
+    }
+
+    #[test]
+    fn test_budget_skips_nameless_items_and_unreached_rooms() {
+        let mut world = start_world();
+        world.max_items = world.items.len() as u32 + 1;
+        let spec = |name: &str| ItemSpec {
+            id: None,
+            name: name.to_string(),
+            description: String::new(),
+            item_type: ItemType::Material,
+            properties: ItemProperties::default(),
+        };
+        let mut generated = GeneratedLocation::from(fallback_location((0, 1, 0)));
+        generated.items = vec![spec("  "), spec("Rope")];
+
+        let notes = place_generated(&mut world, generated, (0, 1, 0));
+        assert_eq!(world.locations[&(0, 1, 0)].items, vec!["rope".to_string()]);
+        assert!(!notes.iter().any(|n| n.contains("budget")));
+        // The rope waits in a room the player hasn't reached, so it doesn't count yet.
+        assert_eq!(item_budget(&world), 1);
+        world.locations.get_mut(&(0, 1, 0)).unwrap().visited = true;
+        assert_eq!(item_budget(&world), 0);
+    }
+
+    #[test]
+    fn test_regions_are_founded_joined_and_kept_contiguous() {
+        let mut world = start_world();
+        let new_region = RegionSpec::New {
+            name: "Blackwood".to_string(),
+            description: "An old dark forest".to_string(),
+            lore: String::new(),
+        };
+        assign_region(&mut world, (0, 0, 0), Some(new_region));
+        assert_eq!(world.region_at((0, 0, 0)).map(|r| r.name.as_str()), Some("Blackwood"));
+
+        assign_region(&mut world, (1, 1, 0), Some(RegionSpec::Existing("blackwood".to_string())));
+        assert_eq!(world.regions["blackwood"].tiles, vec![(0, 0, 0), (1, 1, 0)]);
+
+        let notes = assign_region(&mut world, (9, 9, 0), Some(RegionSpec::Existing("Blackwood".to_string())));
+        assert!(world.region_at((9, 9, 0)).is_none());
+        assert!(notes[0].contains("does not border"));
+
+        let prompt = build_location_prompt(&world, (2, 2, 0), "northeast");
+        assert!(prompt.contains("- 'Blackwood' (to the southwest): An old dark forest"));
+    }
+
+    #[test]
+    fn test_unassigned_tile_joins_bordering_region_of_same_biome() {
+        let mut world = start_world();
+        world.regions.insert("vale".to_string(), Region {
+            id: "vale".to_string(),
+            name: "The Vale".to_string(),
+            description: String::new(),
+            lore: String::new(),
+            // Below ground every tile is cavern, so the biomes always match.
+            tiles: vec![(0, 0, -1)],
+        });
+        assign_region(&mut world, (1, 0, -1), None);
+        assert_eq!(world.region_at((1, 0, -1)).map(|r| r.id.as_str()), Some("vale"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_location_falls_back_and_links() {
+        let llm_client = LlmClient::new("http://127.0.0.1:9".to_string(), "test".to_string());
+        let mut world = start_world();
+
+        let report = generate_location(&llm_client, &mut world, (0, 1, 0), "north").await;
+        assert!(report.used_fallback);
+        assert_eq!(world.current_pos, (0, 0, 0));
+        assert_eq!(world.locations[&(0, 0, 0)].exits.get("north"), Some(&Exit::Open { to: (0, 1, 0) }));
+        assert_eq!(world.locations[&(0, 1, 0)].exits.get("south"), Some(&Exit::Open { to: (0, 0, 0) }));
+    }
+}